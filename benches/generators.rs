@@ -18,11 +18,12 @@ use std::mem::size_of;
 use test::{black_box, Bencher};
 
 use rand::prelude::*;
-use rand::rngs::adapter::ReseedingRng;
-use rand::rngs::{mock::StepRng, OsRng};
-use rand_chacha::{ChaCha12Rng, ChaCha20Core, ChaCha20Rng, ChaCha8Rng};
-use rand_hc::Hc128Rng;
-use rand_pcg::{Pcg32, Pcg64, Pcg64Mcg};
+use rand::rngs::adapter::{BufferedRng, ReseedingRng};
+use rand::rngs::{mock::StepRng, OsRng, ShardedRng, SharedRng};
+use rand_chacha::{Backend, ChaCha12Rng, ChaCha20Core, ChaCha20Rng, ChaCha8Rng};
+use rand_core::block::{BlockRng, BlockRngCore};
+use rand_hc::{Hc128Core, Hc128Rng};
+use rand_pcg::{Pcg32, Pcg32Mcg, Pcg64, Pcg64Dxsm, Pcg64Mcg};
 
 macro_rules! gen_bytes {
     ($fnn:ident, $gen:expr) => {
@@ -44,7 +45,9 @@ macro_rules! gen_bytes {
 gen_bytes!(gen_bytes_step, StepRng::new(0, 1));
 gen_bytes!(gen_bytes_pcg32, Pcg32::from_entropy());
 gen_bytes!(gen_bytes_pcg64, Pcg64::from_entropy());
+gen_bytes!(gen_bytes_pcg32mcg, Pcg32Mcg::from_entropy());
 gen_bytes!(gen_bytes_pcg64mcg, Pcg64Mcg::from_entropy());
+gen_bytes!(gen_bytes_pcg64dxsm, Pcg64Dxsm::from_entropy());
 gen_bytes!(gen_bytes_chacha8, ChaCha8Rng::from_entropy());
 gen_bytes!(gen_bytes_chacha12, ChaCha12Rng::from_entropy());
 gen_bytes!(gen_bytes_chacha20, ChaCha20Rng::from_entropy());
@@ -54,6 +57,106 @@ gen_bytes!(gen_bytes_std, StdRng::from_entropy());
 gen_bytes!(gen_bytes_small, SmallRng::from_entropy());
 gen_bytes!(gen_bytes_os, OsRng);
 
+// Requests smaller than the chunk size of `BufferedRng`, which it serves from
+// its buffer
+macro_rules! gen_bytes_small {
+    ($fnn:ident, $len:expr, $gen:expr) => {
+        #[bench]
+        fn $fnn(b: &mut Bencher) {
+            let mut rng = $gen;
+            let mut buf = [0u8; $len];
+            b.iter(|| {
+                for _ in 0..RAND_BENCH_N {
+                    rng.fill_bytes(&mut buf);
+                    black_box(buf);
+                }
+            });
+            b.bytes = $len as u64 * RAND_BENCH_N;
+        }
+    };
+}
+
+gen_bytes_small!(gen_bytes_16_os, 16, OsRng);
+gen_bytes_small!(gen_bytes_16_os_buffered, 16, BufferedRng::new(OsRng));
+gen_bytes_small!(gen_bytes_64_os, 64, OsRng);
+gen_bytes_small!(gen_bytes_64_os_buffered, 64, BufferedRng::new(OsRng));
+
+// ChaCha20 with each of the ChaCha backends; unsupported backends are skipped
+macro_rules! gen_bytes_chacha_backend {
+    ($fnn:ident, $backend:expr) => {
+        #[bench]
+        fn $fnn(b: &mut Bencher) {
+            if rand_chacha::force_backend($backend).is_err() {
+                return;
+            }
+            let mut rng = ChaCha20Rng::from_entropy();
+            let mut buf = [0u8; BYTES_LEN];
+            b.iter(|| {
+                for _ in 0..RAND_BENCH_N {
+                    rng.fill_bytes(&mut buf);
+                    black_box(buf);
+                }
+            });
+            b.bytes = BYTES_LEN as u64 * RAND_BENCH_N;
+            rand_chacha::reset_backend();
+        }
+    };
+}
+
+gen_bytes_chacha_backend!(gen_bytes_chacha20_scalar, Backend::Scalar);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_generic, Backend::Generic);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_sse2, Backend::Sse2);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_ssse3, Backend::Ssse3);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_sse41, Backend::Sse41);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_avx, Backend::Avx);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_avx2, Backend::Avx2);
+gen_bytes_chacha_backend!(gen_bytes_chacha20_std_arch_sse2, Backend::StdArchSse2);
+
+const BULK_BYTES_LEN: usize = 1024 * 1024;
+const BULK_BENCH_N: u64 = 16;
+
+// A core which does not support `generate_into`, forcing `BlockRng` to copy
+// all output through its results buffer
+#[derive(Clone)]
+struct Buffered<R>(R);
+
+impl<R: BlockRngCore> BlockRngCore for Buffered<R> {
+    type Item = R::Item;
+    type Results = R::Results;
+
+    fn generate(&mut self, results: &mut Self::Results) {
+        self.0.generate(results)
+    }
+}
+
+macro_rules! gen_bytes_bulk {
+    ($fnn:ident, $gen:expr) => {
+        #[bench]
+        fn $fnn(b: &mut Bencher) {
+            let mut rng = $gen;
+            let mut buf = vec![0u8; BULK_BYTES_LEN];
+            b.iter(|| {
+                for _ in 0..BULK_BENCH_N {
+                    rng.fill_bytes(&mut buf);
+                    black_box(&buf);
+                }
+            });
+            b.bytes = BULK_BYTES_LEN as u64 * BULK_BENCH_N;
+        }
+    };
+}
+
+gen_bytes_bulk!(gen_bytes_1M_chacha20, BlockRng::new(ChaCha20Core::from_entropy()));
+gen_bytes_bulk!(
+    gen_bytes_1M_chacha20_buffered,
+    BlockRng::new(Buffered(ChaCha20Core::from_entropy()))
+);
+gen_bytes_bulk!(gen_bytes_1M_hc128, BlockRng::new(Hc128Core::from_entropy()));
+gen_bytes_bulk!(
+    gen_bytes_1M_hc128_buffered,
+    BlockRng::new(Buffered(Hc128Core::from_entropy()))
+);
+
 macro_rules! gen_uint {
     ($fnn:ident, $ty:ty, $gen:expr) => {
         #[bench]
@@ -74,7 +177,9 @@ macro_rules! gen_uint {
 gen_uint!(gen_u32_step, u32, StepRng::new(0, 1));
 gen_uint!(gen_u32_pcg32, u32, Pcg32::from_entropy());
 gen_uint!(gen_u32_pcg64, u32, Pcg64::from_entropy());
+gen_uint!(gen_u32_pcg32mcg, u32, Pcg32Mcg::from_entropy());
 gen_uint!(gen_u32_pcg64mcg, u32, Pcg64Mcg::from_entropy());
+gen_uint!(gen_u32_pcg64dxsm, u32, Pcg64Dxsm::from_entropy());
 gen_uint!(gen_u32_chacha8, u32, ChaCha8Rng::from_entropy());
 gen_uint!(gen_u32_chacha12, u32, ChaCha12Rng::from_entropy());
 gen_uint!(gen_u32_chacha20, u32, ChaCha20Rng::from_entropy());
@@ -83,11 +188,14 @@ gen_uint!(gen_u32_std, u32, StdRng::from_entropy());
 #[cfg(feature = "small_rng")]
 gen_uint!(gen_u32_small, u32, SmallRng::from_entropy());
 gen_uint!(gen_u32_os, u32, OsRng);
+gen_uint!(gen_u32_os_buffered, u32, BufferedRng::new(OsRng));
 
 gen_uint!(gen_u64_step, u64, StepRng::new(0, 1));
 gen_uint!(gen_u64_pcg32, u64, Pcg32::from_entropy());
 gen_uint!(gen_u64_pcg64, u64, Pcg64::from_entropy());
+gen_uint!(gen_u64_pcg32mcg, u64, Pcg32Mcg::from_entropy());
 gen_uint!(gen_u64_pcg64mcg, u64, Pcg64Mcg::from_entropy());
+gen_uint!(gen_u64_pcg64dxsm, u64, Pcg64Dxsm::from_entropy());
 gen_uint!(gen_u64_chacha8, u64, ChaCha8Rng::from_entropy());
 gen_uint!(gen_u64_chacha12, u64, ChaCha12Rng::from_entropy());
 gen_uint!(gen_u64_chacha20, u64, ChaCha20Rng::from_entropy());
@@ -96,6 +204,7 @@ gen_uint!(gen_u64_std, u64, StdRng::from_entropy());
 #[cfg(feature = "small_rng")]
 gen_uint!(gen_u64_small, u64, SmallRng::from_entropy());
 gen_uint!(gen_u64_os, u64, OsRng);
+gen_uint!(gen_u64_os_buffered, u64, BufferedRng::new(OsRng));
 
 macro_rules! init_gen {
     ($fnn:ident, $gen:ident) => {
@@ -113,6 +222,7 @@ macro_rules! init_gen {
 init_gen!(init_pcg32, Pcg32);
 init_gen!(init_pcg64, Pcg64);
 init_gen!(init_pcg64mcg, Pcg64Mcg);
+init_gen!(init_pcg64dxsm, Pcg64Dxsm);
 init_gen!(init_hc128, Hc128Rng);
 init_gen!(init_chacha, ChaCha20Rng);
 
@@ -163,3 +273,68 @@ macro_rules! threadrng_uint {
 
 threadrng_uint!(thread_rng_u32, u32);
 threadrng_uint!(thread_rng_u64, u64);
+
+
+// `$threads` threads each generating `RAND_BENCH_N` values from a shared
+// generator. This includes the cost of spawning the threads, which
+// `shared_contention_thread_rng` measures (using a thread-local generator in
+// each thread) as a baseline.
+macro_rules! shared_contention {
+    ($fnn:ident, $threads:expr, $gen:expr) => {
+        #[bench]
+        fn $fnn(b: &mut Bencher) {
+            let rng = $gen;
+            b.iter(|| {
+                let handles: Vec<_> = (0..$threads)
+                    .map(|_| {
+                        let mut rng = rng.clone();
+                        std::thread::spawn(move || {
+                            let mut accum: u64 = 0;
+                            for _ in 0..RAND_BENCH_N {
+                                accum = accum.wrapping_add(rng.gen::<u64>());
+                            }
+                            accum
+                        })
+                    })
+                    .collect();
+                for handle in handles {
+                    black_box(handle.join().unwrap());
+                }
+            });
+            b.bytes = size_of::<u64>() as u64 * RAND_BENCH_N * $threads;
+        }
+    };
+}
+
+shared_contention!(shared_contention_thread_rng_4, 4, ThreadRngFactory);
+shared_contention!(shared_contention_mutex_1, 1, SharedRng::new(StdRng::from_entropy()));
+shared_contention!(shared_contention_mutex_4, 4, SharedRng::new(StdRng::from_entropy()));
+shared_contention!(shared_contention_sharded_1, 1,
+    ShardedRng::<StdRng>::from_rng(4, thread_rng()).unwrap());
+shared_contention!(shared_contention_sharded_4, 4,
+    ShardedRng::<StdRng>::from_rng(4, thread_rng()).unwrap());
+// More threads than shards, so that new generators are forked at times
+shared_contention!(shared_contention_sharded_8, 8,
+    ShardedRng::<StdRng>::from_rng(4, thread_rng()).unwrap());
+
+// Gives every thread its own `ThreadRng` in `shared_contention`.
+#[derive(Clone)]
+struct ThreadRngFactory;
+
+impl RngCore for ThreadRngFactory {
+    fn next_u32(&mut self) -> u32 {
+        thread_rng().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        thread_rng().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        thread_rng().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        thread_rng().try_fill_bytes(dest)
+    }
+}
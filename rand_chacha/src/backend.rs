@@ -0,0 +1,477 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Selection of the implementation used to generate ChaCha blocks.
+
+use crate::guts::{ChaCha, BLOCK, BUFSZ};
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+// The x86-64 backends of ppv-lite86 are available under the same conditions as
+// its `x86_64` module.
+const PPV_X86_64: bool = cfg!(all(
+    target_arch = "x86_64",
+    target_feature = "sse2",
+    not(miri)
+));
+
+macro_rules! ppv_x86_64_feature {
+    ($feature:tt) => {{
+        #[cfg(all(
+            target_arch = "x86_64",
+            target_feature = "sse2",
+            not(miri),
+            feature = "std"
+        ))]
+        let supported = std::is_x86_feature_detected!($feature);
+        #[cfg(all(
+            target_arch = "x86_64",
+            target_feature = "sse2",
+            not(miri),
+            not(feature = "std")
+        ))]
+        let supported = cfg!(target_feature = $feature);
+        #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2", not(miri))))]
+        let supported = false;
+        supported
+    }};
+}
+
+/// An implementation of the ChaCha block function.
+///
+/// All backends produce identical output and differ only in speed. By default
+/// the fastest backend supported by the CPU is used; this is detected at
+/// runtime with the `std` feature, and from the enabled target features
+/// otherwise. A specific backend may be selected with [`force_backend`], e.g.
+/// for benchmarking.
+///
+/// There is currently no AVX-512 backend: `ppv-lite86` does not provide one,
+/// and the corresponding intrinsics are not available on our MSRV.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Plain Rust implementation without explicit vectorisation, available
+    /// on all platforms.
+    Scalar,
+    /// `ppv-lite86`'s portable implementation, used on platforms without one
+    /// of the x86-64 backends below.
+    Generic,
+    /// `ppv-lite86` using SSE2 (x86-64).
+    Sse2,
+    /// `ppv-lite86` using SSSE3 (x86-64).
+    Ssse3,
+    /// `ppv-lite86` using SSE4.1 (x86-64).
+    Sse41,
+    /// `ppv-lite86` using AVX (x86-64).
+    Avx,
+    /// `ppv-lite86` using AVX2 (x86-64).
+    Avx2,
+    /// SSE2 implementation using `std::arch` intrinsics directly, computing
+    /// the four blocks of a buffer in parallel lanes (x86-64).
+    StdArchSse2,
+}
+
+const BACKENDS: [Backend; 8] = [
+    Backend::Scalar,
+    Backend::Generic,
+    Backend::Sse2,
+    Backend::Ssse3,
+    Backend::Sse41,
+    Backend::Avx,
+    Backend::Avx2,
+    Backend::StdArchSse2,
+];
+
+impl Backend {
+    /// All backends, whether or not they are supported on this platform.
+    pub fn all() -> &'static [Backend] {
+        &BACKENDS
+    }
+
+    /// Whether this backend is available on this platform and CPU.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            Backend::Generic => !PPV_X86_64,
+            Backend::Sse2 | Backend::StdArchSse2 => PPV_X86_64,
+            Backend::Ssse3 => ppv_x86_64_feature!("ssse3"),
+            Backend::Sse41 => ppv_x86_64_feature!("sse4.1"),
+            Backend::Avx => ppv_x86_64_feature!("avx"),
+            Backend::Avx2 => ppv_x86_64_feature!("avx2"),
+        }
+    }
+
+    /// The backend used unless another one is forced: the fastest supported
+    /// backend.
+    pub fn detect() -> Backend {
+        if !PPV_X86_64 {
+            Backend::Generic
+        } else if Backend::Avx2.is_supported() {
+            Backend::Avx2
+        } else if Backend::Avx.is_supported() {
+            Backend::Avx
+        } else if Backend::Sse41.is_supported() {
+            Backend::Sse41
+        } else if Backend::Ssse3.is_supported() {
+            Backend::Ssse3
+        } else {
+            Backend::Sse2
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        BACKENDS.iter().position(|&b| b == self).unwrap() as u8 + 1
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Backend::Scalar => "scalar",
+            Backend::Generic => "generic",
+            Backend::Sse2 => "SSE2",
+            Backend::Ssse3 => "SSSE3",
+            Backend::Sse41 => "SSE4.1",
+            Backend::Avx => "AVX",
+            Backend::Avx2 => "AVX2",
+            Backend::StdArchSse2 => "std::arch SSE2",
+        })
+    }
+}
+
+/// Error type returned from [`force_backend`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsupportedBackend(pub Backend);
+
+impl fmt::Display for UnsupportedBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ChaCha backend {} is not supported on this platform",
+            self.0
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnsupportedBackend {}
+
+// The selected backend, encoded by `Backend::to_u8`, or 0 if not yet selected.
+static SELECTED: AtomicU8 = AtomicU8::new(0);
+
+/// Get the backend used to generate ChaCha blocks.
+pub fn active_backend() -> Backend {
+    match SELECTED.load(Ordering::Relaxed) {
+        0 => {
+            // Don't overwrite a backend forced by another thread meanwhile
+            let detected = Backend::detect().to_u8();
+            let result =
+                SELECTED.compare_exchange(0, detected, Ordering::Relaxed, Ordering::Relaxed);
+            let n = match result {
+                Ok(_) => detected,
+                Err(n) => n,
+            };
+            BACKENDS[usize::from(n) - 1]
+        }
+        n => BACKENDS[usize::from(n) - 1],
+    }
+}
+
+/// Force the use of a specific backend to generate ChaCha blocks.
+///
+/// This affects all ChaCha generators of the process, on all threads. Since
+/// all backends produce the same output, it is safe to switch backends while
+/// generators are in use. Fails if the backend is not supported.
+pub fn force_backend(backend: Backend) -> Result<(), UnsupportedBackend> {
+    if !backend.is_supported() {
+        return Err(UnsupportedBackend(backend));
+    }
+    SELECTED.store(backend.to_u8(), Ordering::Relaxed);
+    Ok(())
+}
+
+/// Undo [`force_backend`], using the fastest supported backend again.
+pub fn reset_backend() {
+    SELECTED.store(0, Ordering::Relaxed);
+}
+
+/// Produce 4 blocks of output with the active backend, advancing the state.
+#[inline]
+pub(crate) fn refill_wide(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+    refill_wide_with(active_backend(), state, drounds, out)
+}
+
+#[inline(always)]
+fn refill_wide_with(backend: Backend, state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2", not(miri)))]
+    unsafe {
+        // Safety: the backend is only selected if the required features are supported
+        match backend {
+            Backend::Sse2 => return ppv::refill_wide_sse2(state, drounds, out),
+            Backend::Ssse3 => return ppv::refill_wide_ssse3(state, drounds, out),
+            Backend::Sse41 => return ppv::refill_wide_sse41(state, drounds, out),
+            Backend::Avx => return ppv::refill_wide_avx(state, drounds, out),
+            Backend::Avx2 => return ppv::refill_wide_avx2(state, drounds, out),
+            Backend::StdArchSse2 => return sse2::refill_wide(state, drounds, out),
+            Backend::Scalar | Backend::Generic => (),
+        }
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2", not(miri))))]
+    {
+        if backend == Backend::Generic {
+            return crate::guts::refill_wide_generic(state, drounds, out);
+        }
+    }
+    scalar::refill_wide(state, drounds, out)
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", not(miri)))]
+mod ppv {
+    use crate::guts::{refill_wide_impl, ChaCha, BUFSZ};
+    use core::arch::x86_64::_mm256_zeroupper;
+    use ppv_lite86::x86_64::{AVX, AVX2, SSE2, SSE41, SSSE3};
+    use ppv_lite86::Machine;
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn refill_wide_sse2(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+        refill_wide_impl(SSE2::instance(), state, drounds, out)
+    }
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn refill_wide_ssse3(
+        state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ],
+    ) {
+        refill_wide_impl(SSSE3::instance(), state, drounds, out)
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn refill_wide_sse41(
+        state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ],
+    ) {
+        refill_wide_impl(SSE41::instance(), state, drounds, out)
+    }
+
+    #[target_feature(enable = "avx")]
+    #[target_feature(enable = "sse4.1")]
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn refill_wide_avx(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+        refill_wide_impl(AVX::instance(), state, drounds, out);
+        _mm256_zeroupper();
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn refill_wide_avx2(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+        refill_wide_impl(AVX2::instance(), state, drounds, out);
+        _mm256_zeroupper();
+    }
+}
+
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+mod scalar {
+    use super::{BLOCK, CONSTANTS};
+    use crate::guts::{ChaCha, BUFSZ};
+
+    #[inline(always)]
+    fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        x[a] = x[a].wrapping_add(x[b]);
+        x[d] = (x[d] ^ x[a]).rotate_left(16);
+        x[c] = x[c].wrapping_add(x[d]);
+        x[b] = (x[b] ^ x[c]).rotate_left(12);
+        x[a] = x[a].wrapping_add(x[b]);
+        x[d] = (x[d] ^ x[a]).rotate_left(8);
+        x[c] = x[c].wrapping_add(x[d]);
+        x[b] = (x[b] ^ x[c]).rotate_left(7);
+    }
+
+    pub(super) fn refill_wide(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+        let b: [u32; 4] = state.b.into();
+        let c: [u32; 4] = state.c.into();
+        let d: [u32; 4] = state.d.into();
+        let mut input = [0u32; 16];
+        input[..4].copy_from_slice(&CONSTANTS);
+        input[4..8].copy_from_slice(&b);
+        input[8..12].copy_from_slice(&c);
+        input[12..].copy_from_slice(&d);
+
+        let mut pos = u64::from(d[0]) | (u64::from(d[1]) << 32);
+        for block in out.chunks_exact_mut(BLOCK) {
+            input[12] = pos as u32;
+            input[13] = (pos >> 32) as u32;
+            let mut x = input;
+            for _ in 0..drounds {
+                quarter_round(&mut x, 0, 4, 8, 12);
+                quarter_round(&mut x, 1, 5, 9, 13);
+                quarter_round(&mut x, 2, 6, 10, 14);
+                quarter_round(&mut x, 3, 7, 11, 15);
+                quarter_round(&mut x, 0, 5, 10, 15);
+                quarter_round(&mut x, 1, 6, 11, 12);
+                quarter_round(&mut x, 2, 7, 8, 13);
+                quarter_round(&mut x, 3, 4, 9, 14);
+            }
+            for ((word, &x), &input) in block.chunks_exact_mut(4).zip(&x).zip(&input) {
+                word.copy_from_slice(&x.wrapping_add(input).to_le_bytes());
+            }
+            pos = pos.wrapping_add(1);
+        }
+        state.d = [pos as u32, (pos >> 32) as u32, d[2], d[3]].into();
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", not(miri)))]
+mod sse2 {
+    use super::{BLOCK, CONSTANTS};
+    use crate::guts::{ChaCha, BUFSZ};
+    use core::arch::x86_64::*;
+
+    macro_rules! rotate_left {
+        ($x:expr, $left:expr, $right:expr) => {
+            _mm_or_si128(_mm_slli_epi32($x, $left), _mm_srli_epi32($x, $right))
+        };
+    }
+
+    #[inline(always)]
+    unsafe fn quarter_round(x: &mut [__m128i; 16], a: usize, b: usize, c: usize, d: usize) {
+        x[a] = _mm_add_epi32(x[a], x[b]);
+        x[d] = rotate_left!(_mm_xor_si128(x[d], x[a]), 16, 16);
+        x[c] = _mm_add_epi32(x[c], x[d]);
+        x[b] = rotate_left!(_mm_xor_si128(x[b], x[c]), 12, 20);
+        x[a] = _mm_add_epi32(x[a], x[b]);
+        x[d] = rotate_left!(_mm_xor_si128(x[d], x[a]), 8, 24);
+        x[c] = _mm_add_epi32(x[c], x[d]);
+        x[b] = rotate_left!(_mm_xor_si128(x[b], x[c]), 7, 25);
+    }
+
+    /// Each vector holds one word of the state, for each of the four blocks.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn refill_wide(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+        let b: [u32; 4] = state.b.into();
+        let c: [u32; 4] = state.c.into();
+        let d: [u32; 4] = state.d.into();
+        let pos = u64::from(d[0]) | (u64::from(d[1]) << 32);
+        let mut lo = [0u32; 4];
+        let mut hi = [0u32; 4];
+        for i in 0..4 {
+            let p = pos.wrapping_add(i as u64);
+            lo[i] = p as u32;
+            hi[i] = (p >> 32) as u32;
+        }
+
+        let mut input = [_mm_setzero_si128(); 16];
+        for i in 0..4 {
+            input[i] = _mm_set1_epi32(CONSTANTS[i] as i32);
+            input[4 + i] = _mm_set1_epi32(b[i] as i32);
+            input[8 + i] = _mm_set1_epi32(c[i] as i32);
+        }
+        input[12] = _mm_loadu_si128(lo.as_ptr() as *const __m128i);
+        input[13] = _mm_loadu_si128(hi.as_ptr() as *const __m128i);
+        input[14] = _mm_set1_epi32(d[2] as i32);
+        input[15] = _mm_set1_epi32(d[3] as i32);
+
+        let mut x = input;
+        for _ in 0..drounds {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        for (x, input) in x.iter_mut().zip(&input) {
+            *x = _mm_add_epi32(*x, *input);
+        }
+
+        // Transpose each group of four words from word-major to block-major
+        // order; x86-64 is little-endian, so words are stored as required.
+        let out = out.as_mut_ptr();
+        for group in 0..4 {
+            let r = &x[group * 4..group * 4 + 4];
+            let a0 = _mm_unpacklo_epi32(r[0], r[1]);
+            let a1 = _mm_unpacklo_epi32(r[2], r[3]);
+            let a2 = _mm_unpackhi_epi32(r[0], r[1]);
+            let a3 = _mm_unpackhi_epi32(r[2], r[3]);
+            let blocks = [
+                _mm_unpacklo_epi64(a0, a1),
+                _mm_unpackhi_epi64(a0, a1),
+                _mm_unpacklo_epi64(a2, a3),
+                _mm_unpackhi_epi64(a2, a3),
+            ];
+            for (i, block) in blocks.iter().enumerate() {
+                let dst = out.add(i * BLOCK + group * 16) as *mut __m128i;
+                _mm_storeu_si128(dst, *block);
+            }
+        }
+
+        let pos = pos.wrapping_add(4);
+        state.d = [pos as u32, (pos >> 32) as u32, d[2], d[3]].into();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn refill_all(state: &ChaCha, drounds: u32) -> [u8; BUFSZ] {
+        let mut expected_state = state.clone();
+        let mut expected = [0u8; BUFSZ];
+        scalar::refill_wide(&mut expected_state, drounds, &mut expected);
+        for &backend in Backend::all().iter().filter(|b| b.is_supported()) {
+            let mut state = state.clone();
+            let mut out = [0u8; BUFSZ];
+            refill_wide_with(backend, &mut state, drounds, &mut out);
+            assert!(
+                out[..] == expected[..],
+                "output mismatch for backend {}",
+                backend
+            );
+            assert!(
+                state == expected_state,
+                "state mismatch for backend {}",
+                backend
+            );
+        }
+        expected
+    }
+
+    #[test]
+    #[allow(clippy::legacy_numeric_constants)] // `u64::MAX` requires Rust 1.43
+    fn test_backends_cross_check() {
+        let mut key = [0u8; 32];
+        for (i, k) in key.iter_mut().enumerate() {
+            *k = (i as u8).wrapping_mul(37);
+        }
+        let mut state = ChaCha::new(&key, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        for &drounds in [4, 6, 10].iter() {
+            for _ in 0..3 {
+                refill_all(&state, drounds);
+                refill_wide_with(Backend::Scalar, &mut state, drounds, &mut [0u8; BUFSZ]);
+            }
+        }
+
+        // The block counter wraps within the buffer
+        for &pos in [u64::max_value() - 1, u64::from(u32::max_value()) - 2].iter() {
+            state.set_stream_param(0, pos);
+            refill_all(&state, 10);
+        }
+    }
+
+    #[test]
+    fn test_backends_true_values() {
+        // Test vector 1 from
+        // https://tools.ietf.org/html/draft-nir-cfrg-chacha20-poly1305-04
+        let state = ChaCha::new(&[0u8; 32], &[0u8; 8]);
+        let out = refill_all(&state, 10);
+        let expected = [
+            0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90, 0x40, 0x5d, 0x6a, 0xe5, 0x53, 0x86,
+            0xbd, 0x28,
+        ];
+        assert_eq!(out[..16], expected);
+    }
+}
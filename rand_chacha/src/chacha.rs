@@ -14,7 +14,13 @@
 use self::core::fmt;
 use crate::guts::ChaCha;
 use rand_core::block::{BlockRng, BlockRngCore};
-use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
+#[cfg(not(target_os = "emscripten"))] use rand_core::block::SeekableBlockRngCore;
+use rand_core::impls::mix64;
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
+use rand_core::{CryptoRng, Error, RngCore, SeedableRng, SplittableRng};
+#[cfg(not(target_os = "emscripten"))] use rand_core::SeekableRng;
+#[cfg(feature = "serde1")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 const STREAM_PARAM_NONCE: u32 = 1;
 const STREAM_PARAM_BLOCK: u32 = 0;
@@ -24,6 +30,16 @@ const BUF_BLOCKS: u8 = 4;
 // number of 32-bit words per ChaCha block (fixed by algorithm definition)
 const BLOCK_WORDS: u8 = 16;
 
+// The serialized form of `ChaChaXRng`: like the snapshot state, it holds the position in the
+// stream rather than the output buffer.
+#[cfg(feature = "serde1")]
+#[derive(Serialize, Deserialize)]
+struct ChaChaState {
+    seed: [u8; 32],
+    stream: u64,
+    word_pos: u128,
+}
+
 pub struct Array64<T>([T; 64]);
 impl<T> Default for Array64<T>
 where T: Default
@@ -68,7 +84,7 @@ impl<T> fmt::Debug for Array64<T> {
 }
 
 macro_rules! chacha_impl {
-    ($ChaChaXCore:ident, $ChaChaXRng:ident, $rounds:expr, $doc:expr) => {
+    ($ChaChaXCore:ident, $ChaChaXRng:ident, $rounds:expr, $doc:expr, $algorithm:expr) => {
         #[doc=$doc]
         #[derive(Clone, PartialEq, Eq)]
         pub struct $ChaChaXCore {
@@ -95,6 +111,52 @@ macro_rules! chacha_impl {
                     *x = x.to_le();
                 }
             }
+
+            #[inline]
+            fn generate_into(&mut self, dest: &mut [u8]) -> usize {
+                // The byte output of `refill4` is what `generate` reads as little-endian words.
+                let chunk_len = usize::from(BUF_BLOCKS) * usize::from(BLOCK_WORDS) * 4;
+                let mut len = 0;
+                for chunk in dest.chunks_exact_mut(chunk_len) {
+                    self.state.refill4($rounds, unsafe {
+                        &mut *(chunk.as_mut_ptr() as *mut [u8; 256])
+                    });
+                    len += chunk_len;
+                }
+                len
+            }
+        }
+
+        impl $ChaChaXCore {
+            // The word position `buffered` words before the end of the generated blocks. The
+            // block counter is 64 bits wide, so positions are 68-bit numbers.
+            #[inline]
+            fn buffer_word_pos(&self, buffered: usize) -> u128 {
+                let block = self.state.get_stream_param(STREAM_PARAM_BLOCK);
+                let end = u128::from(block) * u128::from(BLOCK_WORDS);
+                end.wrapping_sub(buffered as u128) & ((1 << 68) - 1)
+            }
+
+            // Seek to the block containing `pos`, and return the index of `pos` in it.
+            #[inline]
+            fn seek_word_pos(&mut self, pos: u128) -> usize {
+                let block = (pos / u128::from(BLOCK_WORDS)) as u64;
+                self.state.set_stream_param(STREAM_PARAM_BLOCK, block);
+                (pos % u128::from(BLOCK_WORDS)) as usize
+            }
+        }
+
+        #[cfg(not(target_os = "emscripten"))]
+        impl SeekableBlockRngCore for $ChaChaXCore {
+            #[inline]
+            fn word_pos(&self, buffered: usize) -> u128 {
+                self.buffer_word_pos(buffered)
+            }
+
+            #[inline]
+            fn set_word_pos(&mut self, pos: u128) -> usize {
+                self.seek_word_pos(pos)
+            }
         }
 
         impl SeedableRng for $ChaChaXCore {
@@ -140,6 +202,9 @@ macro_rules! chacha_impl {
         /// This implementation uses an output buffer of sixteen `u32` words, and uses
         /// [`BlockRng`] to implement the [`RngCore`] methods.
         ///
+        /// With the `serde1` feature the generator is serialized as its seed, stream and word
+        /// position; deserializing regenerates the current block of the buffer.
+        ///
         /// [^1]: D. J. Bernstein, [*ChaCha, a variant of Salsa20*](
         ///       https://cr.yp.to/chacha.html)
         ///
@@ -180,7 +245,152 @@ macro_rules! chacha_impl {
             }
         }
 
+        // The position is the 68-bit word position of `get_word_pos`.
+        #[cfg(not(target_os = "emscripten"))]
+        impl SeekableRng for $ChaChaXRng {
+            type Position = u128;
+
+            #[inline]
+            fn get_position(&self) -> u128 {
+                self.rng.get_position()
+            }
+
+            #[inline]
+            fn set_position(&mut self, pos: u128) {
+                self.rng.set_position(pos)
+            }
+
+            #[inline]
+            fn advance(&mut self, delta: u128) {
+                self.rng.advance(delta)
+            }
+        }
+
+        /// Children have the same key as their parent, and start at the beginning of a stream
+        /// derived from the stream of the parent and the index. Children with different indices
+        /// use different streams; 2<sup>64</sup> streams are available, so that overlapping streams
+        /// only become likely for trees of about 2<sup>32</sup> generators.
+        impl SplittableRng for $ChaChaXRng {
+            fn fork(&self, index: u64) -> Self {
+                let stream = mix64(self.get_stream() ^ mix64(index ^ 0x9e3779b97f4a7c15));
+                let mut child = Self::from_seed(self.get_seed());
+                child.set_stream(stream);
+                child
+            }
+        }
+
+        /// The state consists of the seed, the stream number (as `u64`) and the word position
+        /// (as `u128`, written as two `u64` words, low word first, since `write_u128` is not
+        /// available on Emscripten).
+        impl SnapshotState for $ChaChaXRng {
+            const STATE_LEN: usize = 32 + 8 + 16;
+
+            fn write_state(&self, w: &mut StateWriter) {
+                w.write_bytes(&self.get_seed());
+                w.write_u64(self.get_stream());
+                let word_pos = self.get_word_pos();
+                w.write_u64(word_pos as u64);
+                w.write_u64((word_pos >> 64) as u64);
+            }
+
+            fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+                let mut seed = [0; 32];
+                r.read_bytes(&mut seed);
+                let stream = r.read_u64();
+                let low = r.read_u64();
+                let high = r.read_u64();
+                let word_pos = (u128::from(high) << 64) | u128::from(low);
+                if word_pos >> 68 != 0 {
+                    return Err(SnapshotError::InvalidState);
+                }
+                let mut rng = Self::from_seed(seed);
+                rng.set_stream(stream);
+                rng.set_word_pos(word_pos);
+                Ok(rng)
+            }
+        }
+
+        impl Snapshot for $ChaChaXRng {
+            const ALGORITHM: &'static str = $algorithm;
+            const VERSION: u16 = 1;
+        }
+
+        #[cfg(feature = "serde1")]
+        impl Serialize for $ChaChaXRng {
+            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                ChaChaState {
+                    seed: self.get_seed(),
+                    stream: self.get_stream(),
+                    word_pos: self.get_word_pos(),
+                }
+                .serialize(s)
+            }
+        }
+
+        #[cfg(feature = "serde1")]
+        impl<'de> Deserialize<'de> for $ChaChaXRng {
+            fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                let state = ChaChaState::deserialize(d)?;
+                if state.word_pos >> 68 != 0 {
+                    return Err(de::Error::custom("word position exceeds 68 bits"));
+                }
+                let mut rng = Self::from_seed(state.seed);
+                rng.set_stream(state.stream);
+                rng.set_word_pos(state.word_pos);
+                Ok(rng)
+            }
+        }
+
         impl $ChaChaXRng {
+            /// Construct an RNG from a key and a 64-bit nonce, using the original layout.
+            ///
+            /// This is the layout of Bernstein's reference implementation: a 64-bit block counter
+            /// (starting at zero) followed by the 64-bit nonce. It is equivalent to seeding with
+            /// `key` and passing the nonce, read as a little-endian `u64`, to `set_stream`.
+            #[inline]
+            pub fn from_key_nonce(key: [u8; 32], nonce: [u8; 8]) -> Self {
+                let core = $ChaChaXCore {
+                    state: ChaCha::new(&key, &nonce),
+                };
+                Self::from(core)
+            }
+
+            /// Construct an RNG from a key and a 96-bit nonce, using the IETF layout.
+            ///
+            /// This is the layout of [RFC 8439]: a 32-bit block counter (starting at zero)
+            /// followed by the 96-bit nonce. With 20 rounds the output is the RFC 8439 keystream
+            /// for `key` and `nonce`; note that for encryption RFC 8439 starts with block 1, which
+            /// may be reached by skipping one block (16 words) of output.
+            ///
+            /// Internally the block counter remains 64 bits wide, its high half holding the first
+            /// 32 bits of the nonce. Hence this half of the nonce is included in the value of
+            /// `get_word_pos` (and replaced by `set_word_pos`), and after 2<sup>32</sup> blocks
+            /// (256 GiB) of output the counter carries into the nonce instead of wrapping around.
+            ///
+            /// ```
+            /// # use rand_chacha::ChaCha20Rng;
+            /// let key = [0u8; 32];
+            /// let nonce = [0, 0, 0, 9, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+            /// let mut rng = ChaCha20Rng::from_key_ietf_nonce(key, nonce);
+            /// // Start at block 1, as for RFC 8439 encryption
+            /// rng.set_word_pos(rng.get_word_pos() + 16);
+            /// ```
+            ///
+            /// [RFC 8439]: https://tools.ietf.org/html/rfc8439
+            #[inline]
+            pub fn from_key_ietf_nonce(key: [u8; 32], nonce: [u8; 12]) -> Self {
+                let core = $ChaChaXCore {
+                    state: ChaCha::new(&key, &nonce),
+                };
+                Self::from(core)
+            }
+
+            /// Get the seed, i.e. the 256-bit key.
+            #[inline]
+            pub fn get_seed(&self) -> [u8; 32] {
+                self.rng.core.state.get_seed()
+            }
+
             // The buffer is a 4-block window, i.e. it is always at a block-aligned position in the
             // stream but if the stream has been seeked it may not be self-aligned.
 
@@ -192,19 +402,8 @@ macro_rules! chacha_impl {
             /// byte-offset.
             #[inline]
             pub fn get_word_pos(&self) -> u128 {
-                let buf_start_block = {
-                    let buf_end_block = self.rng.core.state.get_stream_param(STREAM_PARAM_BLOCK);
-                    u64::wrapping_sub(buf_end_block, BUF_BLOCKS.into())
-                };
-                let (buf_offset_blocks, block_offset_words) = {
-                    let buf_offset_words = self.rng.index() as u64;
-                    let blocks_part = buf_offset_words / u64::from(BLOCK_WORDS);
-                    let words_part = buf_offset_words % u64::from(BLOCK_WORDS);
-                    (blocks_part, words_part)
-                };
-                let pos_block = u64::wrapping_add(buf_start_block, buf_offset_blocks);
-                let pos_block_words = u128::from(pos_block) * u128::from(BLOCK_WORDS);
-                pos_block_words + u128::from(block_offset_words)
+                let buffered = usize::from(BUF_BLOCKS * BLOCK_WORDS).saturating_sub(self.rng.index());
+                self.rng.core.buffer_word_pos(buffered)
             }
 
             /// Set the offset from the start of the stream, in 32-bit words.
@@ -214,12 +413,14 @@ macro_rules! chacha_impl {
             /// 60 bits.
             #[inline]
             pub fn set_word_pos(&mut self, word_offset: u128) {
-                let block = (word_offset / u128::from(BLOCK_WORDS)) as u64;
-                self.rng
-                    .core
-                    .state
-                    .set_stream_param(STREAM_PARAM_BLOCK, block);
-                self.rng.generate_and_set((word_offset % u128::from(BLOCK_WORDS)) as usize);
+                let index = self.rng.core.seek_word_pos(word_offset);
+                self.rng.generate_and_set(index);
+            }
+
+            /// Get the stream number.
+            #[inline]
+            pub fn get_stream(&self) -> u64 {
+                self.rng.core.state.get_stream_param(STREAM_PARAM_NONCE)
             }
 
             /// Set the stream number.
@@ -232,7 +433,7 @@ macro_rules! chacha_impl {
             /// and pass to this function. In theory a 96-bit nonce can be used by
             /// passing the last 64-bits to this function and using the first 32-bits as
             /// the most significant half of the 64-bit counter (which may be set
-            /// indirectly via `set_word_pos`), but this is not directly supported.
+            /// indirectly via `set_word_pos`); `from_key_ietf_nonce` does exactly this.
             #[inline]
             pub fn set_stream(&mut self, stream: u64) {
                 self.rng
@@ -266,9 +467,9 @@ macro_rules! chacha_impl {
     }
 }
 
-chacha_impl!(ChaCha20Core, ChaCha20Rng, 10, "ChaCha with 20 rounds");
-chacha_impl!(ChaCha12Core, ChaCha12Rng, 6, "ChaCha with 12 rounds");
-chacha_impl!(ChaCha8Core, ChaCha8Rng, 4, "ChaCha with 8 rounds");
+chacha_impl!(ChaCha20Core, ChaCha20Rng, 10, "ChaCha with 20 rounds", "ChaCha20");
+chacha_impl!(ChaCha12Core, ChaCha12Rng, 6, "ChaCha with 12 rounds", "ChaCha12");
+chacha_impl!(ChaCha8Core, ChaCha8Rng, 4, "ChaCha with 8 rounds", "ChaCha8");
 
 #[cfg(test)]
 mod test {
@@ -396,6 +597,24 @@ mod test {
         assert_eq!(rng2.get_word_pos(), expected_end + 21);
     }
 
+    #[test]
+    fn test_chacha_fill_bytes_bulk() {
+        // Large requests are written to the destination directly
+        let mut rng1 = ChaChaRng::seed_from_u64(3);
+        let mut rng2 = ChaChaRng::seed_from_u64(3);
+        rng1.next_u32();
+        rng2.next_u32();
+        let mut bytes = [0u8; 1003];
+        rng1.fill_bytes(&mut bytes);
+        for chunk in bytes.chunks(4) {
+            let word = rng2.next_u32().to_le_bytes();
+            assert_eq!(chunk, &word[..chunk.len()]);
+        }
+        assert_eq!(rng1.get_word_pos(), 252);
+        assert_eq!(rng1.get_word_pos(), rng2.get_word_pos());
+        assert_eq!(rng1.next_u64(), rng2.next_u64());
+    }
+
     #[test]
     fn test_chacha_multiple_blocks() {
         let seed = [
@@ -457,6 +676,71 @@ mod test {
         assert_eq!(results, expected);
     }
 
+    #[test]
+    fn test_chacha_key_nonce() {
+        // Test vector 5 from
+        // https://tools.ietf.org/html/draft-nir-cfrg-chacha20-poly1305-04
+        let expected = [
+            0x374dc6c2, 0x3736d58c, 0xb904e24a, 0xcd3f93ef, 0x88228b1a, 0x96a4dfb3, 0x5b76ab72,
+            0xc727ee54, 0x0e0e978a, 0xf3145c95, 0x1b748ea8, 0xf786c297, 0x99c28f5f, 0x628314e8,
+            0x398a19fa, 0x6ded1b53,
+        ];
+        let mut results = [0u32; 16];
+
+        let mut rng = ChaChaRng::from_key_nonce([0u8; 32], [0, 0, 0, 0, 0, 0, 0, 2]);
+        for i in results.iter_mut() {
+            *i = rng.next_u32();
+        }
+        assert_eq!(results, expected);
+
+        let mut rng =
+            ChaChaRng::from_key_ietf_nonce([0u8; 32], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        for i in results.iter_mut() {
+            *i = rng.next_u32();
+        }
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_chacha_ietf_nonce_rfc8439() {
+        // Block function test vector, RFC 8439 section 2.3.2
+        let mut key = [0u8; 32];
+        for (i, k) in key.iter_mut().enumerate() {
+            *k = i as u8;
+        }
+        let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let mut rng = ChaChaRng::from_key_ietf_nonce(key, nonce);
+        // The first nonce word is the high half of the internal 64-bit block counter.
+        assert_eq!(rng.get_word_pos(), 0x09000000u128 << 36);
+        rng.set_word_pos(rng.get_word_pos() + 16);
+
+        let mut results = [0u32; 16];
+        for i in results.iter_mut() {
+            *i = rng.next_u32();
+        }
+        let expected = [
+            0xe4e7f110, 0x15593bd1, 0x1fdd0f50, 0xc47120a3, 0xc7f4d1c7, 0x0368c033, 0x9aaa2204,
+            0x4e6cd4c3, 0x466482d2, 0x09aa9f07, 0x05d7c214, 0xa2028bd9, 0xd19c12b5, 0xb94e16de,
+            0xe883d0cb, 0x4e3c50a2,
+        ];
+        assert_eq!(results, expected);
+        assert_eq!(rng.get_seed(), key);
+    }
+
+    #[test]
+    fn test_chacha_get_seed() {
+        let seed = [
+            0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7,
+            0, 0, 0,
+        ];
+        let mut rng = ChaChaRng::from_seed(seed);
+        assert_eq!(rng.get_seed(), seed);
+        rng.next_u32();
+        rng.set_stream(3);
+        assert_eq!(rng.get_seed(), seed);
+        assert_eq!(ChaChaRng::from_key_nonce(seed, [1; 8]).get_seed(), seed);
+    }
+
     #[test]
     fn test_chacha_clone_streams() {
         let seed = [
@@ -506,4 +790,111 @@ mod test {
         rng.set_word_pos(0);
         assert_eq!(rng.get_word_pos(), 0);
     }
+
+    #[test]
+    fn test_chacha_snapshot() {
+        use rand_core::snapshot::{Snapshot, SnapshotError};
+
+        let mut rng = ChaChaRng::from_key_ietf_nonce([7; 32], [1; 12]);
+        let mut buf = [0; 75];
+        assert_eq!(ChaChaRng::snapshot_len(), buf.len());
+        for &n in [0, 1, 15, 16, 63, 64, 100].iter() {
+            for _ in 0..n {
+                rng.next_u32();
+            }
+            rng.export_snapshot(&mut buf).unwrap();
+            assert_eq!(&buf[..13], b"RNGS\x08ChaCha20");
+            let mut restored = ChaChaRng::from_snapshot(&buf).unwrap();
+            assert_eq!(restored, rng);
+            let mut clone = rng.clone();
+            for _ in 0..100 {
+                assert_eq!(restored.next_u32(), clone.next_u32());
+            }
+        }
+
+        // Other numbers of rounds are other algorithms
+        let result = crate::ChaCha12Rng::from_snapshot(&buf);
+        assert_eq!(result.unwrap_err(), SnapshotError::AlgorithmMismatch);
+
+        // Word positions are 68 bits
+        buf[74] = 1;
+        assert_eq!(ChaChaRng::from_snapshot(&buf).unwrap_err(), SnapshotError::InvalidState);
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_chacha_serde() {
+        let mut rng = ChaChaRng::from_key_ietf_nonce([7; 32], [1; 12]);
+        for &n in [0, 1, 15, 16, 63, 64, 100].iter() {
+            for _ in 0..n {
+                rng.next_u32();
+            }
+            let buf = bincode::serialize(&rng).unwrap();
+            let mut restored: ChaChaRng = bincode::deserialize(&buf).unwrap();
+            assert_eq!(restored, rng);
+            let mut clone = rng.clone();
+            for _ in 0..100 {
+                assert_eq!(restored.next_u32(), clone.next_u32());
+            }
+        }
+
+        // Word positions are 68 bits
+        let mut buf = bincode::serialize(&rng).unwrap();
+        buf[55] = 1;
+        assert!(bincode::deserialize::<ChaChaRng>(&buf).is_err());
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn test_chacha_seekable() {
+        use rand_core::SeekableRng;
+
+        let mut rng1 = ChaChaRng::from_seed([7; 32]);
+        let mut rng2 = rng1.clone();
+        for _ in 0..37 {
+            rng1.next_u32();
+        }
+        rng2.advance(37);
+        assert_eq!(rng2.get_position(), 37);
+        assert_eq!(rng1.get_position(), rng1.get_word_pos());
+        for _ in 0..100 {
+            assert_eq!(rng1.next_u32(), rng2.next_u32());
+        }
+
+        let pos = rng1.get_position();
+        let x = rng1.next_u64();
+        rng1.set_position(pos);
+        assert_eq!(rng1.next_u64(), x);
+
+        // Positions wrap around at the end of the 68-bit word counter
+        rng2.set_word_pos((1 << 68) - 5);
+        rng2.advance(6);
+        assert_eq!(rng2.get_position(), 1);
+        rng1.set_word_pos(1);
+        assert_eq!(rng1.next_u32(), rng2.next_u32());
+    }
+
+    #[test]
+    fn test_chacha_fork() {
+        use rand_core::SplittableRng;
+
+        let mut rng = ChaChaRng::from_seed([7; 32]);
+        rng.next_u32();
+        let child = rng.fork(3);
+        assert_eq!(child.get_seed(), rng.get_seed());
+        assert_eq!(child.get_word_pos(), 0);
+        assert_ne!(child.get_stream(), rng.get_stream());
+
+        // Forking depends on the stream, but not on the position
+        rng.set_word_pos(1000);
+        assert_eq!(rng.fork(3), child);
+        assert_ne!(rng.fork(4).get_stream(), child.get_stream());
+        assert_ne!(child.fork(4).get_stream(), rng.fork(4).get_stream());
+
+        let mut parent = rng.clone();
+        let mut child = parent.split();
+        assert_eq!(child, rng.fork(0));
+        assert_eq!(parent, rng.fork(1));
+        assert_ne!(child.next_u64(), parent.next_u64());
+    }
 }
@@ -79,7 +79,7 @@ impl ChaCha {
     /// Produce 4 blocks of output, advancing the state
     #[inline(always)]
     pub fn refill4(&mut self, drounds: u32, out: &mut [u8; BUFSZ]) {
-        refill_wide(self, drounds, out)
+        crate::backend::refill_wide(self, drounds, out)
     }
 
     #[inline(always)]
@@ -92,6 +92,11 @@ impl ChaCha {
         get_stream_param(self, param)
     }
 
+    #[inline(always)]
+    pub fn get_seed(&self) -> [u8; 32] {
+        get_seed(self)
+    }
+
     /// Return whether rhs is equal in all parameters except current 64-bit position.
     #[inline]
     pub fn stream64_eq(&self, rhs: &Self) -> bool {
@@ -103,7 +108,7 @@ impl ChaCha {
 
 #[allow(clippy::many_single_char_names)]
 #[inline(always)]
-fn refill_wide_impl<Mach: Machine>(
+pub(crate) fn refill_wide_impl<Mach: Machine>(
     m: Mach, state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ],
 ) {
     let k = m.vec([0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
@@ -158,8 +163,10 @@ fn refill_wide_impl<Mach: Machine>(
     }
 }
 
+// The x86-64 machines are dispatched to by `crate::backend`.
+#[cfg(not(all(target_arch = "x86_64", target_feature = "sse2", not(miri))))]
 dispatch!(m, Mach, {
-    fn refill_wide(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
+    [pub(crate)] fn refill_wide_generic(state: &mut ChaCha, drounds: u32, out: &mut [u8; BUFSZ]) {
         refill_wide_impl(m, state, drounds, out);
     }
 });
@@ -205,6 +212,17 @@ dispatch_light128!(m, Mach, {
     }
 });
 
+dispatch_light128!(m, Mach, {
+    fn get_seed(state: &ChaCha) -> [u8; 32] {
+        let b: Mach::u32x4 = m.unpack(state.b);
+        let c: Mach::u32x4 = m.unpack(state.c);
+        let mut key = [0u8; 32];
+        b.write_le(&mut key[..16]);
+        c.write_le(&mut key[16..]);
+        key
+    }
+});
+
 fn read_u32le(xs: &[u8]) -> u32 {
     assert_eq!(xs.len(), 4);
     u32::from(xs[0]) | (u32::from(xs[1]) << 8) | (u32::from(xs[2]) << 16) | (u32::from(xs[3]) << 24)
@@ -7,6 +7,23 @@
 // except according to those terms.
 
 //! The ChaCha random number generator.
+//!
+//! # Backends
+//!
+//! ChaCha blocks are generated by one of several implementations, listed by
+//! [`Backend`]. All of them produce identical output; by default the fastest
+//! one supported by the CPU is used. The backend in use may be queried with
+//! [`active_backend`], and a specific backend may be selected with
+//! [`force_backend`], e.g. for benchmarking:
+//!
+//! ```
+//! use rand_chacha::{active_backend, force_backend, reset_backend, Backend};
+//!
+//! println!("ChaCha backend: {}", active_backend());
+//! force_backend(Backend::Scalar).unwrap();
+//! assert_eq!(active_backend(), Backend::Scalar);
+//! reset_backend();
+//! ```
 
 #![doc(
     html_logo_url = "https://www.rust-lang.org/logos/rust-logo-128x128-blk.png",
@@ -20,9 +37,11 @@
 
 pub use rand_core;
 
+mod backend;
 mod chacha;
 mod guts;
 
+pub use crate::backend::{active_backend, force_backend, reset_backend, Backend, UnsupportedBackend};
 pub use crate::chacha::{
     ChaCha12Core, ChaCha12Rng, ChaCha20Core, ChaCha20Rng, ChaCha8Core, ChaCha8Rng,
 };
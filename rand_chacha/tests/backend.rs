@@ -0,0 +1,42 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tests of `force_backend`.
+//!
+//! The selected backend is global to the process, so these checks run in
+//! their own binary, in a single test.
+
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::{active_backend, force_backend, reset_backend, Backend, UnsupportedBackend};
+use rand_chacha::ChaCha20Rng;
+
+#[test]
+fn force_backend_output() {
+    let mut reference = ChaCha20Rng::seed_from_u64(7);
+    let mut expected = [0u8; 1000];
+    reference.fill_bytes(&mut expected);
+
+    assert!(Backend::Scalar.is_supported());
+    assert!(Backend::detect().is_supported());
+    for &backend in Backend::all() {
+        match force_backend(backend) {
+            Ok(()) => assert_eq!(active_backend(), backend),
+            Err(e) => {
+                assert_eq!(e, UnsupportedBackend(backend));
+                assert!(!backend.is_supported());
+                continue;
+            }
+        }
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let mut out = [0u8; 1000];
+        rng.fill_bytes(&mut out);
+        assert!(out[..] == expected[..]);
+    }
+    reset_backend();
+    assert_eq!(active_backend(), Backend::detect());
+}
@@ -55,8 +55,10 @@
 
 use crate::impls::{fill_via_u32_chunks, fill_via_u64_chunks};
 use crate::{CryptoRng, Error, RngCore, SeedableRng};
+#[cfg(not(target_os = "emscripten"))] use crate::SeekableRng;
+use crate::snapshot::{SnapshotError, SnapshotState, StateReader, StateWriter};
 use core::convert::AsRef;
-use core::fmt;
+use core::{fmt, mem};
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
@@ -75,6 +77,46 @@ pub trait BlockRngCore {
 
     /// Generate a new block of results.
     fn generate(&mut self, results: &mut Self::Results);
+
+    /// Generate whole blocks of results directly into a byte slice.
+    ///
+    /// This allows [`BlockRng`] and [`BlockRng64`] to fill large byte slices
+    /// without copying the output through their `results` buffer. An
+    /// implementation should write as many whole blocks as fit in `dest`,
+    /// each block being the output of [`generate`] converted to little-endian
+    /// bytes, and return the number of bytes written.
+    ///
+    /// The default implementation writes nothing and returns 0, in which
+    /// case the results buffer is used.
+    ///
+    /// [`generate`]: BlockRngCore::generate
+    #[inline]
+    fn generate_into(&mut self, _dest: &mut [u8]) -> usize {
+        0
+    }
+}
+
+/// A [`BlockRngCore`] whose position in its output sequence can be queried
+/// and changed.
+///
+/// This allows [`BlockRng`] and [`BlockRng64`] to implement [`SeekableRng`],
+/// accounting for the words which were generated into their results buffer
+/// but not consumed yet. Positions are measured in words, i.e. elements of
+/// `Results`, and it is up to the core to reduce them modulo its period.
+///
+/// Not available on Emscripten, which does not support `u128`.
+#[cfg(not(target_os = "emscripten"))]
+pub trait SeekableBlockRngCore: BlockRngCore {
+    /// Get the position of the word `buffered` words before the first word
+    /// of the results the next call to `generate` produces.
+    ///
+    /// `buffered` is never greater than the length of `Results`.
+    fn word_pos(&self, buffered: usize) -> u128;
+
+    /// Set the position, such that the results of the next call to
+    /// `generate` contain the word at `pos`, and return its index within
+    /// those results.
+    fn set_word_pos(&mut self, pos: u128) -> usize;
 }
 
 /// A wrapper type implementing [`RngCore`] for some type implementing
@@ -91,7 +133,7 @@ pub trait BlockRngCore {
 ///
 /// `BlockRng` has heavily optimized implementations of the [`RngCore`] methods
 /// reading values from the results buffer, as well as
-/// calling [`BlockRngCore::generate`] directly on the output array when
+/// calling [`BlockRngCore::generate_into`] directly on the output array when
 /// [`fill_bytes`] / [`try_fill_bytes`] is called on a large array. These methods
 /// also handle the bookkeeping of when to generate a new batch of values.
 ///
@@ -217,6 +259,14 @@ where
         let mut read_len = 0;
         while read_len < dest.len() {
             if self.index >= self.results.as_ref().len() {
+                // The buffer is empty: generate whole blocks directly into
+                // `dest` if it is large enough.
+                if dest.len() - read_len >= self.results.as_ref().len() * 4 {
+                    read_len += self.core.generate_into(&mut dest[read_len..]);
+                    if read_len == dest.len() {
+                        break;
+                    }
+                }
                 self.generate_and_set(0);
             }
             let (consumed_u32, filled_u8) =
@@ -234,6 +284,62 @@ where
     }
 }
 
+#[cfg(not(target_os = "emscripten"))]
+impl<R: SeekableBlockRngCore<Item = u32>> SeekableRng for BlockRng<R>
+where
+    <R as BlockRngCore>::Results: AsRef<[u32]> + AsMut<[u32]>,
+{
+    type Position = u128;
+
+    #[inline]
+    fn get_position(&self) -> u128 {
+        let buffered = self.results.as_ref().len().saturating_sub(self.index);
+        self.core.word_pos(buffered)
+    }
+
+    #[inline]
+    fn set_position(&mut self, pos: u128) {
+        let index = self.core.set_word_pos(pos);
+        self.generate_and_set(index);
+    }
+
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        let pos = self.get_position();
+        self.set_position(pos.wrapping_add(delta));
+    }
+}
+
+/// The state consists of the state of the core, followed by the output buffer
+/// and the index into it (as `u32`).
+impl<R: BlockRngCore<Item = u32> + SnapshotState> SnapshotState for BlockRng<R>
+where
+    <R as BlockRngCore>::Results: AsRef<[u32]> + AsMut<[u32]>,
+{
+    const STATE_LEN: usize = R::STATE_LEN + mem::size_of::<R::Results>() + 4;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        self.core.write_state(w);
+        for &x in self.results.as_ref() {
+            w.write_u32(x);
+        }
+        w.write_u32(self.index as u32);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let core = R::read_state(r)?;
+        let mut results = R::Results::default();
+        for x in results.as_mut() {
+            *x = r.read_u32();
+        }
+        let index = r.read_u32() as usize;
+        if index > results.as_ref().len() {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(BlockRng { results, index, core })
+    }
+}
+
 impl<R: BlockRngCore + SeedableRng> SeedableRng for BlockRng<R> {
     type Seed = R::Seed;
 
@@ -358,15 +464,8 @@ where
         self.half_used = !self.half_used;
         self.index += self.half_used as usize;
 
-        // Index as if this is a u32 slice.
-        unsafe {
-            let results = &*(self.results.as_ref() as *const [u64] as *const [u32]);
-            if cfg!(target_endian = "little") {
-                *results.get_unchecked(index)
-            } else {
-                *results.get_unchecked(index ^ 1)
-            }
-        }
+        // Index as if this is a u32 slice, low half first.
+        (self.results.as_ref()[index >> 1] >> (32 * (index & 1))) as u32
     }
 
     #[inline]
@@ -388,6 +487,14 @@ where
         self.half_used = false;
         while read_len < dest.len() {
             if self.index as usize >= self.results.as_ref().len() {
+                // The buffer is empty: generate whole blocks directly into
+                // `dest` if it is large enough.
+                if dest.len() - read_len >= self.results.as_ref().len() * 8 {
+                    read_len += self.core.generate_into(&mut dest[read_len..]);
+                    if read_len == dest.len() {
+                        break;
+                    }
+                }
                 self.core.generate(&mut self.results);
                 self.index = 0;
             }
@@ -409,6 +516,72 @@ where
     }
 }
 
+/// A half-consumed `u64` counts as consumed: after setting the position, the
+/// next call to `next_u32` returns the low half of the word at the position.
+#[cfg(not(target_os = "emscripten"))]
+impl<R: SeekableBlockRngCore<Item = u64>> SeekableRng for BlockRng64<R>
+where
+    <R as BlockRngCore>::Results: AsRef<[u64]> + AsMut<[u64]>,
+{
+    type Position = u128;
+
+    #[inline]
+    fn get_position(&self) -> u128 {
+        let buffered = self.results.as_ref().len().saturating_sub(self.index);
+        self.core.word_pos(buffered)
+    }
+
+    #[inline]
+    fn set_position(&mut self, pos: u128) {
+        let index = self.core.set_word_pos(pos);
+        self.generate_and_set(index);
+    }
+
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        let pos = self.get_position();
+        self.set_position(pos.wrapping_add(delta));
+    }
+}
+
+/// The state consists of the state of the core, followed by the output buffer,
+/// the index into it (as `u32`), and whether half of the previous word is
+/// used (as a `u8`).
+impl<R: BlockRngCore<Item = u64> + SnapshotState> SnapshotState for BlockRng64<R>
+where
+    <R as BlockRngCore>::Results: AsRef<[u64]> + AsMut<[u64]>,
+{
+    const STATE_LEN: usize = R::STATE_LEN + mem::size_of::<R::Results>() + 5;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        self.core.write_state(w);
+        for &x in self.results.as_ref() {
+            w.write_u64(x);
+        }
+        w.write_u32(self.index as u32);
+        w.write_u8(self.half_used as u8);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let core = R::read_state(r)?;
+        let mut results = R::Results::default();
+        for x in results.as_mut() {
+            *x = r.read_u64();
+        }
+        let index = r.read_u32() as usize;
+        let half_used = match r.read_u8() {
+            0 => false,
+            1 => true,
+            _ => return Err(SnapshotError::InvalidState),
+        };
+        // A half-used word is one of the consumed words
+        if index > results.as_ref().len() || (half_used && index == 0) {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(BlockRng64 { results, index, half_used, core })
+    }
+}
+
 impl<R: BlockRngCore + SeedableRng> SeedableRng for BlockRng64<R> {
     type Seed = R::Seed;
 
@@ -429,3 +602,353 @@ impl<R: BlockRngCore + SeedableRng> SeedableRng for BlockRng64<R> {
 }
 
 impl<R: BlockRngCore + CryptoRng> CryptoRng for BlockRng<R> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Counts the words it outputs, optionally supporting `generate_into`.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+    struct CounterCore {
+        counter: u64,
+        bulk: bool,
+    }
+
+    impl CounterCore {
+        fn next(&mut self) -> u64 {
+            self.counter = self.counter.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            self.counter
+        }
+    }
+
+    impl BlockRngCore for CounterCore {
+        type Item = u32;
+        type Results = [u32; 8];
+
+        fn generate(&mut self, results: &mut Self::Results) {
+            for r in results.iter_mut() {
+                *r = self.next() as u32;
+            }
+        }
+
+        fn generate_into(&mut self, dest: &mut [u8]) -> usize {
+            if !self.bulk {
+                return 0;
+            }
+            let mut len = 0;
+            for chunk in dest.chunks_exact_mut(32) {
+                for bytes in chunk.chunks_exact_mut(4) {
+                    bytes.copy_from_slice(&(self.next() as u32).to_le_bytes());
+                }
+                len += 32;
+            }
+            len
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+    struct CounterCore64(CounterCore);
+
+    impl BlockRngCore for CounterCore64 {
+        type Item = u64;
+        type Results = [u64; 8];
+
+        fn generate(&mut self, results: &mut Self::Results) {
+            for r in results.iter_mut() {
+                *r = self.0.next();
+            }
+        }
+
+        fn generate_into(&mut self, dest: &mut [u8]) -> usize {
+            if !self.0.bulk {
+                return 0;
+            }
+            let mut len = 0;
+            for chunk in dest.chunks_exact_mut(64) {
+                for bytes in chunk.chunks_exact_mut(8) {
+                    bytes.copy_from_slice(&self.0.next().to_le_bytes());
+                }
+                len += 64;
+            }
+            len
+        }
+    }
+
+    #[test]
+    fn test_block_rng_generate_into() {
+        let core = CounterCore { counter: 0, bulk: false };
+        for &(skip, len) in [(0, 1000), (3, 1000), (0, 64), (5, 31), (8, 333), (1, 32)].iter() {
+            let mut buffered = BlockRng::new(core.clone());
+            let mut bulk = BlockRng::new(CounterCore { bulk: true, ..core.clone() });
+            for _ in 0..skip {
+                assert_eq!(buffered.next_u32(), bulk.next_u32());
+            }
+            let mut expected = [0u8; 1000];
+            let mut results = [0u8; 1000];
+            buffered.fill_bytes(&mut expected[..len]);
+            bulk.fill_bytes(&mut results[..len]);
+            assert!(expected[..len] == results[..len]);
+            for _ in 0..20 {
+                assert_eq!(buffered.next_u32(), bulk.next_u32());
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_rng64_generate_into() {
+        let core = CounterCore64(CounterCore { counter: 0, bulk: false });
+        for &(skip, len) in [(0, 1000), (3, 1000), (0, 128), (5, 63), (8, 333), (1, 64)].iter() {
+            let mut buffered = BlockRng64::new(core.clone());
+            let mut bulk = BlockRng64::new(CounterCore64(CounterCore {
+                bulk: true,
+                ..core.0.clone()
+            }));
+            for _ in 0..skip {
+                assert_eq!(buffered.next_u32(), bulk.next_u32());
+            }
+            let mut expected = [0u8; 1000];
+            let mut results = [0u8; 1000];
+            buffered.fill_bytes(&mut expected[..len]);
+            bulk.fill_bytes(&mut results[..len]);
+            assert!(expected[..len] == results[..len]);
+            for _ in 0..20 {
+                assert_eq!(buffered.next_u64(), bulk.next_u64());
+            }
+        }
+    }
+
+    // Outputs its word position, and can only seek to the start of one of
+    // its four-word blocks, like a counter-based generator.
+    #[cfg(not(target_os = "emscripten"))]
+    #[derive(Debug, Clone)]
+    struct PositionCore {
+        block: u64,
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    impl BlockRngCore for PositionCore {
+        type Item = u32;
+        type Results = [u32; 8];
+
+        fn generate(&mut self, results: &mut Self::Results) {
+            for (i, r) in results.iter_mut().enumerate() {
+                *r = (self.block * 4) as u32 + i as u32;
+            }
+            self.block += 2;
+        }
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    impl SeekableBlockRngCore for PositionCore {
+        fn word_pos(&self, buffered: usize) -> u128 {
+            u128::from(self.block * 4) - buffered as u128
+        }
+
+        fn set_word_pos(&mut self, pos: u128) -> usize {
+            self.block = (pos / 4) as u64;
+            (pos % 4) as usize
+        }
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[derive(Debug, Clone)]
+    struct PositionCore64(PositionCore);
+
+    #[cfg(not(target_os = "emscripten"))]
+    impl BlockRngCore for PositionCore64 {
+        type Item = u64;
+        type Results = [u64; 8];
+
+        fn generate(&mut self, results: &mut Self::Results) {
+            let mut buf = [0u32; 8];
+            self.0.generate(&mut buf);
+            for (r, &x) in results.iter_mut().zip(buf.iter()) {
+                *r = u64::from(x);
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    impl SeekableBlockRngCore for PositionCore64 {
+        fn word_pos(&self, buffered: usize) -> u128 {
+            self.0.word_pos(buffered)
+        }
+
+        fn set_word_pos(&mut self, pos: u128) -> usize {
+            self.0.set_word_pos(pos)
+        }
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn test_block_rng_seek() {
+        let mut rng = BlockRng::new(PositionCore { block: 0 });
+        assert_eq!(rng.get_position(), 0);
+        for i in 0..20 {
+            assert_eq!(rng.next_u32(), i);
+            assert_eq!(rng.get_position(), u128::from(i) + 1);
+        }
+        rng.next_u64();
+        assert_eq!(rng.get_position(), 22);
+
+        for &pos in [0u128, 3, 7, 8, 13, 100].iter() {
+            rng.set_position(pos);
+            assert_eq!(rng.get_position(), pos);
+            assert_eq!(rng.next_u32() as u128, pos);
+        }
+
+        rng.set_position(5);
+        rng.advance(10);
+        assert_eq!(rng.next_u32(), 15);
+        rng.advance(0);
+        assert_eq!(rng.next_u32(), 16);
+
+        // `fill_bytes` consumes whole words
+        let mut buf = [0u8; 100];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(rng.get_position(), 42);
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn test_block_rng64_seek() {
+        let mut rng = BlockRng64::new(PositionCore64(PositionCore { block: 0 }));
+        for i in 0..10 {
+            assert_eq!(rng.next_u64(), i);
+        }
+        assert_eq!(rng.get_position(), 10);
+
+        // A half-used word counts as consumed
+        assert_eq!(rng.next_u32(), 10);
+        assert_eq!(rng.get_position(), 11);
+
+        for &pos in [0u128, 3, 7, 8, 13, 100].iter() {
+            rng.set_position(pos);
+            assert_eq!(rng.get_position(), pos);
+            assert_eq!(rng.next_u64() as u128, pos);
+        }
+
+        rng.set_position(9);
+        rng.advance(30);
+        assert_eq!(rng.next_u32(), 39);
+    }
+
+    impl SnapshotState for CounterCore {
+        const STATE_LEN: usize = 9;
+
+        fn write_state(&self, w: &mut StateWriter) {
+            w.write_u64(self.counter);
+            w.write_u8(self.bulk as u8);
+        }
+
+        fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+            let counter = r.read_u64();
+            let bulk = r.read_u8() != 0;
+            Ok(CounterCore { counter, bulk })
+        }
+    }
+
+    impl SnapshotState for CounterCore64 {
+        const STATE_LEN: usize = 9;
+
+        fn write_state(&self, w: &mut StateWriter) {
+            self.0.write_state(w)
+        }
+
+        fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+            CounterCore::read_state(r).map(CounterCore64)
+        }
+    }
+
+    #[test]
+    fn test_block_rng_state() {
+        type Rng = BlockRng<CounterCore>;
+        let mut rng = Rng::new(CounterCore { counter: 0, bulk: false });
+        let mut buf = [0; 9 + 32 + 4];
+        assert_eq!(Rng::STATE_LEN, buf.len());
+        for n in 0..20 {
+            rng.write_state(&mut StateWriter::new(&mut buf));
+            let mut restored = Rng::read_state(&mut StateReader::new(&buf)).unwrap();
+            assert_eq!(restored.index(), rng.index());
+            for _ in 0..20 {
+                assert_eq!(restored.next_u32(), rng.next_u32());
+            }
+            for _ in 0..n {
+                rng.next_u32();
+            }
+        }
+
+        // The index must not exceed the buffer length
+        buf[41..].copy_from_slice(&9u32.to_le_bytes());
+        let result = Rng::read_state(&mut StateReader::new(&buf));
+        assert_eq!(result.unwrap_err(), SnapshotError::InvalidState);
+    }
+
+    #[test]
+    fn test_block_rng64_state() {
+        type Rng = BlockRng64<CounterCore64>;
+        let core = CounterCore64(CounterCore { counter: 0, bulk: false });
+        let mut rng = Rng::new(core);
+        let mut buf = [0; 9 + 64 + 5];
+        assert_eq!(Rng::STATE_LEN, buf.len());
+        for n in 0..20 {
+            rng.write_state(&mut StateWriter::new(&mut buf));
+            let mut restored = Rng::read_state(&mut StateReader::new(&buf)).unwrap();
+            // Includes a half-used word for odd `n`
+            for _ in 0..20 {
+                assert_eq!(restored.next_u32(), rng.next_u32());
+            }
+            for _ in 0..n {
+                rng.next_u32();
+            }
+        }
+
+        buf[77] = 2;
+        let result = Rng::read_state(&mut StateReader::new(&buf));
+        assert_eq!(result.unwrap_err(), SnapshotError::InvalidState);
+
+        // No word is half-used before the first one is consumed
+        buf[73..77].copy_from_slice(&0u32.to_le_bytes());
+        buf[77] = 1;
+        let result = Rng::read_state(&mut StateReader::new(&buf));
+        assert_eq!(result.unwrap_err(), SnapshotError::InvalidState);
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_block_rng_serde() {
+        let mut rng = BlockRng::new(CounterCore { counter: 0, bulk: false });
+        for n in 0..20 {
+            let buf = bincode::serialize(&rng).unwrap();
+            let mut restored: BlockRng<CounterCore> = bincode::deserialize(&buf).unwrap();
+            assert_eq!(restored.index(), rng.index());
+            for _ in 0..20 {
+                assert_eq!(restored.next_u32(), rng.next_u32());
+            }
+            for _ in 0..n {
+                rng.next_u32();
+            }
+        }
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_block_rng64_serde() {
+        let core = CounterCore64(CounterCore { counter: 0, bulk: false });
+        let mut rng = BlockRng64::new(core);
+        for n in 0..20 {
+            let buf = bincode::serialize(&rng).unwrap();
+            let mut restored: BlockRng64<CounterCore64> = bincode::deserialize(&buf).unwrap();
+            assert_eq!(restored.index(), rng.index());
+            // Includes a half-used word for odd `n`
+            for _ in 0..20 {
+                assert_eq!(restored.next_u32(), rng.next_u32());
+            }
+            for _ in 0..n {
+                rng.next_u32();
+            }
+        }
+    }
+}
@@ -0,0 +1,571 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Sources of entropy for seeding generators.
+//!
+//! An [`EntropySource`] provides the bytes used to seed and reseed generators.
+//! The following sources are available:
+//!
+//! - [`OsRng`], the operating system's generator via `getrandom`
+//!   (with the `getrandom` feature)
+//! - [`DevUrandom`], reading the `/dev/urandom` device (with `std`, on Unix)
+//! - [`RdRand`] and [`RdSeed`], the x86 CPU instructions, where supported
+//! - [`TestEntropy`], a deterministic source for tests
+//! - [`DefaultEntropy`], the process-wide default source
+//!
+//! All sources except `TestEntropy` also implement [`RngCore`] and
+//! [`CryptoRng`], so that they can be used wherever a generator is expected,
+//! e.g. as the reseeder of `rand`'s `ReseedingRng`.
+//!
+//! # The default source
+//!
+//! [`SeedableRng::from_entropy`] and `rand`'s `ThreadRng` use
+//! [`DefaultEntropy`], which by default forwards to `getrandom`. A different
+//! source may be installed for the whole process with [`set_default_source`],
+//! e.g. at the start of `main`:
+//!
+//! ```
+//! use rand_core::entropy::{self, EntropySource, RdRand};
+//! use rand_core::Error;
+//!
+//! fn fill(dest: &mut [u8]) -> Result<(), Error> {
+//!     RdRand::new()?.fill_entropy(dest)
+//! }
+//!
+//! if RdRand::is_available() {
+//!     entropy::set_default_source(fill);
+//! }
+//! # entropy::reset_default_source();
+//! ```
+//!
+//! [`OsRng`]: crate::OsRng
+//! [`SeedableRng::from_entropy`]: crate::SeedableRng::from_entropy
+//! [`CryptoRng`]: crate::CryptoRng
+
+use crate::{impls, Error, RngCore};
+use core::fmt;
+use core::mem;
+use core::num::NonZeroU32;
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "alloc")] use alloc::boxed::Box;
+
+/// A source of entropy.
+///
+/// Unlike [`RngCore`], this trait has a single fallible method, and does not
+/// imply that the source is cheap to use: sources typically make a system
+/// call or execute a slow instruction for each request. It is used to seed
+/// generators, e.g. with [`SeedableRng::from_entropy_source`].
+///
+/// [`SeedableRng::from_entropy_source`]: crate::SeedableRng::from_entropy_source
+pub trait EntropySource {
+    /// Fill `dest` entirely with entropy.
+    ///
+    /// On failure the contents of `dest` are unspecified.
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error>;
+}
+
+impl<S: EntropySource + ?Sized> EntropySource for &mut S {
+    #[inline(always)]
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        (**self).fill_entropy(dest)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: EntropySource + ?Sized> EntropySource for Box<S> {
+    #[inline(always)]
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        (**self).fill_entropy(dest)
+    }
+}
+
+#[cfg(feature = "getrandom")]
+impl EntropySource for crate::OsRng {
+    #[inline]
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        getrandom::getrandom(dest)?;
+        Ok(())
+    }
+}
+
+fn error(code: u32) -> Error {
+    Error::from(NonZeroU32::new(code).unwrap())
+}
+
+/// The signature of a function which may be installed as the default source
+/// with [`set_default_source`].
+pub type EntropyFn = fn(&mut [u8]) -> Result<(), Error>;
+
+// The installed `EntropyFn`, or zero if none is installed.
+static DEFAULT_SOURCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Install `source` as the default entropy source of the process.
+///
+/// This affects all subsequent uses of [`DefaultEntropy`], including
+/// [`SeedableRng::from_entropy`] and the (re)seeding of `rand`'s `ThreadRng`.
+/// The source must be suitable for seeding cryptographic generators unless
+/// this is only done for testing.
+///
+/// The source must not use [`DefaultEntropy`] itself, nor `thread_rng` or
+/// `ThreadRng`: it may be called while `ThreadRng` reseeds, and such use
+/// panics. With debug assertions, using `DefaultEntropy` from the source
+/// panics too, instead of recursing.
+///
+/// [`SeedableRng::from_entropy`]: crate::SeedableRng::from_entropy
+pub fn set_default_source(source: EntropyFn) {
+    DEFAULT_SOURCE.store(source as usize, Ordering::SeqCst);
+}
+
+/// Restore `getrandom` as the default entropy source.
+pub fn reset_default_source() {
+    DEFAULT_SOURCE.store(0, Ordering::SeqCst);
+}
+
+/// The process-wide default entropy source.
+///
+/// This uses the function installed with [`set_default_source`] if any, and
+/// otherwise `getrandom`. Without the `getrandom` feature and with no source
+/// installed, it fails with [`Error::ENTROPY_UNAVAILABLE`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultEntropy;
+
+impl EntropySource for DefaultEntropy {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        match DEFAULT_SOURCE.load(Ordering::SeqCst) {
+            0 => {
+                #[cfg(feature = "getrandom")]
+                {
+                    crate::OsRng.fill_entropy(dest)
+                }
+                #[cfg(not(feature = "getrandom"))]
+                {
+                    let _ = dest;
+                    Err(error(Error::ENTROPY_UNAVAILABLE))
+                }
+            }
+            f => {
+                // SAFETY: only `set_default_source` stores non-zero values,
+                // which are `EntropyFn` pointers.
+                let f: EntropyFn = unsafe { mem::transmute(f) };
+                #[cfg(all(feature = "std", debug_assertions))]
+                let _guard = SourceGuard::enter();
+                f(dest)
+            }
+        }
+    }
+}
+
+crate::impl_crypto_rng_via_try_fill!(DefaultEntropy, EntropySource::fill_entropy);
+
+#[cfg(all(feature = "std", debug_assertions))]
+std::thread_local!(
+    // `const` initializers require Rust 1.59
+    #[allow(clippy::missing_const_for_thread_local)]
+    static IN_SOURCE: core::cell::Cell<bool> = core::cell::Cell::new(false);
+);
+
+// Marks that the installed source is running on this thread, to detect
+// re-entrant use of `DefaultEntropy` by the source.
+#[cfg(all(feature = "std", debug_assertions))]
+struct SourceGuard;
+
+#[cfg(all(feature = "std", debug_assertions))]
+impl SourceGuard {
+    fn enter() -> Self {
+        if IN_SOURCE.with(|flag| flag.replace(true)) {
+            panic!("DefaultEntropy used by the installed default source");
+        }
+        SourceGuard
+    }
+}
+
+#[cfg(all(feature = "std", debug_assertions))]
+impl Drop for SourceGuard {
+    fn drop(&mut self) {
+        IN_SOURCE.with(|flag| flag.set(false));
+    }
+}
+
+/// An entropy source reading from `/dev/urandom`.
+///
+/// On Linux, `getrandom` (and hence [`OsRng`]) is preferable since it does not
+/// need a file descriptor, and blocks until the system generator has been
+/// seeded at boot. This source is useful where the device is the mandated
+/// source, or where the system call is not available, e.g. in some sandboxes.
+///
+/// [`OsRng`]: crate::OsRng
+#[cfg(all(feature = "std", unix))]
+#[cfg_attr(doc_cfg, doc(cfg(all(feature = "std", unix))))]
+#[derive(Debug)]
+pub struct DevUrandom {
+    file: std::fs::File,
+}
+
+#[cfg(all(feature = "std", unix))]
+impl DevUrandom {
+    /// Open `/dev/urandom`.
+    pub fn new() -> Result<Self, Error> {
+        let file = std::fs::File::open("/dev/urandom").map_err(Error::new)?;
+        Ok(DevUrandom { file })
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl EntropySource for DevUrandom {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        use std::io::Read;
+        self.file.read_exact(dest).map_err(Error::new)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+crate::impl_crypto_rng_via_try_fill!(DevUrandom, EntropySource::fill_entropy);
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")] use core::arch::x86 as arch;
+    #[cfg(target_arch = "x86_64")] use core::arch::x86_64 as arch;
+
+    #[cfg(target_arch = "x86")] pub type Word = u32;
+    #[cfg(target_arch = "x86_64")] pub type Word = u64;
+
+    fn has_cpuid() -> bool {
+        #[cfg(target_arch = "x86")]
+        {
+            arch::has_cpuid()
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
+            true
+        }
+    }
+
+    #[allow(unused_unsafe)] // `cpuid` is safe since Rust 1.87
+    pub fn has_rdrand() -> bool {
+        // SAFETY: `cpuid` is available, and leaf 1 always exists.
+        has_cpuid() && unsafe { arch::__cpuid(1).ecx & (1 << 30) != 0 }
+    }
+
+    #[allow(unused_unsafe)]
+    pub fn has_rdseed() -> bool {
+        // SAFETY: `cpuid` is available, and leaf 7 exists if the maximum leaf
+        // is at least 7.
+        has_cpuid()
+            && unsafe {
+                arch::__get_cpuid_max(0).0 >= 7 && arch::__cpuid_count(7, 0).ebx & (1 << 18) != 0
+            }
+    }
+
+    #[target_feature(enable = "rdrand")]
+    pub unsafe fn rdrand(word: &mut Word) -> bool {
+        #[cfg(target_arch = "x86")]
+        {
+            arch::_rdrand32_step(word) == 1
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
+            arch::_rdrand64_step(word) == 1
+        }
+    }
+
+    #[target_feature(enable = "rdseed")]
+    pub unsafe fn rdseed(word: &mut Word) -> bool {
+        #[cfg(target_arch = "x86")]
+        {
+            arch::_rdseed32_step(word) == 1
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
+            arch::_rdseed64_step(word) == 1
+        }
+    }
+}
+
+// Fill `dest` using `step`, retrying each word up to `retries` times.
+//
+// Some AMD CPUs return all ones (with the success flag set) after a
+// suspend/resume cycle; like `getrandom`, we treat this value as a failure.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn fill_via_instruction(
+    dest: &mut [u8], retries: usize, step: unsafe fn(&mut x86::Word) -> bool,
+) -> Result<(), Error> {
+    for chunk in dest.chunks_mut(mem::size_of::<x86::Word>()) {
+        let mut word = 0;
+        let mut ok = false;
+        for _ in 0..retries {
+            // SAFETY: the caller checked that the instruction is supported.
+            if unsafe { step(&mut word) } && word != !0 {
+                ok = true;
+                break;
+            }
+            #[allow(deprecated)] // spin_loop is only stable since 1.49
+            core::sync::atomic::spin_loop_hint();
+        }
+        if !ok {
+            return Err(error(Error::ENTROPY_FAILURE));
+        }
+        chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+    }
+    Ok(())
+}
+
+/// An entropy source using the x86 `RDRAND` instruction.
+///
+/// `RDRAND` returns the output of a DRBG inside the CPU which is reseeded
+/// from a hardware noise source. It is fast, but relies on trusting the CPU
+/// vendor; it is best combined with other sources.
+///
+/// Each word is retried up to ten times, as recommended by Intel, before
+/// failing with [`Error::ENTROPY_FAILURE`].
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Clone, Copy, Debug)]
+pub struct RdRand(());
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl RdRand {
+    /// Construct, or fail with [`Error::ENTROPY_UNAVAILABLE`] if the CPU does
+    /// not support `RDRAND`.
+    pub fn new() -> Result<Self, Error> {
+        if Self::is_available() {
+            Ok(RdRand(()))
+        } else {
+            Err(error(Error::ENTROPY_UNAVAILABLE))
+        }
+    }
+
+    /// Whether the CPU supports `RDRAND`.
+    pub fn is_available() -> bool {
+        x86::has_rdrand()
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl EntropySource for RdRand {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        fill_via_instruction(dest, 10, x86::rdrand)
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+crate::impl_crypto_rng_via_try_fill!(RdRand, EntropySource::fill_entropy);
+
+/// An entropy source using the x86 `RDSEED` instruction.
+///
+/// `RDSEED` returns conditioned output of the CPU's hardware noise source, and
+/// is intended for seeding other generators. It is slower than `RDRAND` and
+/// fails transiently when its output is exhausted; each word is retried up to
+/// 1000 times before failing with [`Error::ENTROPY_FAILURE`].
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Clone, Copy, Debug)]
+pub struct RdSeed(());
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl RdSeed {
+    /// Construct, or fail with [`Error::ENTROPY_UNAVAILABLE`] if the CPU does
+    /// not support `RDSEED`.
+    pub fn new() -> Result<Self, Error> {
+        if Self::is_available() {
+            Ok(RdSeed(()))
+        } else {
+            Err(error(Error::ENTROPY_UNAVAILABLE))
+        }
+    }
+
+    /// Whether the CPU supports `RDSEED`.
+    pub fn is_available() -> bool {
+        x86::has_rdseed()
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl EntropySource for RdSeed {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        fill_via_instruction(dest, 1000, x86::rdseed)
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+crate::impl_crypto_rng_via_try_fill!(RdSeed, EntropySource::fill_entropy);
+
+/// A deterministic entropy source for tests.
+///
+/// The output is the SplitMix64 sequence for the given seed, which is
+/// *not* random: this source must only be used to make code which seeds
+/// generators reproducible in tests. Optionally, a limit on the number of
+/// bytes makes the source fail with [`Error::ENTROPY_FAILURE`] once
+/// exhausted, to test error handling.
+///
+/// Unlike the other sources, `TestEntropy` implements [`RngCore`] but not
+/// [`CryptoRng`].
+///
+/// ```
+/// use rand_core::entropy::{EntropySource, TestEntropy};
+///
+/// let mut source = TestEntropy::new(1).with_limit(16);
+/// let mut seed = [0u8; 16];
+/// assert!(source.fill_entropy(&mut seed).is_ok());
+/// assert_eq!(source.bytes_read(), 16);
+/// assert!(source.fill_entropy(&mut seed).is_err());
+/// ```
+///
+/// [`CryptoRng`]: crate::CryptoRng
+#[derive(Clone, PartialEq, Eq)]
+pub struct TestEntropy {
+    state: u64,
+    bytes_read: u64,
+    limit: Option<u64>,
+}
+
+impl TestEntropy {
+    /// Construct with the given seed and no limit.
+    pub fn new(seed: u64) -> Self {
+        TestEntropy {
+            state: seed,
+            bytes_read: 0,
+            limit: None,
+        }
+    }
+
+    /// Fail all requests which would read more than `limit` bytes in total.
+    pub fn with_limit(self, limit: u64) -> Self {
+        TestEntropy {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// The number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        impls::mix64(self.state)
+    }
+}
+
+// Custom Debug implementation that does not expose the internal state
+impl fmt::Debug for TestEntropy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TestEntropy")
+            .field("bytes_read", &self.bytes_read)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl EntropySource for TestEntropy {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let bytes_read = self.bytes_read.saturating_add(dest.len() as u64);
+        if let Some(limit) = self.limit {
+            if bytes_read > limit {
+                return Err(error(Error::ENTROPY_FAILURE));
+            }
+        }
+        for chunk in dest.chunks_mut(8) {
+            let x = self.next();
+            chunk.copy_from_slice(&x.to_le_bytes()[..chunk.len()]);
+        }
+        self.bytes_read = bytes_read;
+        Ok(())
+    }
+}
+
+impl RngCore for TestEntropy {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.fill_entropy(dest) {
+            panic!("Error: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_entropy(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SeedableRng;
+
+    // A trivial generator, to check seeding
+    #[derive(Debug)]
+    struct SeedRng([u8; 20]);
+
+    impl SeedableRng for SeedRng {
+        type Seed = [u8; 20];
+
+        fn from_seed(seed: Self::Seed) -> Self {
+            SeedRng(seed)
+        }
+    }
+
+    #[test]
+    fn test_test_entropy() {
+        let mut a = TestEntropy::new(7);
+        let mut b = TestEntropy::new(7);
+        let mut buf = [0u8; 13];
+        a.fill_entropy(&mut buf).unwrap();
+        // Reading in other chunks gives a different stream; only whole words
+        // are consistent.
+        assert_eq!(&buf[..8], &b.next_u64().to_le_bytes()[..]);
+        assert_eq!(a.bytes_read(), 13);
+        assert_ne!(TestEntropy::new(8).next_u64(), TestEntropy::new(7).next_u64());
+
+        let rng1 = SeedRng::from_entropy_source(&mut TestEntropy::new(1)).unwrap();
+        let rng2 = SeedRng::from_entropy_source(&mut TestEntropy::new(1)).unwrap();
+        assert_eq!(rng1.0, rng2.0);
+
+        let mut limited = TestEntropy::new(1).with_limit(30);
+        assert!(SeedRng::from_entropy_source(&mut limited).is_ok());
+        let err = SeedRng::from_entropy_source(&mut limited).unwrap_err();
+        assert_eq!(err.code().unwrap().get(), Error::ENTROPY_FAILURE);
+        assert_eq!(limited.bytes_read(), 20);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Miri does not support these instructions
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    fn test_rdrand_rdseed() {
+        let mut buf = [0u8; 33];
+        match RdRand::new() {
+            Ok(mut rng) => {
+                rng.fill_entropy(&mut buf).unwrap();
+                assert_ne!(rng.next_u64(), rng.next_u64());
+            }
+            Err(e) => assert_eq!(e.code().unwrap().get(), Error::ENTROPY_UNAVAILABLE),
+        }
+        match RdSeed::new() {
+            Ok(mut rng) => {
+                rng.fill_entropy(&mut buf).unwrap();
+                assert_ne!(rng.next_u64(), rng.next_u64());
+            }
+            Err(e) => assert_eq!(e.code().unwrap().get(), Error::ENTROPY_UNAVAILABLE),
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", unix))]
+    #[cfg_attr(miri, ignore)] // Miri does not support opening files
+    fn test_dev_urandom() {
+        let mut rng = DevUrandom::new().unwrap();
+        let mut buf = [0u8; 1000];
+        rng.fill_entropy(&mut buf).unwrap();
+        assert!(buf.iter().any(|&x| x != 0));
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+}
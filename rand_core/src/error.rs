@@ -41,6 +41,32 @@ impl Error {
     /// This is identical to [`getrandom::Error::INTERNAL_START`](https://docs.rs/getrandom/latest/getrandom/struct.Error.html#associatedconstant.INTERNAL_START).
     pub const INTERNAL_START: u32 = 1 << 31;
 
+    /// The entropy source is not available, e.g. the CPU does not support the
+    /// instruction it relies on, or no default source is configured.
+    ///
+    /// This is an internal code, in the upper half of the range reserved for
+    /// the `rand` and `getrandom` crates.
+    pub const ENTROPY_UNAVAILABLE: u32 = Self::INTERNAL_START + (1 << 29);
+    /// The entropy source failed to produce output, e.g. a hardware source
+    /// did not return a value after several retries.
+    pub const ENTROPY_FAILURE: u32 = Self::INTERNAL_START + (1 << 29) + 1;
+    /// The output of an entropy source failed a health check, e.g. it is
+    /// stuck at a constant value or repeats itself.
+    pub const ENTROPY_HEALTH_FAILURE: u32 = Self::INTERNAL_START + (1 << 29) + 2;
+    /// The output of an entropy source failed the repetition count test of
+    /// NIST SP 800-90B: a sample was repeated too many times in a row.
+    pub const ENTROPY_REPETITION_COUNT_FAILURE: u32 = Self::INTERNAL_START + (1 << 29) + 3;
+    /// The output of an entropy source failed the adaptive proportion test of
+    /// NIST SP 800-90B: a sample was too frequent within a window.
+    pub const ENTROPY_ADAPTIVE_PROPORTION_FAILURE: u32 = Self::INTERNAL_START + (1 << 29) + 4;
+    /// The generator must be reseeded before producing more output, e.g. a
+    /// DRBG reached its reseed interval.
+    pub const RESEED_REQUIRED: u32 = Self::INTERNAL_START + (1 << 29) + 5;
+    /// An input to a DRBG (entropy input, nonce, personalization string or
+    /// additional input) has an invalid length, or too many bytes were
+    /// requested at once.
+    pub const DRBG_INVALID_REQUEST: u32 = Self::INTERNAL_START + (1 << 29) + 6;
+
     /// Construct from any type supporting `std::error::Error`
     ///
     /// Available only when configured with `std`.
@@ -138,6 +164,12 @@ impl fmt::Display for Error {
         {
             write!(f, "{}", self.inner)
         }
+        #[cfg(not(feature = "std"))]
+        {
+            if let Some(desc) = code_description(self.code) {
+                return f.write_str(desc);
+            }
+        }
         #[cfg(all(feature = "getrandom", not(feature = "std")))]
         {
             getrandom::Error::from(self.code).fmt(f)
@@ -149,6 +181,24 @@ impl fmt::Display for Error {
     }
 }
 
+/// Describe the codes defined by `rand_core`.
+fn code_description(code: NonZeroU32) -> Option<&'static str> {
+    match code.get() {
+        Error::ENTROPY_UNAVAILABLE => Some("entropy source unavailable"),
+        Error::ENTROPY_FAILURE => Some("entropy source failed"),
+        Error::ENTROPY_HEALTH_FAILURE => Some("entropy source failed a health check"),
+        Error::ENTROPY_REPETITION_COUNT_FAILURE => {
+            Some("entropy source failed the repetition count test")
+        }
+        Error::ENTROPY_ADAPTIVE_PROPORTION_FAILURE => {
+            Some("entropy source failed the adaptive proportion test")
+        }
+        Error::RESEED_REQUIRED => Some("generator must be reseeded"),
+        Error::DRBG_INVALID_REQUEST => Some("invalid DRBG input length or request size"),
+        _ => None,
+    }
+}
+
 impl From<NonZeroU32> for Error {
     #[inline]
     fn from(code: NonZeroU32) -> Self {
@@ -209,7 +259,10 @@ struct ErrorCode(NonZeroU32);
 #[cfg(feature = "std")]
 impl fmt::Display for ErrorCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "error code {}", self.0)
+        match code_description(self.0) {
+            Some(desc) => f.write_str(desc),
+            None => write!(f, "error code {}", self.0),
+        }
     }
 }
 
@@ -225,4 +278,16 @@ mod test {
         assert_eq!(super::Error::CUSTOM_START, getrandom::Error::CUSTOM_START);
         assert_eq!(super::Error::INTERNAL_START, getrandom::Error::INTERNAL_START);
     }
+
+    #[test]
+    fn test_code_description() {
+        use super::Error;
+        use core::num::NonZeroU32;
+
+        let err = Error::from(NonZeroU32::new(Error::ENTROPY_FAILURE).unwrap());
+        assert_eq!(err.code().unwrap().get(), Error::ENTROPY_FAILURE);
+        assert_eq!(err.raw_os_error(), None);
+        #[cfg(feature = "std")]
+        assert_eq!(std::format!("{}", err), "entropy source failed");
+    }
 }
@@ -146,10 +146,86 @@ pub fn next_u64_via_fill<R: RngCore + ?Sized>(rng: &mut R) -> u64 {
     u64::from_le_bytes(buf)
 }
 
+/// The output function of SplitMix64, a bijection on `u64`.
+///
+/// Every input bit affects every output bit, which makes this suitable to
+/// derive the state of a child in [`SplittableRng::fork`] from the state of
+/// its parent and its index.
+///
+/// [`SplittableRng::fork`]: crate::SplittableRng::fork
+#[inline]
+pub fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Implement [`RngCore`] and [`CryptoRng`] for a type via a fallible fill
+/// function.
+///
+/// `$fill` is called as `$fill(self, dest)` and must return
+/// `Result<(), Error>`. `try_fill_bytes` forwards to it, `fill_bytes` panics
+/// on error, and `next_u32` and `next_u64` use `fill_bytes`. This suits
+/// entropy sources and other cryptographic generators which may fail.
+///
+/// ```
+/// use rand_core::{impl_crypto_rng_via_try_fill, Error, RngCore};
+///
+/// struct Zeros;
+///
+/// fn fill_zeros(_: &mut Zeros, dest: &mut [u8]) -> Result<(), Error> {
+///     for byte in dest.iter_mut() {
+///         *byte = 0;
+///     }
+///     Ok(())
+/// }
+///
+/// impl_crypto_rng_via_try_fill!(Zeros, fill_zeros);
+///
+/// assert_eq!(Zeros.next_u64(), 0);
+/// ```
+///
+/// [`CryptoRng`]: crate::CryptoRng
+#[macro_export]
+macro_rules! impl_crypto_rng_via_try_fill {
+    ($ty:ty, $fill:path) => {
+        impl $crate::RngCore for $ty {
+            fn next_u32(&mut self) -> u32 {
+                $crate::impls::next_u32_via_fill(self)
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                $crate::impls::next_u64_via_fill(self)
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                if let Err(e) = $fill(self, dest) {
+                    panic!("Error: {}", e);
+                }
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), $crate::Error> {
+                $fill(self, dest)
+            }
+        }
+
+        impl $crate::CryptoRng for $ty {}
+    };
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_mix64() {
+        // The first outputs of SplitMix64 seeded with zero
+        let gamma = 0x9e3779b97f4a7c15u64;
+        assert_eq!(mix64(gamma), 0xe220a8397b1dcdaf);
+        assert_eq!(mix64(gamma.wrapping_mul(2)), 0x6e789e6aa1b965f4);
+        assert_eq!(mix64(0), 0);
+    }
+
     #[test]
     fn test_fill_via_u32_chunks() {
         let src = [1, 2, 3];
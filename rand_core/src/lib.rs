@@ -25,6 +25,12 @@
 //! The [`impls`] and [`le`] sub-modules include a few small functions to assist
 //! implementation of [`RngCore`].
 //!
+//! The [`snapshot`] sub-module allows checkpointing generators in a versioned
+//! binary format.
+//!
+//! The [`entropy`] sub-module provides sources of entropy for seeding
+//! generators, and allows configuring the default source.
+//!
 //! [`rand`]: https://docs.rs/rand
 
 #![doc(
@@ -40,6 +46,7 @@
 
 use core::convert::AsMut;
 use core::default::Default;
+use core::fmt;
 
 #[cfg(feature = "std")] extern crate std;
 #[cfg(feature = "alloc")] extern crate alloc;
@@ -50,9 +57,11 @@ pub use error::Error;
 
 
 pub mod block;
+pub mod entropy;
 mod error;
 pub mod impls;
 pub mod le;
+pub mod snapshot;
 #[cfg(feature = "getrandom")] mod os;
 
 
@@ -359,28 +368,148 @@ pub trait SeedableRng: Sized {
         Ok(Self::from_seed(seed))
     }
 
-    /// Creates a new instance of the RNG seeded via [`getrandom`].
+    /// Create a new PRNG seeded from an [`EntropySource`].
+    ///
+    /// Unlike [`SeedableRng::from_entropy`], this allows choosing the source,
+    /// e.g. a deterministic [`TestEntropy`] in tests.
+    ///
+    /// [`EntropySource`]: entropy::EntropySource
+    /// [`TestEntropy`]: entropy::TestEntropy
+    fn from_entropy_source<S: entropy::EntropySource + ?Sized>(
+        source: &mut S,
+    ) -> Result<Self, Error> {
+        let mut seed = Self::Seed::default();
+        source.fill_entropy(seed.as_mut())?;
+        Ok(Self::from_seed(seed))
+    }
+
+    /// Creates a new instance of the RNG seeded via the default entropy source.
     ///
     /// This method is the recommended way to construct non-deterministic PRNGs
     /// since it is convenient and secure.
     ///
+    /// The default source is [`getrandom`], unless another source was
+    /// installed with [`entropy::set_default_source`].
+    ///
     /// In case the overhead of using [`getrandom`] to seed *many* PRNGs is an
     /// issue, one may prefer to seed from a local PRNG, e.g.
     /// `from_rng(thread_rng()).unwrap()`.
     ///
     /// # Panics
     ///
-    /// If [`getrandom`] is unable to provide secure entropy this method will panic.
+    /// If the default source is unable to provide secure entropy this method
+    /// will panic.
     ///
     /// [`getrandom`]: https://docs.rs/getrandom
     #[cfg(feature = "getrandom")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "getrandom")))]
     fn from_entropy() -> Self {
-        let mut seed = Self::Seed::default();
-        if let Err(err) = getrandom::getrandom(seed.as_mut()) {
-            panic!("from_entropy failed: {}", err);
+        match Self::from_entropy_source(&mut entropy::DefaultEntropy) {
+            Ok(rng) => rng,
+            Err(err) => panic!("from_entropy failed: {}", err),
         }
-        Self::from_seed(seed)
+    }
+}
+
+/// A random number generator whose position in its output sequence can be
+/// queried and changed.
+///
+/// Positions are measured in *words*: one word is one step of the generator,
+/// i.e. one `u32` for generators with 32-bit output and one `u64` for
+/// generators with 64-bit output. Generators with a 64-bit word consume a
+/// whole word on [`RngCore::next_u32`].
+///
+/// Seeking is useful to reproduce part of the output of a generator without
+/// generating what precedes it, or to skip over output which was consumed
+/// elsewhere:
+///
+/// ```
+/// # #![allow(dead_code)]
+/// use rand_core::SeekableRng;
+///
+/// fn reproduce<R: SeekableRng>(rng: &mut R) {
+///     let start = rng.get_position();
+///     let x = rng.next_u64();
+///     rng.set_position(start);
+///     assert_eq!(rng.next_u64(), x);
+/// }
+/// ```
+///
+/// # Implementing `SeekableRng`
+///
+/// Generators with an output buffer, such as those based on
+/// [`BlockRng`](block::BlockRng), must account for buffered output which was
+/// not consumed yet. Block generators should implement
+/// [`SeekableBlockRngCore`](block::SeekableBlockRngCore) for their core,
+/// then forward the methods of this trait to `BlockRng`, which takes care of
+/// the buffer.
+pub trait SeekableRng: RngCore {
+    /// Position type.
+    ///
+    /// For generators with a natural origin in their output sequence, such as
+    /// counter-based generators, this is the number of words from the origin.
+    /// Other generators may use an opaque type, which can only be used to
+    /// return to a previously recorded position.
+    type Position: Copy + PartialEq + fmt::Debug;
+
+    /// Get the position of the next output word.
+    fn get_position(&self) -> Self::Position;
+
+    /// Set the position, such that the next output word is the word at `pos`.
+    fn set_position(&mut self, pos: Self::Position);
+
+    /// Advance the generator by `delta` words.
+    ///
+    /// This is equivalent to discarding `delta` words of output, but usually
+    /// much faster. If the period of the generator is shorter than 2^128,
+    /// `delta` is reduced modulo the period.
+    ///
+    /// Not available on Emscripten, which does not support `u128`.
+    #[cfg(not(target_os = "emscripten"))]
+    fn advance(&mut self, delta: u128);
+}
+
+/// A random number generator which can derive child generators from its
+/// state.
+///
+/// Children are derived without consuming the output of the parent. With
+/// overwhelming probability, the sequences of a parent and of its children
+/// with different indices do not overlap. Since a child only depends on the
+/// state of its parent and on its index, a tree of generators built with
+/// [`fork`](SplittableRng::fork) is reproducible, regardless of the order in
+/// which its nodes are created, e.g. when a task tree is scheduled on a
+/// thread pool:
+///
+/// ```
+/// # #![allow(dead_code)]
+/// use rand_core::SplittableRng;
+///
+/// fn sum_tree<R: SplittableRng>(rng: &R, depth: u32) -> u64 {
+///     if depth == 0 {
+///         return rng.fork(0).next_u64() >> 32;
+///     }
+///     // Each subtree gets its own generator, however it is scheduled
+///     sum_tree(&rng.fork(1), depth - 1) + sum_tree(&rng.fork(2), depth - 1)
+/// }
+/// ```
+///
+/// How children are derived depends on the generator, which documents what
+/// its children have in common with its parent (e.g. a key).
+pub trait SplittableRng: RngCore + Sized {
+    /// Derive the child generator with the given `index`.
+    ///
+    /// This does not modify `self`: calling it again with the same index
+    /// gives the same child.
+    fn fork(&self, index: u64) -> Self;
+
+    /// Split off a new child generator.
+    ///
+    /// The child is `fork(0)`, and `self` is replaced by `fork(1)`, such that
+    /// the children of successive calls differ.
+    fn split(&mut self) -> Self {
+        let child = self.fork(0);
+        *self = self.fork(1);
+        child
     }
 }
 
@@ -435,6 +564,49 @@ impl<R: RngCore + ?Sized> RngCore for Box<R> {
     }
 }
 
+// Implement `SeekableRng` for references to a `SeekableRng`.
+impl<R: SeekableRng + ?Sized> SeekableRng for &mut R {
+    type Position = R::Position;
+
+    #[inline(always)]
+    fn get_position(&self) -> Self::Position {
+        (**self).get_position()
+    }
+
+    #[inline(always)]
+    fn set_position(&mut self, pos: Self::Position) {
+        (**self).set_position(pos)
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[inline(always)]
+    fn advance(&mut self, delta: u128) {
+        (**self).advance(delta)
+    }
+}
+
+// Implement `SeekableRng` for boxed references to a `SeekableRng`.
+#[cfg(feature = "alloc")]
+impl<R: SeekableRng + ?Sized> SeekableRng for Box<R> {
+    type Position = R::Position;
+
+    #[inline(always)]
+    fn get_position(&self) -> Self::Position {
+        (**self).get_position()
+    }
+
+    #[inline(always)]
+    fn set_position(&mut self, pos: Self::Position) {
+        (**self).set_position(pos)
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[inline(always)]
+    fn advance(&mut self, delta: u128) {
+        (**self).advance(delta)
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::io::Read for dyn RngCore {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
@@ -0,0 +1,423 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Versioned snapshots of the state of a generator.
+//!
+//! [`Snapshot`] exports the state of a generator as a self-describing byte
+//! blob, from which a generator can be restored to produce exactly the same
+//! output. This is intended to checkpoint long-running computations; unlike
+//! `serde` support, the format is independent of the layout of the generator
+//! types, and is stable for a given algorithm and version.
+//!
+//! # Format
+//!
+//! A snapshot consists of the following fields, with integers in
+//! little-endian byte order:
+//!
+//! | Size | Field                                               |
+//! | ---- | --------------------------------------------------- |
+//! | 4    | magic bytes `b"RNGS"`                               |
+//! | 1    | length `n` of the algorithm identifier              |
+//! | `n`  | algorithm identifier ([`Snapshot::ALGORITHM`])      |
+//! | 2    | version of the state format ([`Snapshot::VERSION`]) |
+//! | 4    | length `m` of the state                             |
+//! | `m`  | state ([`SnapshotState::write_state`])              |
+//!
+//! The state of generators using [`BlockRng`] or [`BlockRng64`] includes the
+//! output buffer and the index into it, so that no buffered output is lost.
+//!
+//! [`BlockRng`]: crate::block::BlockRng
+//! [`BlockRng64`]: crate::block::BlockRng64
+
+use core::fmt;
+#[cfg(feature = "alloc")] use alloc::vec::Vec;
+
+const MAGIC: [u8; 4] = *b"RNGS";
+// Length of the header, excluding the algorithm identifier
+const HEADER_LEN: usize = 4 + 1 + 2 + 4;
+
+/// Error type of [`Snapshot::export_snapshot`] and
+/// [`Snapshot::from_snapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The destination buffer is too small.
+    BufferTooSmall,
+    /// The data is not a snapshot, or has the wrong length.
+    InvalidFormat,
+    /// The snapshot is of a different algorithm.
+    AlgorithmMismatch,
+    /// The version of the state format is not supported.
+    UnsupportedVersion,
+    /// The state is not a valid state of the generator.
+    InvalidState,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SnapshotError::BufferTooSmall => "buffer too small for snapshot",
+            SnapshotError::InvalidFormat => "invalid snapshot format",
+            SnapshotError::AlgorithmMismatch => "snapshot of a different algorithm",
+            SnapshotError::UnsupportedVersion => "unsupported snapshot version",
+            SnapshotError::InvalidState => "invalid generator state in snapshot",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnapshotError {}
+
+/// Writes the state of a generator, see [`SnapshotState::write_state`].
+///
+/// The methods panic when writing past the end of the buffer, which is
+/// [`SnapshotState::STATE_LEN`] bytes long.
+#[derive(Debug)]
+pub struct StateWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> StateWriter<'a> {
+    /// Create a writer to the start of `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        StateWriter { buf, pos: 0 }
+    }
+
+    /// Get the number of bytes written.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Write a byte slice.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        let end = self.pos + bytes.len();
+        self.buf[self.pos..end].copy_from_slice(bytes);
+        self.pos = end;
+    }
+
+    /// Write a `u8`.
+    pub fn write_u8(&mut self, x: u8) {
+        self.write_bytes(&[x]);
+    }
+
+    /// Write a `u32` in little-endian order.
+    pub fn write_u32(&mut self, x: u32) {
+        self.write_bytes(&x.to_le_bytes());
+    }
+
+    /// Write a `u64` in little-endian order.
+    pub fn write_u64(&mut self, x: u64) {
+        self.write_bytes(&x.to_le_bytes());
+    }
+
+    /// Write a `u128` in little-endian order.
+    ///
+    /// Not available on Emscripten; write two `u64` words, low word first,
+    /// for the same encoding.
+    #[cfg(not(target_os = "emscripten"))]
+    pub fn write_u128(&mut self, x: u128) {
+        self.write_bytes(&x.to_le_bytes());
+    }
+}
+
+/// Reads the state of a generator, see [`SnapshotState::read_state`].
+///
+/// The methods panic when reading past the end of the buffer, which is
+/// [`SnapshotState::STATE_LEN`] bytes long.
+#[derive(Debug)]
+pub struct StateReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /// Create a reader from the start of `buf`.
+    pub fn new(buf: &'a [u8]) -> Self {
+        StateReader { buf, pos: 0 }
+    }
+
+    /// Get the number of bytes read.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Fill `dest` with the next bytes.
+    pub fn read_bytes(&mut self, dest: &mut [u8]) {
+        let end = self.pos + dest.len();
+        dest.copy_from_slice(&self.buf[self.pos..end]);
+        self.pos = end;
+    }
+
+    /// Read a `u8`.
+    pub fn read_u8(&mut self) -> u8 {
+        let mut buf = [0; 1];
+        self.read_bytes(&mut buf);
+        buf[0]
+    }
+
+    /// Read a `u32` in little-endian order.
+    pub fn read_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.read_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    /// Read a `u64` in little-endian order.
+    pub fn read_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.read_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    /// Read a `u128` in little-endian order.
+    ///
+    /// Not available on Emscripten; see [`StateWriter::write_u128`].
+    #[cfg(not(target_os = "emscripten"))]
+    pub fn read_u128(&mut self) -> u128 {
+        let mut buf = [0; 16];
+        self.read_bytes(&mut buf);
+        u128::from_le_bytes(buf)
+    }
+}
+
+/// A generator (or generator core) whose state can be written to and read
+/// from a fixed-size byte representation.
+///
+/// This is the building block of [`Snapshot`]; it is implemented separately
+/// so that wrappers such as [`BlockRng`] can include the state of their core.
+///
+/// [`BlockRng`]: crate::block::BlockRng
+pub trait SnapshotState: Sized {
+    /// Length of the state in bytes.
+    const STATE_LEN: usize;
+
+    /// Write exactly [`STATE_LEN`](Self::STATE_LEN) bytes of state to `w`.
+    fn write_state(&self, w: &mut StateWriter);
+
+    /// Read [`STATE_LEN`](Self::STATE_LEN) bytes of state from `r`, as
+    /// written by [`write_state`](Self::write_state).
+    ///
+    /// Returns [`SnapshotError::InvalidState`] if the data is not a valid
+    /// state, e.g. an all-zero state for generators that do not allow one.
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError>;
+}
+
+/// A generator that can export its state to, and be restored from, a
+/// versioned snapshot.
+///
+/// See the [module documentation](crate::snapshot) for the format of
+/// snapshots.
+///
+/// # Example
+///
+/// ```
+/// use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
+/// use rand_core::{impls, Error, RngCore};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct CountingRng(u64);
+///
+/// impl RngCore for CountingRng {
+///     fn next_u32(&mut self) -> u32 {
+///         self.next_u64() as u32
+///     }
+///
+///     fn next_u64(&mut self) -> u64 {
+///         self.0 += 1;
+///         self.0
+///     }
+///
+///     fn fill_bytes(&mut self, dest: &mut [u8]) {
+///         impls::fill_bytes_via_next(self, dest)
+///     }
+///
+///     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+///         Ok(self.fill_bytes(dest))
+///     }
+/// }
+///
+/// impl SnapshotState for CountingRng {
+///     const STATE_LEN: usize = 8;
+///
+///     fn write_state(&self, w: &mut StateWriter) {
+///         w.write_u64(self.0);
+///     }
+///
+///     fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+///         Ok(CountingRng(r.read_u64()))
+///     }
+/// }
+///
+/// impl Snapshot for CountingRng {
+///     const ALGORITHM: &'static str = "Counting";
+///     const VERSION: u16 = 1;
+/// }
+///
+/// let mut rng = CountingRng(0);
+/// rng.next_u64();
+/// let mut snapshot = [0; 64];
+/// let len = rng.export_snapshot(&mut snapshot).unwrap();
+/// let x = rng.next_u64();
+///
+/// let mut restored = CountingRng::from_snapshot(&snapshot[..len]).unwrap();
+/// assert_eq!(restored.next_u64(), x);
+/// ```
+pub trait Snapshot: SnapshotState {
+    /// Identifier of the algorithm, at most 255 bytes of ASCII.
+    ///
+    /// Generators producing the same output from the same state should use
+    /// the same identifier, so that snapshots can be exchanged between them.
+    const ALGORITHM: &'static str;
+
+    /// Version of the state format.
+    ///
+    /// This must be increased whenever the state format changes; snapshots
+    /// of other versions are rejected.
+    const VERSION: u16;
+
+    /// Length of a snapshot in bytes.
+    fn snapshot_len() -> usize {
+        HEADER_LEN + Self::ALGORITHM.len() + Self::STATE_LEN
+    }
+
+    /// Write a snapshot to the start of `dest`, returning its length.
+    fn export_snapshot(&self, dest: &mut [u8]) -> Result<usize, SnapshotError> {
+        let len = Self::snapshot_len();
+        if dest.len() < len {
+            return Err(SnapshotError::BufferTooSmall);
+        }
+        let algorithm = Self::ALGORITHM.as_bytes();
+        debug_assert!(algorithm.len() <= 255);
+        let mut w = StateWriter::new(&mut dest[..len]);
+        w.write_bytes(&MAGIC);
+        w.write_u8(algorithm.len() as u8);
+        w.write_bytes(algorithm);
+        w.write_bytes(&Self::VERSION.to_le_bytes());
+        w.write_u32(Self::STATE_LEN as u32);
+        self.write_state(&mut w);
+        assert_eq!(w.position(), len, "state length differs from STATE_LEN");
+        Ok(len)
+    }
+
+    /// Get a snapshot as a `Vec`.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+    fn to_snapshot(&self) -> Vec<u8> {
+        let mut buf = alloc::vec![0; Self::snapshot_len()];
+        self.export_snapshot(&mut buf).unwrap();
+        buf
+    }
+
+    /// Restore a generator from a snapshot.
+    ///
+    /// `src` must contain exactly one snapshot, of the same algorithm and
+    /// version.
+    fn from_snapshot(src: &[u8]) -> Result<Self, SnapshotError> {
+        let algorithm = Self::ALGORITHM.as_bytes();
+        if src.len() < HEADER_LEN || src[..4] != MAGIC {
+            return Err(SnapshotError::InvalidFormat);
+        }
+        let n = usize::from(src[4]);
+        if src.len() < HEADER_LEN + n {
+            return Err(SnapshotError::InvalidFormat);
+        }
+        let mut r = StateReader::new(&src[5 + n..]);
+        if &src[5..5 + n] != algorithm {
+            return Err(SnapshotError::AlgorithmMismatch);
+        }
+        let mut version = [0; 2];
+        r.read_bytes(&mut version);
+        if u16::from_le_bytes(version) != Self::VERSION {
+            return Err(SnapshotError::UnsupportedVersion);
+        }
+        let state_len = r.read_u32() as usize;
+        if state_len != Self::STATE_LEN || src.len() != Self::snapshot_len() {
+            return Err(SnapshotError::InvalidFormat);
+        }
+        let state = &src[HEADER_LEN + n..];
+        let mut r = StateReader::new(state);
+        let rng = Self::read_state(&mut r)?;
+        assert_eq!(r.position(), state.len(), "state length differs from STATE_LEN");
+        Ok(rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TestRng([u32; 2]);
+
+    impl SnapshotState for TestRng {
+        const STATE_LEN: usize = 8;
+
+        fn write_state(&self, w: &mut StateWriter) {
+            w.write_u32(self.0[0]);
+            w.write_u32(self.0[1]);
+        }
+
+        fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+            let state = [r.read_u32(), r.read_u32()];
+            if state == [0, 0] {
+                return Err(SnapshotError::InvalidState);
+            }
+            Ok(TestRng(state))
+        }
+    }
+
+    impl Snapshot for TestRng {
+        const ALGORITHM: &'static str = "Test";
+        const VERSION: u16 = 3;
+    }
+
+    #[test]
+    fn test_snapshot_format() {
+        let rng = TestRng([0x04030201, 0x08070605]);
+        assert_eq!(TestRng::snapshot_len(), 23);
+        let mut buf = [0xff; 24];
+        assert_eq!(rng.export_snapshot(&mut buf[..22]), Err(SnapshotError::BufferTooSmall));
+        assert_eq!(rng.export_snapshot(&mut buf), Ok(23));
+        let expected = [
+            b'R', b'N', b'G', b'S', 4, b'T', b'e', b's', b't', 3, 0, 8, 0, 0, 0,
+            1, 2, 3, 4, 5, 6, 7, 8, 0xff,
+        ];
+        assert_eq!(buf, expected);
+        assert_eq!(TestRng::from_snapshot(&buf[..23]), Ok(rng));
+        #[cfg(feature = "alloc")]
+        assert_eq!(TestRng([0x04030201, 0x08070605]).to_snapshot(), &buf[..23]);
+    }
+
+    #[test]
+    fn test_snapshot_errors() {
+        let mut buf = [0; 23];
+        TestRng([1, 2]).export_snapshot(&mut buf).unwrap();
+        assert!(TestRng::from_snapshot(&buf).is_ok());
+
+        let invalid = |f: &dyn Fn(&mut [u8])| {
+            let mut buf = buf;
+            f(&mut buf);
+            TestRng::from_snapshot(&buf)
+        };
+        assert_eq!(invalid(&|b| b[0] = b'r'), Err(SnapshotError::InvalidFormat));
+        assert_eq!(invalid(&|b| b[5] = b't'), Err(SnapshotError::AlgorithmMismatch));
+        assert_eq!(invalid(&|b| b[4] = 3), Err(SnapshotError::AlgorithmMismatch));
+        assert_eq!(invalid(&|b| b[4] = 255), Err(SnapshotError::InvalidFormat));
+        assert_eq!(invalid(&|b| b[9] = 2), Err(SnapshotError::UnsupportedVersion));
+        assert_eq!(invalid(&|b| b[11] = 9), Err(SnapshotError::InvalidFormat));
+        assert_eq!(
+            invalid(&|b| b[15..].copy_from_slice(&[0; 8])),
+            Err(SnapshotError::InvalidState)
+        );
+        assert_eq!(TestRng::from_snapshot(&buf[..22]), Err(SnapshotError::InvalidFormat));
+        assert_eq!(TestRng::from_snapshot(&buf[..10]), Err(SnapshotError::InvalidFormat));
+        let mut long = [0; 24];
+        long[..23].copy_from_slice(&buf);
+        assert_eq!(TestRng::from_snapshot(&long), Err(SnapshotError::InvalidFormat));
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tests of `entropy::set_default_source`.
+//!
+//! The default source is global to the process, so these checks run in their
+//! own binary, in a single test.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use rand_core::entropy::{
+    reset_default_source, set_default_source, DefaultEntropy, EntropySource, TestEntropy,
+};
+use rand_core::{Error, SeedableRng};
+
+// A trivial generator, to check seeding
+struct SeedRng([u8; 20]);
+
+impl SeedableRng for SeedRng {
+    type Seed = [u8; 20];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        SeedRng(seed)
+    }
+}
+
+// A stateful source, so that `from_entropy` differs between calls
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn counting(dest: &mut [u8]) -> Result<(), Error> {
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    TestEntropy::new(n).fill_entropy(dest)
+}
+
+#[cfg(all(feature = "std", debug_assertions))]
+fn recursive(dest: &mut [u8]) -> Result<(), Error> {
+    DefaultEntropy.fill_entropy(dest)
+}
+
+#[test]
+fn default_source() {
+    set_default_source(counting);
+    let rng = SeedRng::from_entropy_source(&mut DefaultEntropy).unwrap();
+    let mut expected = [0u8; 20];
+    TestEntropy::new(0).fill_entropy(&mut expected).unwrap();
+    assert_eq!(rng.0, expected);
+    assert_eq!(COUNTER.load(Ordering::SeqCst), 1);
+    reset_default_source();
+
+    // Back to `getrandom`, if available
+    let result = DefaultEntropy.fill_entropy(&mut expected);
+    #[cfg(feature = "getrandom")]
+    assert!(result.is_ok());
+    #[cfg(not(feature = "getrandom"))]
+    assert_eq!(result.unwrap_err().code().unwrap().get(), Error::ENTROPY_UNAVAILABLE);
+    assert_eq!(COUNTER.load(Ordering::SeqCst), 1);
+
+    // A source using `DefaultEntropy` panics instead of recursing
+    #[cfg(all(feature = "std", debug_assertions))]
+    {
+        set_default_source(recursive);
+        let result = std::panic::catch_unwind(|| DefaultEntropy.fill_entropy(&mut [0u8; 4]));
+        reset_default_source();
+        assert!(result.is_err());
+        // The source may be used again after the panic
+        set_default_source(counting);
+        assert!(DefaultEntropy.fill_entropy(&mut expected).is_ok());
+        reset_default_source();
+    }
+}
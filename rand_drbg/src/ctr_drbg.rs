@@ -0,0 +1,308 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! CTR_DRBG with AES-256, without derivation function.
+
+use core::fmt;
+use core::mem;
+
+use aes::cipher::{BlockEncrypt, NewBlockCipher};
+use aes::{Aes256, Block};
+use rand_core::SeedableRng;
+
+use crate::{zeroize, zeroize_raw, Drbg, DrbgError, Seed384, MAX_REQUEST_LEN, MAX_RESEED_INTERVAL};
+
+// Key length, block length and seed length of AES-256 CTR_DRBG, in bytes.
+const KEY_LEN: usize = 32;
+const BLOCK_LEN: usize = 16;
+const SEED_LEN: usize = KEY_LEN + BLOCK_LEN;
+
+/// CTR_DRBG with AES-256, as specified in NIST SP 800-90A, section 10.2.1,
+/// without derivation function.
+///
+/// Without derivation function, the entropy input must be exactly 48 bytes
+/// of full entropy, no nonce is used, and the personalization string and
+/// additional input are limited to 48 bytes. The security strength is
+/// 256 bits.
+///
+/// With [`SeedableRng::from_seed`], the seed is the entropy input; there is
+/// no personalization string.
+pub struct CtrDrbg {
+    cipher: Aes256,
+    v: u128,
+    reseed_counter: u64,
+    reseed_interval: u64,
+}
+
+impl CtrDrbg {
+    /// Set the reseed interval: the number of requests after which the DRBG
+    /// must be reseeded.
+    ///
+    /// # Panics
+    ///
+    /// If `interval` is zero or greater than [`MAX_RESEED_INTERVAL`].
+    pub fn set_reseed_interval(&mut self, interval: u64) {
+        assert!(
+            interval > 0 && interval <= MAX_RESEED_INTERVAL,
+            "CtrDrbg: invalid reseed interval"
+        );
+        self.reseed_interval = interval;
+    }
+
+    // Increment V and encrypt it.
+    fn next_block(&mut self) -> [u8; BLOCK_LEN] {
+        self.v = self.v.wrapping_add(1);
+        let mut block = Block::from(self.v.to_be_bytes());
+        self.cipher.encrypt_block(&mut block);
+        block.into()
+    }
+
+    // CTR_DRBG_Update, with `data` (zero padded to the seed length) as
+    // provided data.
+    fn update(&mut self, data: &[u8]) {
+        let mut temp = [0u8; SEED_LEN];
+        for chunk in temp.chunks_mut(BLOCK_LEN) {
+            chunk.copy_from_slice(&self.next_block());
+        }
+        for (t, d) in temp.iter_mut().zip(data.iter()) {
+            *t ^= d;
+        }
+        zeroize_cipher(&mut self.cipher);
+        self.cipher = Aes256::new_from_slice(&temp[..KEY_LEN]).unwrap();
+        let mut v = [0u8; BLOCK_LEN];
+        v.copy_from_slice(&temp[KEY_LEN..]);
+        self.v = u128::from_be_bytes(v);
+        zeroize(&mut temp);
+        zeroize(&mut v);
+    }
+}
+
+// Zero the key schedule of `cipher`.
+fn zeroize_cipher(cipher: &mut Aes256) {
+    // SAFETY: `Aes256` has no `Drop` implementation and only holds round
+    // keys, for which all-zero bytes are valid.
+    unsafe { zeroize_raw(cipher as *mut Aes256 as *mut u8, mem::size_of::<Aes256>()) }
+}
+
+impl Drop for CtrDrbg {
+    fn drop(&mut self) {
+        zeroize_cipher(&mut self.cipher);
+        // SAFETY: all-zero bytes are a valid `u128`.
+        unsafe { zeroize_raw(&mut self.v as *mut u128 as *mut u8, mem::size_of::<u128>()) }
+    }
+}
+
+impl Drbg for CtrDrbg {
+    const ENTROPY_LEN: usize = SEED_LEN;
+    const NONCE_LEN: usize = 0;
+
+    /// Instantiate the DRBG.
+    ///
+    /// The entropy input must be 48 bytes long, the nonce empty, and the
+    /// personalization string at most 48 bytes long.
+    fn instantiate(
+        entropy_input: &[u8], nonce: &[u8], personalization: &[u8],
+    ) -> Result<Self, DrbgError> {
+        if entropy_input.len() != SEED_LEN || !nonce.is_empty() || personalization.len() > SEED_LEN
+        {
+            return Err(DrbgError::InvalidLength);
+        }
+        let mut drbg = CtrDrbg {
+            cipher: Aes256::new_from_slice(&[0; KEY_LEN]).unwrap(),
+            v: 0,
+            reseed_counter: 1,
+            reseed_interval: MAX_RESEED_INTERVAL,
+        };
+        let mut seed_material = [0u8; SEED_LEN];
+        seed_material.copy_from_slice(entropy_input);
+        for (s, p) in seed_material.iter_mut().zip(personalization.iter()) {
+            *s ^= p;
+        }
+        drbg.update(&seed_material);
+        zeroize(&mut seed_material);
+        Ok(drbg)
+    }
+
+    fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        if entropy_input.len() != SEED_LEN || additional_input.len() > SEED_LEN {
+            return Err(DrbgError::InvalidLength);
+        }
+        let mut seed_material = [0u8; SEED_LEN];
+        seed_material.copy_from_slice(entropy_input);
+        for (s, a) in seed_material.iter_mut().zip(additional_input.iter()) {
+            *s ^= a;
+        }
+        self.update(&seed_material);
+        zeroize(&mut seed_material);
+        self.reseed_counter = 1;
+        Ok(())
+    }
+
+    fn generate(&mut self, out: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        if out.len() > MAX_REQUEST_LEN {
+            return Err(DrbgError::RequestTooLarge);
+        }
+        if additional_input.len() > SEED_LEN {
+            return Err(DrbgError::InvalidLength);
+        }
+        if self.reseed_counter > self.reseed_interval {
+            return Err(DrbgError::ReseedRequired);
+        }
+        if !additional_input.is_empty() {
+            self.update(additional_input);
+        }
+        for chunk in out.chunks_mut(BLOCK_LEN) {
+            let block = self.next_block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.update(additional_input);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+}
+
+impl SeedableRng for CtrDrbg {
+    type Seed = Seed384;
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::instantiate(&seed.0, &[], &[]).unwrap()
+    }
+}
+
+rand_core::impl_crypto_rng_via_try_fill!(CtrDrbg, crate::generate_chunked);
+
+// Custom Debug implementation that does not expose the internal state
+impl fmt::Debug for CtrDrbg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CtrDrbg")
+            .field("reseed_counter", &self.reseed_counter)
+            .field("reseed_interval", &self.reseed_interval)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CtrDrbg;
+    use crate::test::hex;
+    use crate::{Drbg, DrbgError, Seed384};
+    use rand_core::{RngCore, SeedableRng};
+
+    // From the CAVP test vectors for CTR_DRBG (drbgtestvectors.zip,
+    // CTR_DRBG.rsp, no derivation function), [AES-256 no df],
+    // [PredictionResistance = False], [EntropyInputLen = 384],
+    // [NonceLen = 0], [PersonalizationStringLen = 0],
+    // [ReturnedBitsLen = 512].
+    //
+    // Each test instantiates the DRBG, reseeds it with the given entropy
+    // input and additional input, and generates twice with the given
+    // additional input; the output of the second request is returned.
+    const CAVP: [(&str, &str, &str, &str, &str, &str); 2] = [
+        // [AdditionalInputLen = 0], COUNT = 0
+        (
+            "e4bc23c5089a19d86f4119cb3fa08c0a4991e0a1def17e101e4c14d9c323460a\
+             7c2fb58e0b086c6c57b55f56cae25bad",
+            "fd85a836bba85019881e8c6bad23c9061adc75477659acaea8e4a01dfe07a183\
+             2dad1c136f59d70f8653a5dc118663d6",
+            "",
+            "",
+            "",
+            "b2cb8905c05e5950ca31895096be29ea3d5a3b82b269495554eb80fe07de43e1\
+             93b9e7c3ece73b80e062b1c1f68202fbb1c52a040ea2478864295282234aaada",
+        ),
+        // [AdditionalInputLen = 384], COUNT = 0
+        (
+            "99903165903fea49c2db26ed675e44cc14cb2c1f28b836b203240b02771e8311\
+             46ffc4335373bb344688c5c950670291",
+            "b4ee99fa9e0eddaf4a3612013cd636c4af69177b43eebb3c58a305b9979b68b5\
+             cc820504f6c029aad78a5d29c66e84a0",
+            "2d8c5c28b05696e74774eb69a10f01c5fabc62691ddf7848a8004bb5eeb4d2c5\
+             febe1aa01f4d557b23d7e9a0e4e90655",
+            "0dc9cde42ac6e856f01a55f219c614de90c659260948db5053d414bab0ec2e13\
+             e995120c3eb5aafc25dc4bdcef8ace24",
+            "711be6c035013189f362211889248ca8a3268e63a7eb26836d915810a680ac4a\
+             33cd1180811a31a0f44f08db3dd64f91",
+            "11c7a0326ea737baa7a993d510fafee5374e7bbe17ef0e3e29f50fa68aac2124\
+             b017d449768491cac06d136d691a4e80785739f9aaedf311bba752a3268cc531",
+        ),
+    ];
+
+    #[test]
+    fn test_ctr_drbg_cavp() {
+        for &(entropy_input, reseed_input, add_reseed, add1, add2, expected) in CAVP.iter() {
+            let mut drbg = CtrDrbg::instantiate(&hex(entropy_input), &[], &[]).unwrap();
+            drbg.reseed(&hex(reseed_input), &hex(add_reseed)).unwrap();
+            let expected = hex(expected);
+            let mut out = [0u8; 64];
+            drbg.generate(&mut out, &hex(add1)).unwrap();
+            drbg.generate(&mut out, &hex(add2)).unwrap();
+            assert_eq!(&out[..], &expected[..]);
+            assert_eq!(drbg.reseed_counter(), 3);
+        }
+    }
+
+    #[test]
+    fn test_ctr_drbg_large_request() {
+        use sha2::{Digest, Sha256};
+
+        // A maximum size request from an all-zero seed; the expected digest
+        // is from the BoringSSL CTR_DRBG tests.
+        let mut drbg = CtrDrbg::from_seed(Seed384::default());
+        let mut out = [0u8; 1 << 16];
+        drbg.generate(&mut out, &[]).unwrap();
+        let expected = hex("69781596cac03f6a6ded221e26d07549a04b91583cf4e36dff41bfb9f8a81c2b");
+        assert_eq!(&Sha256::digest(&out)[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_ctr_drbg_errors() {
+        let entropy_input = [0u8; 48];
+        for &(len, nonce, pers) in [(32, 0, 0), (49, 0, 0), (48, 16, 0), (48, 0, 49)].iter() {
+            let zeros = [0u8; 64];
+            let result = CtrDrbg::instantiate(&zeros[..len], &zeros[..nonce], &zeros[..pers]);
+            assert_eq!(result.unwrap_err(), DrbgError::InvalidLength);
+        }
+
+        let mut drbg = CtrDrbg::instantiate(&entropy_input, &[], b"personalization").unwrap();
+        drbg.set_reseed_interval(1);
+        let mut out = [0u8; 20];
+        assert_eq!(drbg.generate(&mut out, &[0; 49]), Err(DrbgError::InvalidLength));
+        assert!(drbg.generate(&mut out, b"additional").is_ok());
+        assert_eq!(drbg.generate(&mut out, &[]), Err(DrbgError::ReseedRequired));
+        let err = drbg.try_fill_bytes(&mut out).unwrap_err();
+        assert_eq!(err.code().unwrap().get(), rand_core::Error::RESEED_REQUIRED);
+        drbg.reseed(&entropy_input, &[]).unwrap();
+        assert!(drbg.generate(&mut out, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_ctr_drbg_prediction_resistance() {
+        // A request with prediction resistance reseeds first, so without
+        // additional input it reproduces the requests of a CAVP vector
+        let (entropy_input, reseed_input, _, _, _, expected) = CAVP[0];
+        let mut drbg = CtrDrbg::instantiate(&hex(entropy_input), &[], &[]).unwrap();
+        let mut out = [0u8; 64];
+        drbg.generate_with_prediction_resistance(&hex(reseed_input), &mut out, &[]).unwrap();
+        drbg.generate(&mut out, &[]).unwrap();
+        assert_eq!(&out[..], &hex(expected)[..]);
+
+        let mut drbg1 = CtrDrbg::from_seed(Seed384([7; 48]));
+        let mut drbg2 = CtrDrbg::from_seed(Seed384([7; 48]));
+        let mut out1 = [0u8; 64];
+        let mut out2 = [0u8; 64];
+        drbg1.generate_with_prediction_resistance(&[1; 48], &mut out1, b"add").unwrap();
+        drbg2.reseed(&[1; 48], b"add").unwrap();
+        drbg2.generate(&mut out2, &[]).unwrap();
+        assert_eq!(&out1[..], &out2[..]);
+        assert_ne!(&out1[..], &[0u8; 64][..]);
+    }
+}
@@ -0,0 +1,300 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! HMAC_DRBG with HMAC-SHA-256.
+
+use core::fmt;
+
+use hmac::{Hmac, Mac, NewMac};
+use rand_core::SeedableRng;
+use sha2::Sha256;
+
+use crate::{zeroize, Drbg, DrbgError, Seed384, MAX_REQUEST_LEN, MAX_RESEED_INTERVAL};
+
+// Security strength, and output length of SHA-256, in bytes.
+const STRENGTH: usize = 32;
+
+// Maximum length of any input: 2^35 bits.
+const MAX_INPUT_LEN: u64 = 1 << 32;
+
+/// HMAC_DRBG with HMAC-SHA-256, as specified in NIST SP 800-90A, section
+/// 10.1.2.
+///
+/// The security strength is 256 bits: the entropy input must be at least
+/// 32 bytes long, and the nonce at least 16 bytes long.
+///
+/// With [`SeedableRng::from_seed`], the first 32 bytes of the seed are the
+/// entropy input, and the last 16 bytes the nonce; there is no
+/// personalization string.
+pub struct HmacDrbg {
+    k: [u8; STRENGTH],
+    v: [u8; STRENGTH],
+    reseed_counter: u64,
+    reseed_interval: u64,
+}
+
+impl HmacDrbg {
+    /// Set the reseed interval: the number of requests after which the DRBG
+    /// must be reseeded.
+    ///
+    /// # Panics
+    ///
+    /// If `interval` is zero or greater than [`MAX_RESEED_INTERVAL`].
+    pub fn set_reseed_interval(&mut self, interval: u64) {
+        assert!(
+            interval > 0 && interval <= MAX_RESEED_INTERVAL,
+            "HmacDrbg: invalid reseed interval"
+        );
+        self.reseed_interval = interval;
+    }
+
+    // HMAC(K, V || sep || data), with `sep` omitted if `None`.
+    fn hmac(&self, sep: Option<u8>, data: &[&[u8]]) -> [u8; STRENGTH] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.k).unwrap();
+        mac.update(&self.v);
+        if let Some(sep) = sep {
+            mac.update(&[sep]);
+        }
+        for part in data {
+            mac.update(part);
+        }
+        let mut out = [0; STRENGTH];
+        out.copy_from_slice(&mac.finalize().into_bytes());
+        out
+    }
+
+    // HMAC_DRBG_Update, with the concatenation of `data` as provided data.
+    fn update(&mut self, data: &[&[u8]]) {
+        self.k = self.hmac(Some(0), data);
+        self.v = self.hmac(None, &[]);
+        if data.iter().all(|part| part.is_empty()) {
+            return;
+        }
+        self.k = self.hmac(Some(1), data);
+        self.v = self.hmac(None, &[]);
+    }
+}
+
+impl Drop for HmacDrbg {
+    fn drop(&mut self) {
+        zeroize(&mut self.k);
+        zeroize(&mut self.v);
+    }
+}
+
+fn check_len(input: &[u8], min: usize) -> Result<(), DrbgError> {
+    if input.len() < min || input.len() as u64 > MAX_INPUT_LEN {
+        return Err(DrbgError::InvalidLength);
+    }
+    Ok(())
+}
+
+impl Drbg for HmacDrbg {
+    const ENTROPY_LEN: usize = STRENGTH;
+    const NONCE_LEN: usize = STRENGTH / 2;
+
+    fn instantiate(
+        entropy_input: &[u8], nonce: &[u8], personalization: &[u8],
+    ) -> Result<Self, DrbgError> {
+        check_len(entropy_input, STRENGTH)?;
+        check_len(nonce, STRENGTH / 2)?;
+        check_len(personalization, 0)?;
+        let mut drbg = HmacDrbg {
+            k: [0; STRENGTH],
+            v: [1; STRENGTH],
+            reseed_counter: 1,
+            reseed_interval: MAX_RESEED_INTERVAL,
+        };
+        drbg.update(&[entropy_input, nonce, personalization]);
+        Ok(drbg)
+    }
+
+    fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        check_len(entropy_input, STRENGTH)?;
+        check_len(additional_input, 0)?;
+        self.update(&[entropy_input, additional_input]);
+        self.reseed_counter = 1;
+        Ok(())
+    }
+
+    fn generate(&mut self, out: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError> {
+        if out.len() > MAX_REQUEST_LEN {
+            return Err(DrbgError::RequestTooLarge);
+        }
+        check_len(additional_input, 0)?;
+        if self.reseed_counter > self.reseed_interval {
+            return Err(DrbgError::ReseedRequired);
+        }
+        if !additional_input.is_empty() {
+            self.update(&[additional_input]);
+        }
+        for chunk in out.chunks_mut(STRENGTH) {
+            self.v = self.hmac(None, &[]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[additional_input]);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+}
+
+impl SeedableRng for HmacDrbg {
+    type Seed = Seed384;
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let (entropy_input, nonce) = seed.0.split_at(STRENGTH);
+        Self::instantiate(entropy_input, nonce, &[]).unwrap()
+    }
+}
+
+rand_core::impl_crypto_rng_via_try_fill!(HmacDrbg, crate::generate_chunked);
+
+// Custom Debug implementation that does not expose the internal state
+impl fmt::Debug for HmacDrbg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HmacDrbg")
+            .field("reseed_counter", &self.reseed_counter)
+            .field("reseed_interval", &self.reseed_interval)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HmacDrbg;
+    use crate::test::hex;
+    use crate::{Drbg, DrbgError, Seed384};
+    use rand_core::{RngCore, SeedableRng};
+
+    // From the CAVP test vectors for HMAC_DRBG (drbgtestvectors.zip,
+    // HMAC_DRBG.rsp), [SHA-256], [PredictionResistance = False],
+    // [EntropyInputLen = 256], [NonceLen = 128],
+    // [PersonalizationStringLen = 0], [ReturnedBitsLen = 1024].
+    //
+    // Each test instantiates the DRBG, and generates twice with the given
+    // additional input; the output of the second request is returned.
+    const CAVP: [(&str, &str, &str, &str, &str); 4] = [
+        // [AdditionalInputLen = 0], COUNT = 0
+        (
+            "ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488",
+            "659ba96c601dc69fc902940805ec0ca8",
+            "",
+            "",
+            "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+             d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+             07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+             961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8",
+        ),
+        // [AdditionalInputLen = 0], COUNT = 1
+        (
+            "79737479ba4e7642a221fcfd1b820b134e9e3540a35bb48ffae29c20f5418ea3",
+            "3593259c092bef4129bc2c6c9e19f343",
+            "",
+            "",
+            "cf5ad5984f9e43917aa9087380dac46e410ddc8a7731859c84e9d0f31bd43655\
+             b924159413e2293b17610f211e09f770f172b8fb693a35b85d3b9e5e63b1dc25\
+             2ac0e115002e9bedfb4b5b6fd43f33b8e0eafb2d072e1a6fee1f159df9b51e6c\
+             8da737e60d5032dd30544ec51558c6f080bdbdab1de8a939e961e06b5f1aca37",
+        ),
+        // [AdditionalInputLen = 256], COUNT = 0
+        (
+            "d3cc4d1acf3dde0c4bd2290d262337042dc632948223d3a2eaab87da44295fbd",
+            "0109b0e729f457328aa18569a9224921",
+            "3c311848183c9a212a26f27f8c6647e40375e466a0857cc39c4e47575d53f1f6",
+            "fcb9abd19ccfbccef88c9c39bfb3dd7b1c12266c9808992e305bc3cff566e4e4",
+            "9c7b758b212cd0fcecd5daa489821712e3cdea4467b560ef5ddc24ab47749a1f\
+             1ffdbbb118f4e62fcfca3371b8fbfc5b0646b83e06bfbbab5fac30ea09ea2bc7\
+             6f1ea568c9be0444b2cc90517b20ca825f2d0eccd88e7175538b85d90ab39018\
+             3ca6395535d34473af6b5a5b88f5a59ee7561573337ea819da0dcc3573a22974",
+        ),
+        // [AdditionalInputLen = 256], COUNT = 1
+        (
+            "f97a3cfd91faa046b9e61b9493d436c4931f604b22f1081521b3419151e8ff06",
+            "11f3a7d43595357d58120bd1e2dd8aed",
+            "517289afe444a0fe5ed1a41dbbb5eb17150079bdd31e29cf2ff30034d8268e3b",
+            "88028d29ef80b4e6f0fe12f91d7449fe75062682e89c571440c0c9b52c42a6e0",
+            "c6871cff0824fe55ea7689a52229886730450e5d362da5bf590dcf9acd67fed4\
+             cb32107df5d03969a66b1f6494fdf5d63d5b4d0d34ea7399a07d0116126d0d51\
+             8c7c55ba46e12f62efc8fe28a51c9d428e6d371d7397ab319fc73ded4722e5b4\
+             f30004032a6128df5e7497ecf82ca7b0a50e867ef6728a4f509a8c859087039c",
+        ),
+    ];
+
+    #[test]
+    fn test_hmac_drbg_cavp() {
+        for &(entropy_input, nonce, add1, add2, expected) in CAVP.iter() {
+            let mut drbg = HmacDrbg::instantiate(&hex(entropy_input), &hex(nonce), &[]).unwrap();
+            let expected = hex(expected);
+            let mut out = [0u8; 128];
+            drbg.generate(&mut out, &hex(add1)).unwrap();
+            drbg.generate(&mut out, &hex(add2)).unwrap();
+            assert_eq!(&out[..], &expected[..]);
+            assert_eq!(drbg.reseed_counter(), 3);
+        }
+    }
+
+    #[test]
+    fn test_hmac_drbg_seedable() {
+        let (entropy_input, nonce, _, _, expected) = CAVP[0];
+        let mut seed = Seed384::default();
+        seed.0[..32].copy_from_slice(&hex(entropy_input));
+        seed.0[32..].copy_from_slice(&hex(nonce));
+        let mut rng = HmacDrbg::from_seed(seed);
+        let mut out = [0u8; 128];
+        rng.fill_bytes(&mut out);
+        rng.fill_bytes(&mut out);
+        assert_eq!(&out[..], &hex(expected)[..]);
+    }
+
+    #[test]
+    fn test_hmac_drbg_errors() {
+        let entropy_input = [0u8; 32];
+        let nonce = [0u8; 16];
+        let short = HmacDrbg::instantiate(&entropy_input[..31], &nonce, &[]);
+        assert_eq!(short.unwrap_err(), DrbgError::InvalidLength);
+        let short = HmacDrbg::instantiate(&entropy_input, &nonce[..15], &[]);
+        assert_eq!(short.unwrap_err(), DrbgError::InvalidLength);
+
+        let mut drbg = HmacDrbg::instantiate(&entropy_input, &nonce, &[]).unwrap();
+        drbg.set_reseed_interval(2);
+        let mut out = [0u8; 32];
+        let mut big = [0u8; (1 << 16) + 1];
+        assert_eq!(drbg.generate(&mut big, &[]), Err(DrbgError::RequestTooLarge));
+        assert!(drbg.generate(&mut out, &[]).is_ok());
+        assert!(drbg.generate(&mut out, &[]).is_ok());
+        assert_eq!(drbg.generate(&mut out, &[]), Err(DrbgError::ReseedRequired));
+        assert_eq!(drbg.reseed(&entropy_input[..16], &[]), Err(DrbgError::InvalidLength));
+        drbg.reseed(&entropy_input, b"additional").unwrap();
+        assert_eq!(drbg.reseed_counter(), 1);
+        assert!(drbg.generate(&mut out, &[]).is_ok());
+
+        // Large `RngCore` requests are split
+        drbg.set_reseed_interval(4);
+        drbg.fill_bytes(&mut big);
+        assert_eq!(drbg.reseed_counter(), 4);
+    }
+
+    #[test]
+    fn test_hmac_drbg_prediction_resistance() {
+        // Equivalent to a reseed followed by a request without additional
+        // input
+        let mut drbg1 = HmacDrbg::from_seed(Seed384([7; 48]));
+        let mut drbg2 = HmacDrbg::from_seed(Seed384([7; 48]));
+        let mut out1 = [0u8; 64];
+        let mut out2 = [0u8; 64];
+        drbg1.generate_with_prediction_resistance(&[1; 32], &mut out1, b"add").unwrap();
+        drbg2.reseed(&[1; 32], b"add").unwrap();
+        drbg2.generate(&mut out2, &[]).unwrap();
+        assert_eq!(&out1[..], &out2[..]);
+        assert_eq!(drbg1.reseed_counter(), 2);
+    }
+}
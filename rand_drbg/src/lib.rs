@@ -0,0 +1,393 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The NIST SP 800-90A deterministic random bit generators HMAC_DRBG and
+//! CTR_DRBG.
+//!
+//! [NIST SP 800-90A Rev. 1][sp800-90a] specifies DRBG mechanisms approved
+//! for use in FIPS 140 validated modules. This crate implements:
+//!
+//! - [`HmacDrbg`]: HMAC_DRBG with HMAC-SHA-256
+//! - [`CtrDrbg`]: CTR_DRBG with AES-256, without derivation function
+//!
+//! Both have a security strength of 256 bits, and are tested against the
+//! CAVP known-answer test vectors.
+//!
+//! The mechanisms are exposed at two levels:
+//!
+//! - The [`Drbg`] trait provides the *instantiate*, *reseed* and *generate*
+//!   functions of SP 800-90A, with explicit entropy input, nonce,
+//!   personalization string and additional input. The caller is responsible
+//!   for providing entropy input from an approved source.
+//! - [`DrbgRng`] combines a DRBG with an [`EntropySource`], reseeding it
+//!   automatically when its reseed interval is reached, and before every
+//!   request if prediction resistance is enabled.
+//!
+//! The DRBGs also implement [`RngCore`], [`CryptoRng`] and [`SeedableRng`].
+//! Used this way, each request is a *generate* call without additional input,
+//! split into several calls if it exceeds the maximum request size; once the
+//! reseed interval is reached, requests fail with [`Error::RESEED_REQUIRED`].
+//!
+//! ```
+//! use rand_core::entropy::DefaultEntropy;
+//! use rand_core::RngCore;
+//! use rand_drbg::{DrbgRng, HmacDrbg};
+//!
+//! let mut rng = DrbgRng::<HmacDrbg, _>::new(DefaultEntropy, b"example")
+//!     .unwrap()
+//!     .with_prediction_resistance(true);
+//! let mut key = [0u8; 32];
+//! rng.try_fill_bytes(&mut key).unwrap();
+//! ```
+//!
+//! [sp800-90a]: https://csrc.nist.gov/publications/detail/sp/800-90a/rev-1/final
+//! [`EntropySource`]: rand_core::entropy::EntropySource
+//! [`Error::RESEED_REQUIRED`]: rand_core::Error::RESEED_REQUIRED
+
+#![doc(
+    html_logo_url = "https://www.rust-lang.org/logos/rust-logo-128x128-blk.png",
+    html_favicon_url = "https://www.rust-lang.org/favicon.ico",
+    html_root_url = "https://rust-random.github.io/rand/"
+)]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![doc(test(attr(allow(unused_variables), deny(warnings))))]
+#![no_std]
+
+#[cfg(feature = "std")] extern crate std;
+
+mod ctr_drbg;
+mod hmac_drbg;
+
+pub use rand_core;
+
+pub use crate::ctr_drbg::CtrDrbg;
+pub use crate::hmac_drbg::HmacDrbg;
+
+use core::fmt;
+use core::num::NonZeroU32;
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+use rand_core::entropy::EntropySource;
+use rand_core::{CryptoRng, Error, RngCore};
+
+/// The maximum number of bytes per *generate* request: 2^19 bits.
+pub const MAX_REQUEST_LEN: usize = 1 << 16;
+
+/// The maximum (and default) reseed interval: 2^48 requests.
+pub const MAX_RESEED_INTERVAL: u64 = 1 << 48;
+
+/// Error type of DRBG functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrbgError {
+    /// The entropy input, nonce, personalization string or additional input
+    /// has an invalid length.
+    InvalidLength,
+    /// More than [`MAX_REQUEST_LEN`] bytes were requested.
+    RequestTooLarge,
+    /// The reseed interval was reached: the DRBG must be reseeded.
+    ReseedRequired,
+}
+
+impl fmt::Display for DrbgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            DrbgError::InvalidLength => "invalid length of DRBG input",
+            DrbgError::RequestTooLarge => "DRBG request too large",
+            DrbgError::ReseedRequired => "DRBG must be reseeded",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DrbgError {}
+
+impl From<DrbgError> for Error {
+    fn from(err: DrbgError) -> Self {
+        let code = match err {
+            DrbgError::InvalidLength | DrbgError::RequestTooLarge => Error::DRBG_INVALID_REQUEST,
+            DrbgError::ReseedRequired => Error::RESEED_REQUIRED,
+        };
+        NonZeroU32::new(code).unwrap().into()
+    }
+}
+
+/// A 384-bit seed: the entropy input (and nonce, if any) of a DRBG.
+///
+/// See [`SeedableRng`](rand_core::SeedableRng) implementations of the DRBGs
+/// for how the seed is used.
+#[derive(Clone)]
+pub struct Seed384(pub [u8; 48]);
+
+impl fmt::Debug for Seed384 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0[..].fmt(f)
+    }
+}
+
+impl Default for Seed384 {
+    fn default() -> Seed384 {
+        Seed384([0; 48])
+    }
+}
+
+impl AsMut<[u8]> for Seed384 {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+/// A DRBG mechanism of NIST SP 800-90A.
+pub trait Drbg: Sized {
+    /// The length of the entropy input read by [`DrbgRng`], in bytes.
+    const ENTROPY_LEN: usize;
+    /// The length of the nonce read by [`DrbgRng`], in bytes.
+    const NONCE_LEN: usize;
+
+    /// Instantiate the DRBG.
+    ///
+    /// The entropy input must have at least the security strength of the
+    /// DRBG, and the nonce at least half of it, if the DRBG uses a nonce.
+    fn instantiate(
+        entropy_input: &[u8], nonce: &[u8], personalization: &[u8],
+    ) -> Result<Self, DrbgError>;
+
+    /// Reseed the DRBG, resetting its reseed counter.
+    fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]) -> Result<(), DrbgError>;
+
+    /// Fill `out` with pseudorandom bytes.
+    ///
+    /// Fails with [`DrbgError::ReseedRequired`] once the reseed interval is
+    /// reached, and with [`DrbgError::RequestTooLarge`] if `out` is longer
+    /// than [`MAX_REQUEST_LEN`].
+    fn generate(&mut self, out: &mut [u8], additional_input: &[u8]) -> Result<(), DrbgError>;
+
+    /// Fill `out` with pseudorandom bytes, with prediction resistance.
+    ///
+    /// As specified by SP 800-90A, this reseeds the DRBG with `entropy_input`
+    /// and `additional_input`, then generates without additional input.
+    fn generate_with_prediction_resistance(
+        &mut self, entropy_input: &[u8], out: &mut [u8], additional_input: &[u8],
+    ) -> Result<(), DrbgError> {
+        self.reseed(entropy_input, additional_input)?;
+        self.generate(out, &[])
+    }
+
+    /// The number of requests since the last (re)seeding, plus one.
+    fn reseed_counter(&self) -> u64;
+}
+
+// Zero `bytes` using volatile writes, which the compiler may not remove.
+fn zeroize(bytes: &mut [u8]) {
+    // SAFETY: `bytes` is valid for writes, and any byte value is valid
+    unsafe { zeroize_raw(bytes.as_mut_ptr(), bytes.len()) }
+}
+
+// Zero the `len` bytes at `ptr` using volatile writes.
+//
+// SAFETY: `ptr` must be valid for writes of `len` bytes, and all-zero bytes
+// must be a valid value of the type stored there.
+unsafe fn zeroize_raw(ptr: *mut u8, len: usize) {
+    for i in 0..len {
+        ptr::write_volatile(ptr.add(i), 0);
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+// Fill `dest` with as many `generate` requests as needed.
+fn generate_chunked<D: Drbg>(drbg: &mut D, dest: &mut [u8]) -> Result<(), Error> {
+    for chunk in dest.chunks_mut(MAX_REQUEST_LEN) {
+        drbg.generate(chunk, &[])?;
+    }
+    Ok(())
+}
+
+/// A DRBG with an entropy source, for automatic reseeding.
+///
+/// The DRBG is instantiated with entropy input and a nonce read from the
+/// source, and reseeded from it whenever its reseed interval is reached.
+/// With [prediction resistance](DrbgRng::with_prediction_resistance), it is
+/// reseeded before every request instead.
+///
+/// Requests through [`RngCore`] do not use additional input; see
+/// [`DrbgRng::generate`] to provide it.
+#[derive(Debug)]
+pub struct DrbgRng<D, E> {
+    drbg: D,
+    source: E,
+    prediction_resistance: bool,
+}
+
+impl<D: Drbg, E: EntropySource> DrbgRng<D, E> {
+    /// Instantiate a DRBG from `source`, with the given personalization
+    /// string.
+    pub fn new(mut source: E, personalization: &[u8]) -> Result<Self, Error> {
+        let mut input = [0u8; 96];
+        let result = {
+            let (entropy_input, rest) = input.split_at_mut(D::ENTROPY_LEN);
+            let nonce = &mut rest[..D::NONCE_LEN];
+            source
+                .fill_entropy(entropy_input)
+                .and_then(|()| source.fill_entropy(nonce))
+                .and_then(|()| {
+                    D::instantiate(entropy_input, nonce, personalization).map_err(Error::from)
+                })
+        };
+        zeroize(&mut input);
+        let drbg = result?;
+        Ok(DrbgRng {
+            drbg,
+            source,
+            prediction_resistance: false,
+        })
+    }
+
+    /// Enable or disable prediction resistance.
+    pub fn with_prediction_resistance(mut self, enabled: bool) -> Self {
+        self.prediction_resistance = enabled;
+        self
+    }
+
+    /// Reference the DRBG.
+    pub fn drbg(&self) -> &D {
+        &self.drbg
+    }
+
+    /// Reseed the DRBG from the entropy source.
+    pub fn reseed(&mut self, additional_input: &[u8]) -> Result<(), Error> {
+        let mut input = [0u8; 64];
+        let entropy_input = &mut input[..D::ENTROPY_LEN];
+        let result = match self.source.fill_entropy(entropy_input) {
+            Ok(()) => self.drbg.reseed(entropy_input, additional_input).map_err(Error::from),
+            Err(err) => Err(err),
+        };
+        zeroize(&mut input);
+        result
+    }
+
+    /// Fill `out` with pseudorandom bytes, reseeding as required.
+    ///
+    /// `out` must not be longer than [`MAX_REQUEST_LEN`].
+    pub fn generate(&mut self, out: &mut [u8], additional_input: &[u8]) -> Result<(), Error> {
+        if self.prediction_resistance {
+            self.reseed(additional_input)?;
+            self.drbg.generate(out, &[])?;
+            return Ok(());
+        }
+        match self.drbg.generate(out, additional_input) {
+            Err(DrbgError::ReseedRequired) => {
+                self.reseed(additional_input)?;
+                self.drbg.generate(out, &[])?;
+            }
+            result => result?,
+        }
+        Ok(())
+    }
+}
+
+impl<D: Drbg, E: EntropySource> RngCore for DrbgRng<D, E> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("Error: {}", err);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        for chunk in dest.chunks_mut(MAX_REQUEST_LEN) {
+            self.generate(chunk, &[])?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: Drbg + CryptoRng, E: EntropySource> CryptoRng for DrbgRng<D, E> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand_core::entropy::TestEntropy;
+
+    // Decode a hexadecimal string of at most 128 bytes.
+    pub(crate) fn hex(s: &str) -> Hex {
+        let mut out = Hex([0; 128], s.len() / 2);
+        for (i, byte) in out.0[..out.1].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    pub(crate) struct Hex([u8; 128], usize);
+
+    impl core::ops::Deref for Hex {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            &self.0[..self.1]
+        }
+    }
+
+    #[test]
+    fn test_drbg_rng_reseeding() {
+        let source = TestEntropy::new(1);
+        let mut rng = DrbgRng::<HmacDrbg, _>::new(source, b"test").unwrap();
+        rng.drbg.set_reseed_interval(3);
+        let mut buf = [0u8; 16];
+        for i in 0..3 {
+            rng.fill_bytes(&mut buf);
+            assert_eq!(rng.drbg().reseed_counter(), i + 2);
+        }
+        // Reseeded automatically
+        rng.fill_bytes(&mut buf);
+        assert_eq!(rng.drbg().reseed_counter(), 2);
+
+        // With prediction resistance, every request reseeds
+        let mut rng = rng.with_prediction_resistance(true);
+        rng.fill_bytes(&mut buf);
+        rng.fill_bytes(&mut buf);
+        assert_eq!(rng.drbg().reseed_counter(), 2);
+
+        // Entropy source failures are passed through
+        let source = TestEntropy::new(1).with_limit(48 + 32);
+        let mut rng = DrbgRng::<HmacDrbg, _>::new(source, b"")
+            .unwrap()
+            .with_prediction_resistance(true);
+        assert!(rng.try_fill_bytes(&mut buf).is_ok());
+        let err = rng.try_fill_bytes(&mut buf).unwrap_err();
+        assert_eq!(err.code().unwrap().get(), Error::ENTROPY_FAILURE);
+    }
+
+    #[test]
+    fn test_drbg_rng_deterministic() {
+        // `DrbgRng` instantiates with the entropy input followed by the nonce
+        let mut rng = DrbgRng::<CtrDrbg, _>::new(TestEntropy::new(5), b"pers").unwrap();
+        let mut input = [0u8; 48];
+        TestEntropy::new(5).fill_entropy(&mut input).unwrap();
+        let mut drbg = CtrDrbg::instantiate(&input, &[], b"pers").unwrap();
+        let mut buf1 = [0u8; 100];
+        let mut buf2 = [0u8; 100];
+        rng.fill_bytes(&mut buf1);
+        drbg.fill_bytes(&mut buf2);
+        assert_eq!(&buf1[..], &buf2[..]);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let err = Error::from(DrbgError::ReseedRequired);
+        assert_eq!(err.code().unwrap().get(), Error::RESEED_REQUIRED);
+        let err = Error::from(DrbgError::RequestTooLarge);
+        assert_eq!(err.code().unwrap().get(), Error::DRBG_INVALID_REQUEST);
+    }
+}
@@ -10,7 +10,9 @@
 
 use core::fmt;
 use rand_core::block::{BlockRng, BlockRngCore};
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
 use rand_core::{le, CryptoRng, Error, RngCore, SeedableRng};
+#[cfg(feature = "serde1")] use serde::{Deserialize, Serialize};
 
 const SEED_WORDS: usize = 8; // 128 bit key followed by 128 bit iv
 
@@ -64,6 +66,7 @@ const SEED_WORDS: usize = 8; // 128 bit key followed by 128 bit iv
 /// [^5]: Internet Engineering Task Force (February 2015),
 ///       ["Prohibiting RC4 Cipher Suites"](https://tools.ietf.org/html/rfc7465).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub struct Hc128Rng(BlockRng<Hc128Core>);
 
 impl RngCore for Hc128Rng {
@@ -104,6 +107,23 @@ impl SeedableRng for Hc128Rng {
 
 impl CryptoRng for Hc128Rng {}
 
+impl SnapshotState for Hc128Rng {
+    const STATE_LEN: usize = BlockRng::<Hc128Core>::STATE_LEN;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        self.0.write_state(w)
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        BlockRng::read_state(r).map(Hc128Rng)
+    }
+}
+
+impl Snapshot for Hc128Rng {
+    const ALGORITHM: &'static str = "HC-128";
+    const VERSION: u16 = 1;
+}
+
 impl PartialEq for Hc128Rng {
     fn eq(&self, rhs: &Self) -> bool {
         self.0.core == rhs.0.core && self.0.index() == rhs.0.index()
@@ -113,8 +133,11 @@ impl Eq for Hc128Rng {}
 
 /// The core of `Hc128Rng`, used with `BlockRng`.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub struct Hc128Core {
+    #[cfg_attr(feature = "serde1", serde(with = "table"))]
     t: [u32; 1024],
+    #[cfg_attr(feature = "serde1", serde(deserialize_with = "table::deserialize_counter"))]
     counter1024: usize,
 }
 
@@ -130,6 +153,24 @@ impl BlockRngCore for Hc128Core {
     type Results = [u32; 16];
 
     fn generate(&mut self, results: &mut Self::Results) {
+        self.generate_with(|i, x| results[i] = x);
+    }
+
+    fn generate_into(&mut self, dest: &mut [u8]) -> usize {
+        let mut len = 0;
+        for chunk in dest.chunks_exact_mut(64) {
+            self.generate_with(|i, x| chunk[4 * i..4 * i + 4].copy_from_slice(&x.to_le_bytes()));
+            len += 64;
+        }
+        len
+    }
+}
+
+impl Hc128Core {
+    // Generate the next 16 words of keystream, passing each word with its
+    // index to `out`.
+    #[inline(always)]
+    fn generate_with<F: FnMut(usize, u32)>(&mut self, mut out: F) {
         assert!(self.counter1024 % 16 == 0);
 
         let cc = self.counter1024 % 512;
@@ -144,46 +185,44 @@ impl BlockRngCore for Hc128Core {
 
         if self.counter1024 & 512 == 0 {
             // P block
-            results[0]  = self.step_p(cc+0,  cc+1,  ee+13, ee+6,  ee+4);
-            results[1]  = self.step_p(cc+1,  cc+2,  ee+14, ee+7,  ee+5);
-            results[2]  = self.step_p(cc+2,  cc+3,  ee+15, ee+8,  ee+6);
-            results[3]  = self.step_p(cc+3,  cc+4,  cc+0,  ee+9,  ee+7);
-            results[4]  = self.step_p(cc+4,  cc+5,  cc+1,  ee+10, ee+8);
-            results[5]  = self.step_p(cc+5,  cc+6,  cc+2,  ee+11, ee+9);
-            results[6]  = self.step_p(cc+6,  cc+7,  cc+3,  ee+12, ee+10);
-            results[7]  = self.step_p(cc+7,  cc+8,  cc+4,  ee+13, ee+11);
-            results[8]  = self.step_p(cc+8,  cc+9,  cc+5,  ee+14, ee+12);
-            results[9]  = self.step_p(cc+9,  cc+10, cc+6,  ee+15, ee+13);
-            results[10] = self.step_p(cc+10, cc+11, cc+7,  cc+0,  ee+14);
-            results[11] = self.step_p(cc+11, cc+12, cc+8,  cc+1,  ee+15);
-            results[12] = self.step_p(cc+12, cc+13, cc+9,  cc+2,  cc+0);
-            results[13] = self.step_p(cc+13, cc+14, cc+10, cc+3,  cc+1);
-            results[14] = self.step_p(cc+14, cc+15, cc+11, cc+4,  cc+2);
-            results[15] = self.step_p(cc+15, dd+0,  cc+12, cc+5,  cc+3);
+            out(0,  self.step_p(cc+0,  cc+1,  ee+13, ee+6,  ee+4));
+            out(1,  self.step_p(cc+1,  cc+2,  ee+14, ee+7,  ee+5));
+            out(2,  self.step_p(cc+2,  cc+3,  ee+15, ee+8,  ee+6));
+            out(3,  self.step_p(cc+3,  cc+4,  cc+0,  ee+9,  ee+7));
+            out(4,  self.step_p(cc+4,  cc+5,  cc+1,  ee+10, ee+8));
+            out(5,  self.step_p(cc+5,  cc+6,  cc+2,  ee+11, ee+9));
+            out(6,  self.step_p(cc+6,  cc+7,  cc+3,  ee+12, ee+10));
+            out(7,  self.step_p(cc+7,  cc+8,  cc+4,  ee+13, ee+11));
+            out(8,  self.step_p(cc+8,  cc+9,  cc+5,  ee+14, ee+12));
+            out(9,  self.step_p(cc+9,  cc+10, cc+6,  ee+15, ee+13));
+            out(10, self.step_p(cc+10, cc+11, cc+7,  cc+0,  ee+14));
+            out(11, self.step_p(cc+11, cc+12, cc+8,  cc+1,  ee+15));
+            out(12, self.step_p(cc+12, cc+13, cc+9,  cc+2,  cc+0));
+            out(13, self.step_p(cc+13, cc+14, cc+10, cc+3,  cc+1));
+            out(14, self.step_p(cc+14, cc+15, cc+11, cc+4,  cc+2));
+            out(15, self.step_p(cc+15, dd+0,  cc+12, cc+5,  cc+3));
         } else {
             // Q block
-            results[0]  = self.step_q(cc+0,  cc+1,  ee+13, ee+6,  ee+4);
-            results[1]  = self.step_q(cc+1,  cc+2,  ee+14, ee+7,  ee+5);
-            results[2]  = self.step_q(cc+2,  cc+3,  ee+15, ee+8,  ee+6);
-            results[3]  = self.step_q(cc+3,  cc+4,  cc+0,  ee+9,  ee+7);
-            results[4]  = self.step_q(cc+4,  cc+5,  cc+1,  ee+10, ee+8);
-            results[5]  = self.step_q(cc+5,  cc+6,  cc+2,  ee+11, ee+9);
-            results[6]  = self.step_q(cc+6,  cc+7,  cc+3,  ee+12, ee+10);
-            results[7]  = self.step_q(cc+7,  cc+8,  cc+4,  ee+13, ee+11);
-            results[8]  = self.step_q(cc+8,  cc+9,  cc+5,  ee+14, ee+12);
-            results[9]  = self.step_q(cc+9,  cc+10, cc+6,  ee+15, ee+13);
-            results[10] = self.step_q(cc+10, cc+11, cc+7,  cc+0,  ee+14);
-            results[11] = self.step_q(cc+11, cc+12, cc+8,  cc+1,  ee+15);
-            results[12] = self.step_q(cc+12, cc+13, cc+9,  cc+2,  cc+0);
-            results[13] = self.step_q(cc+13, cc+14, cc+10, cc+3,  cc+1);
-            results[14] = self.step_q(cc+14, cc+15, cc+11, cc+4,  cc+2);
-            results[15] = self.step_q(cc+15, dd+0,  cc+12, cc+5,  cc+3);
+            out(0,  self.step_q(cc+0,  cc+1,  ee+13, ee+6,  ee+4));
+            out(1,  self.step_q(cc+1,  cc+2,  ee+14, ee+7,  ee+5));
+            out(2,  self.step_q(cc+2,  cc+3,  ee+15, ee+8,  ee+6));
+            out(3,  self.step_q(cc+3,  cc+4,  cc+0,  ee+9,  ee+7));
+            out(4,  self.step_q(cc+4,  cc+5,  cc+1,  ee+10, ee+8));
+            out(5,  self.step_q(cc+5,  cc+6,  cc+2,  ee+11, ee+9));
+            out(6,  self.step_q(cc+6,  cc+7,  cc+3,  ee+12, ee+10));
+            out(7,  self.step_q(cc+7,  cc+8,  cc+4,  ee+13, ee+11));
+            out(8,  self.step_q(cc+8,  cc+9,  cc+5,  ee+14, ee+12));
+            out(9,  self.step_q(cc+9,  cc+10, cc+6,  ee+15, ee+13));
+            out(10, self.step_q(cc+10, cc+11, cc+7,  cc+0,  ee+14));
+            out(11, self.step_q(cc+11, cc+12, cc+8,  cc+1,  ee+15));
+            out(12, self.step_q(cc+12, cc+13, cc+9,  cc+2,  cc+0));
+            out(13, self.step_q(cc+13, cc+14, cc+10, cc+3,  cc+1));
+            out(14, self.step_q(cc+14, cc+15, cc+11, cc+4,  cc+2));
+            out(15, self.step_q(cc+15, dd+0,  cc+12, cc+5,  cc+3));
         }
         self.counter1024 = self.counter1024.wrapping_add(16);
     }
-}
 
-impl Hc128Core {
     // One step of HC-128, update P and generate 32 bits keystream
     #[inline(always)]
     fn step_p(&mut self, i: usize, i511: usize, i3: usize, i10: usize, i12: usize) -> u32 {
@@ -351,6 +390,83 @@ impl SeedableRng for Hc128Core {
 
 impl CryptoRng for Hc128Core {}
 
+/// The state consists of the table `T` (the tables `P` and `Q`) followed by
+/// the step counter (as `u64`).
+impl SnapshotState for Hc128Core {
+    const STATE_LEN: usize = 1024 * 4 + 8;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        for &x in self.t.iter() {
+            w.write_u32(x);
+        }
+        w.write_u64(self.counter1024 as u64);
+    }
+
+    #[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` requires Rust 1.87
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let mut t = [0; 1024];
+        for x in t.iter_mut() {
+            *x = r.read_u32();
+        }
+        // Only the counter modulo 1024 is significant
+        let counter1024 = r.read_u64() as usize;
+        if counter1024 % 16 != 0 {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Hc128Core { t, counter1024 })
+    }
+}
+
+// Serde only supports arrays of up to 32 elements, so the table is (de)serialized
+// as a tuple by hand.
+#[cfg(feature = "serde1")]
+mod table {
+    use core::fmt;
+    use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+    use serde::ser::{SerializeTuple, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(t: &[u32; 1024], s: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = s.serialize_tuple(t.len())?;
+        for x in t.iter() {
+            tuple.serialize_element(x)?;
+        }
+        tuple.end()
+    }
+
+    struct TableVisitor;
+
+    impl<'de> Visitor<'de> for TableVisitor {
+        type Value = [u32; 1024];
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of 1024 u32")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut t = [0; 1024];
+            for (i, x) in t.iter_mut().enumerate() {
+                *x = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            Ok(t)
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[u32; 1024], D::Error> {
+        d.deserialize_tuple(1024, TableVisitor)
+    }
+
+    // `generate` requires the counter to be a multiple of 16
+    pub(super) fn deserialize_counter<'de, D: Deserializer<'de>>(d: D) -> Result<usize, D::Error> {
+        let counter = usize::deserialize(d)?;
+        if counter % 16 != 0 {
+            return Err(de::Error::custom("HC-128 counter is not a multiple of 16"));
+        }
+        Ok(counter)
+    }
+}
+
 // Custom PartialEq implementation as it can't currently be derived from an array of size 1024
 impl PartialEq for Hc128Core {
     fn eq(&self, rhs: &Self) -> bool {
@@ -499,6 +615,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_hc128_fill_bytes_bulk() {
+        // Large requests are written to the destination directly
+        let seed = [0x55; 32];
+        let mut rng1 = Hc128Rng::from_seed(seed);
+        let mut rng2 = Hc128Rng::from_seed(seed);
+        rng1.next_u32();
+        rng2.next_u32();
+        let mut bytes = [0u8; 1003];
+        rng1.fill_bytes(&mut bytes);
+        for chunk in bytes.chunks(4) {
+            let word = rng2.next_u32().to_le_bytes();
+            assert_eq!(chunk, &word[..chunk.len()]);
+        }
+        assert_eq!(rng1.next_u64(), rng2.next_u64());
+    }
+
     #[test]
     fn test_hc128_clone() {
         #[rustfmt::skip]
@@ -510,4 +643,61 @@ mod test {
             assert_eq!(rng1.next_u32(), rng2.next_u32());
         }
     }
+
+    #[test]
+    #[cfg(feature = "serde1")]
+    fn test_hc128_serde() {
+        let seed = [0x55; 32];
+        let mut rng = Hc128Rng::from_seed(seed);
+        for &n in [0, 1, 15, 16, 17, 100].iter() {
+            for _ in 0..n {
+                rng.next_u32();
+            }
+            let buf = bincode::serialize(&rng).unwrap();
+            let mut restored: Hc128Rng = bincode::deserialize(&buf).unwrap();
+            assert_eq!(restored, rng);
+            let mut clone = rng.clone();
+            for _ in 0..100 {
+                assert_eq!(restored.next_u32(), clone.next_u32());
+            }
+        }
+
+        // The core alone
+        let core = super::Hc128Core::from_seed(seed);
+        let buf = bincode::serialize(&core).unwrap();
+        assert_eq!(bincode::deserialize::<super::Hc128Core>(&buf).unwrap(), core);
+
+        // The counter must be a multiple of 16
+        let mut buf = bincode::serialize(&rng).unwrap();
+        // The counter is the last field of `Hc128Core`, the last field of `BlockRng`
+        let i = buf.len() - 8;
+        buf[i] |= 1;
+        assert!(bincode::deserialize::<Hc128Rng>(&buf).is_err());
+    }
+
+    #[test]
+    fn test_hc128_snapshot() {
+        use rand_core::snapshot::{Snapshot, SnapshotError};
+
+        let seed = [0x55; 32];
+        let mut rng = Hc128Rng::from_seed(seed);
+        let mut buf = [0; 11 + 6 + 4096 + 8 + 64 + 4];
+        assert_eq!(Hc128Rng::snapshot_len(), buf.len());
+        for &n in [0, 1, 15, 16, 600].iter() {
+            for _ in 0..n {
+                rng.next_u32();
+            }
+            rng.export_snapshot(&mut buf).unwrap();
+            let mut restored = Hc128Rng::from_snapshot(&buf).unwrap();
+            assert_eq!(restored, rng);
+            let mut clone = rng.clone();
+            for _ in 0..1100 {
+                assert_eq!(restored.next_u32(), clone.next_u32());
+            }
+        }
+
+        buf[17 + 4096] = 1;
+        let result = Hc128Rng::from_snapshot(&buf);
+        assert_eq!(result.unwrap_err(), SnapshotError::InvalidState);
+    }
 }
@@ -23,10 +23,25 @@
 //!     a general purpose RNG using 128-bit multiplications. This has poor
 //!     performance on 32-bit CPUs but is a good choice on 64-bit CPUs for
 //!     both 32-bit and 64-bit output.
+//! -   `Pcg64Dxsm` aka `Lcg128CmDxsm64`, known as `PCG64DXSM` in NumPy, which
+//!     replaces the output function of `Pcg64` to avoid correlations between
+//!     streams. This is a good choice on 64-bit CPUs.
+//! -   `Pcg32Mcg` aka `Mcg64Xsh32`, using a 64-bit multiplicative generator
+//!     with the `pcg32` output function. This uses only 8 bytes of state.
 //!
-//! Both of these use 16 bytes of state and 128-bit seeds, and are considered
-//! value-stable (i.e. any change affecting the output given a fixed seed would
-//! be considered a breaking change to the crate).
+//! All of these are considered value-stable (i.e. any change affecting the
+//! output given a fixed seed would be considered a breaking change to the
+//! crate).
+//!
+//! # Jumping ahead
+//!
+//! All generators can skip any number of steps with `advance`, and compute
+//! the number of steps between two generators with `distance`, in `O(log n)`
+//! time. `advance` is based on Brown, "Random Number Generation with Arbitrary
+//! Stride", Transactions of the American Nuclear Society (Nov. 1994), which
+//! is very similar to fast exponentiation. Since `delta` is unsigned, a
+//! generator is moved backwards by passing a negative number cast to the
+//! unsigned type: it just goes "the long way round".
 
 #![doc(
     html_logo_url = "https://www.rust-lang.org/logos/rust-logo-128x128-blk.png",
@@ -38,8 +53,11 @@
 #![no_std]
 
 #[cfg(not(target_os = "emscripten"))] mod pcg128;
+#[cfg(not(target_os = "emscripten"))] mod pcg128cm;
 mod pcg64;
 
 #[cfg(not(target_os = "emscripten"))]
 pub use self::pcg128::{Lcg128Xsl64, Mcg128Xsl64, Pcg64, Pcg64Mcg};
-pub use self::pcg64::{Lcg64Xsh32, Pcg32};
+#[cfg(not(target_os = "emscripten"))]
+pub use self::pcg128cm::{Lcg128CmDxsm64, Pcg64Dxsm};
+pub use self::pcg64::{Lcg64Xsh32, Mcg64Xsh32, Pcg32, Pcg32Mcg};
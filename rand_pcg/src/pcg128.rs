@@ -14,7 +14,10 @@
 const MULTIPLIER: u128 = 0x2360_ED05_1FC6_5DA4_4385_DF64_9FCC_F645;
 
 use core::fmt;
-use rand_core::{le, Error, RngCore, SeedableRng};
+use crate::pcg64::{advance_lcg, distance_lcg, FORK_KEY};
+use rand_core::impls::mix64;
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
+use rand_core::{le, Error, RngCore, SeedableRng, SeekableRng, SplittableRng};
 #[cfg(feature = "serde1")] use serde::{Deserialize, Serialize};
 
 /// A PCG random number generator (XSL RR 128/64 (LCG) variant).
@@ -52,6 +55,25 @@ impl Lcg128Xsl64 {
         Lcg128Xsl64::from_state_incr(state, increment)
     }
 
+    /// Advance by `delta` steps, like calling `next_u64()` `delta` times.
+    #[inline]
+    pub fn advance(&mut self, delta: u128) {
+        self.state = advance_lcg(self.state, delta, MULTIPLIER, self.increment);
+    }
+
+    /// Number of steps from `self` to `other`.
+    ///
+    /// Returns the `delta` such that calling [`advance`](Self::advance)
+    /// with `delta` on `self` yields a generator equal to `other`, or `None`
+    /// if the generators use different streams (in which case the state of
+    /// `other` is never reached).
+    pub fn distance(&self, other: &Self) -> Option<u128> {
+        if self.increment != other.increment {
+            return None;
+        }
+        Some(distance_lcg(self.state, other.state, MULTIPLIER, self.increment, 1))
+    }
+
     #[inline]
     fn from_state_incr(state: u128, increment: u128) -> Self {
         let mut pcg = Lcg128Xsl64 { state, increment };
@@ -118,6 +140,63 @@ impl RngCore for Lcg128Xsl64 {
     }
 }
 
+/// The position is the number of steps from state zero of the current
+/// stream.
+impl SeekableRng for Lcg128Xsl64 {
+    type Position = u128;
+
+    #[inline]
+    fn get_position(&self) -> u128 {
+        let origin = Lcg128Xsl64 { state: 0, increment: self.increment };
+        // All states of the stream are reachable from state zero.
+        origin.distance(self).unwrap()
+    }
+
+    #[inline]
+    fn set_position(&mut self, pos: u128) {
+        self.state = 0;
+        Lcg128Xsl64::advance(self, pos);
+    }
+
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        Lcg128Xsl64::advance(self, delta);
+    }
+}
+
+/// Children use a stream and state derived from those of their parent and the
+/// index. 2<sup>127</sup> streams are available.
+impl SplittableRng for Lcg128Xsl64 {
+    fn fork(&self, index: u64) -> Self {
+        let key = mix128(u128::from(index ^ FORK_KEY));
+        Lcg128Xsl64::new(mix128(self.state ^ key), mix128((self.increment >> 1) ^ key))
+    }
+}
+
+/// The state consists of the LCG state and increment (as `u128`).
+impl SnapshotState for Lcg128Xsl64 {
+    const STATE_LEN: usize = 32;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        w.write_u128(self.state);
+        w.write_u128(self.increment);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let state = r.read_u128();
+        let increment = r.read_u128();
+        if increment & 1 == 0 {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Lcg128Xsl64 { state, increment })
+    }
+}
+
+impl Snapshot for Lcg128Xsl64 {
+    const ALGORITHM: &'static str = "Lcg128Xsl64";
+    const VERSION: u16 = 1;
+}
+
 
 /// A PCG random number generator (XSL 128/64 (MCG) variant).
 ///
@@ -149,6 +228,29 @@ impl Mcg128Xsl64 {
         // Force low bit to 1, as in C version (C++ uses `state | 3` instead).
         Mcg128Xsl64 { state: state | 1 }
     }
+
+    /// Advance by `delta` steps, like calling `next_u64()` `delta` times.
+    #[inline]
+    pub fn advance(&mut self, delta: u128) {
+        self.state = advance_lcg(self.state, delta, MULTIPLIER, 0);
+    }
+
+    /// Number of steps from `self` to `other`.
+    ///
+    /// Returns the `delta` such that calling [`advance`](Self::advance)
+    /// with `delta` on `self` yields a generator equal to `other`, or `None`
+    /// if `other` is not reachable from `self`.
+    ///
+    /// The two lowest bits of the state are never changed by the MCG, hence
+    /// its states form two disjoint cycles of length 2^126.
+    pub fn distance(&self, other: &Self) -> Option<u128> {
+        if self.state & 3 != other.state & 3 {
+            return None;
+        }
+        // The MCG multiplier is 5 modulo 8, so each doubling of the step size
+        // determines one bit of the state starting with the third.
+        Some(distance_lcg(self.state, other.state, MULTIPLIER, 0, 4) >> 2)
+    }
 }
 
 // Custom Debug implementation that does not expose the internal state
@@ -197,6 +299,70 @@ impl RngCore for Mcg128Xsl64 {
     }
 }
 
+/// The position is the number of steps from the state with the same two
+/// lowest bits as the current state and all other bits zero. Positions are
+/// reduced modulo the period of 2^126.
+impl SeekableRng for Mcg128Xsl64 {
+    type Position = u128;
+
+    #[inline]
+    fn get_position(&self) -> u128 {
+        let origin = Mcg128Xsl64 { state: self.state & 3 };
+        // The two lowest bits are invariant, so `self` is reachable.
+        origin.distance(self).unwrap()
+    }
+
+    #[inline]
+    fn set_position(&mut self, pos: u128) {
+        self.state &= 3;
+        Mcg128Xsl64::advance(self, pos);
+    }
+
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        Mcg128Xsl64::advance(self, delta);
+    }
+}
+
+/// An MCG has no streams: children start at a state derived from the state of
+/// their parent and the index, i.e. at a pseudo-random position in the cycle
+/// of 2<sup>126</sup> states.
+impl SplittableRng for Mcg128Xsl64 {
+    fn fork(&self, index: u64) -> Self {
+        Mcg128Xsl64::new(mix128(self.state ^ mix128(u128::from(index ^ FORK_KEY))))
+    }
+}
+
+/// The state consists of the MCG state (as `u128`).
+impl SnapshotState for Mcg128Xsl64 {
+    const STATE_LEN: usize = 16;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        w.write_u128(self.state);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let state = r.read_u128();
+        if state & 1 == 0 {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Mcg128Xsl64 { state })
+    }
+}
+
+impl Snapshot for Mcg128Xsl64 {
+    const ALGORITHM: &'static str = "Mcg128Xsl64";
+    const VERSION: u16 = 1;
+}
+
+/// A bijection on `u128` built from [`mix64`], used to derive children.
+#[inline]
+pub(crate) fn mix128(x: u128) -> u128 {
+    let lo = mix64(x as u64);
+    let hi = mix64((x >> 64) as u64 ^ lo);
+    u128::from(hi) << 64 | u128::from(lo)
+}
+
 #[inline(always)]
 fn output_xsl_rr(state: u128) -> u64 {
     // Output function XSL RR ("xorshift low (bits), random rotation")
@@ -210,7 +376,7 @@ fn output_xsl_rr(state: u128) -> u64 {
 }
 
 #[inline(always)]
-fn fill_bytes_impl<R: RngCore + ?Sized>(rng: &mut R, dest: &mut [u8]) {
+pub(crate) fn fill_bytes_impl<R: RngCore + ?Sized>(rng: &mut R, dest: &mut [u8]) {
     let mut left = dest;
     while left.len() >= 8 {
         let (l, r) = { left }.split_at_mut(8);
@@ -0,0 +1,222 @@
+// Copyright 2018-2021 Developers of the Rand project.
+// Copyright 2017 Paul Dicker.
+// Copyright 2014-2017, 2019 Melissa O'Neill and PCG Project contributors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! PCG random number generators
+
+// This is the cheap multiplier used by PCG for 128-bit state.
+const MULTIPLIER: u64 = 15750249268501108917;
+
+use crate::pcg128::{fill_bytes_impl, mix128};
+use crate::pcg64::{advance_lcg, distance_lcg, FORK_KEY};
+use core::fmt;
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
+use rand_core::{le, Error, RngCore, SeedableRng, SeekableRng, SplittableRng};
+#[cfg(feature = "serde1")] use serde::{Deserialize, Serialize};
+
+/// A PCG random number generator (CM DXSM 128/64 (LCG) variant).
+///
+/// Permuted Congruential Generator with 128-bit state, internal Linear
+/// Congruential Generator with a "cheap" 64-bit multiplier, and 64-bit output
+/// via "double xorshift multiply" output function.
+///
+/// This is a 128-bit LCG with explicitly chosen stream with the PCG-DXSM
+/// output function. This corresponds to `pcg_engines::cm_setseq_dxsm_128_64`
+/// from pcg-cpp and `PCG64DXSM` from NumPy: given the same `state` and
+/// `stream` (NumPy's `seed` and `inc` words), [`Lcg128CmDxsm64::new`] yields
+/// the same output sequence.
+///
+/// The DXSM output function mitigates the correlation which may be observed
+/// between [`Lcg128Xsl64`](crate::Lcg128Xsl64) generators with different
+/// stream parameters; see the [NumPy discussion][upgrading-pcg64].
+///
+/// Despite the name, this implementation uses 32 bytes (256 bit) space
+/// comprising 128 bits of state and 128 bits stream selector. These are both
+/// set by `SeedableRng`, using a 256-bit seed.
+///
+/// [upgrading-pcg64]: https://numpy.org/doc/stable/reference/random/upgrading-pcg64.html
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct Lcg128CmDxsm64 {
+    state: u128,
+    increment: u128,
+}
+
+/// [`Lcg128CmDxsm64`] is also known as `PCG64DXSM`.
+pub type Pcg64Dxsm = Lcg128CmDxsm64;
+
+impl Lcg128CmDxsm64 {
+    /// Construct an instance compatible with PCG seed and stream.
+    ///
+    /// Note that PCG specifies default values for both parameters:
+    ///
+    /// - `state = 0xcafef00dd15ea5e5`
+    /// - `stream = 0xa02bdbf7bb3c0a7ac28fa16a64abf96`
+    pub fn new(state: u128, stream: u128) -> Self {
+        // The increment must be odd, hence we discard one bit:
+        let increment = (stream << 1) | 1;
+        Lcg128CmDxsm64::from_state_incr(state, increment)
+    }
+
+    /// Advance by `delta` steps, like calling `next_u64()` `delta` times.
+    #[inline]
+    pub fn advance(&mut self, delta: u128) {
+        self.state = advance_lcg(self.state, delta, MULTIPLIER.into(), self.increment);
+    }
+
+    /// Number of steps from `self` to `other`.
+    ///
+    /// Returns the `delta` such that calling [`advance`](Self::advance)
+    /// with `delta` on `self` yields a generator equal to `other`, or `None`
+    /// if the generators use different streams (in which case the state of
+    /// `other` is never reached).
+    pub fn distance(&self, other: &Self) -> Option<u128> {
+        if self.increment != other.increment {
+            return None;
+        }
+        Some(distance_lcg(self.state, other.state, MULTIPLIER.into(), self.increment, 1))
+    }
+
+    #[inline]
+    fn from_state_incr(state: u128, increment: u128) -> Self {
+        let mut pcg = Lcg128CmDxsm64 { state, increment };
+        // Move away from inital value:
+        pcg.state = pcg.state.wrapping_add(pcg.increment);
+        pcg.step();
+        pcg
+    }
+
+    #[inline(always)]
+    fn step(&mut self) {
+        // prepare the LCG for the next round
+        self.state = self
+            .state
+            .wrapping_mul(u128::from(MULTIPLIER))
+            .wrapping_add(self.increment);
+    }
+}
+
+// Custom Debug implementation that does not expose the internal state
+impl fmt::Debug for Lcg128CmDxsm64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Lcg128CmDxsm64 {{}}")
+    }
+}
+
+/// We use a single 255-bit seed to initialise the state and select a stream.
+/// One `seed` bit (lowest bit of `seed[8]`) is ignored.
+impl SeedableRng for Lcg128CmDxsm64 {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut seed_u64 = [0u64; 4];
+        le::read_u64_into(&seed, &mut seed_u64);
+        let state = u128::from(seed_u64[0]) | (u128::from(seed_u64[1]) << 64);
+        let incr = u128::from(seed_u64[2]) | (u128::from(seed_u64[3]) << 64);
+
+        // The increment must be odd, hence we discard one bit:
+        Lcg128CmDxsm64::from_state_incr(state, incr | 1)
+    }
+}
+
+impl RngCore for Lcg128CmDxsm64 {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        // Unlike the XSL RR variants, DXSM uses the state before stepping.
+        let res = output_dxsm(self.state);
+        self.step();
+        res
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_impl(self, dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The position is the number of steps from state zero of the current
+/// stream.
+impl SeekableRng for Lcg128CmDxsm64 {
+    type Position = u128;
+
+    #[inline]
+    fn get_position(&self) -> u128 {
+        let origin = Lcg128CmDxsm64 { state: 0, increment: self.increment };
+        // All states of the stream are reachable from state zero.
+        origin.distance(self).unwrap()
+    }
+
+    #[inline]
+    fn set_position(&mut self, pos: u128) {
+        self.state = 0;
+        Lcg128CmDxsm64::advance(self, pos);
+    }
+
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        Lcg128CmDxsm64::advance(self, delta);
+    }
+}
+
+/// Children use a stream and state derived from those of their parent and the
+/// index. 2<sup>127</sup> streams are available.
+impl SplittableRng for Lcg128CmDxsm64 {
+    fn fork(&self, index: u64) -> Self {
+        let key = mix128(u128::from(index ^ FORK_KEY));
+        Lcg128CmDxsm64::new(mix128(self.state ^ key), mix128((self.increment >> 1) ^ key))
+    }
+}
+
+/// The state consists of the LCG state and increment (as `u128`).
+impl SnapshotState for Lcg128CmDxsm64 {
+    const STATE_LEN: usize = 32;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        w.write_u128(self.state);
+        w.write_u128(self.increment);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let state = r.read_u128();
+        let increment = r.read_u128();
+        if increment & 1 == 0 {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Lcg128CmDxsm64 { state, increment })
+    }
+}
+
+impl Snapshot for Lcg128CmDxsm64 {
+    const ALGORITHM: &'static str = "Lcg128CmDxsm64";
+    const VERSION: u16 = 1;
+}
+
+#[inline(always)]
+fn output_dxsm(state: u128) -> u64 {
+    // Output function DXSM ("double xorshift multiply"), see
+    // https://github.com/imneme/pcg-cpp/blob/ffd522e7188bef30a00c74dc7eb9de5faff90092/include/pcg_random.hpp#L1016
+    let mut hi = (state >> 64) as u64;
+    let lo = (state as u64) | 1;
+
+    hi ^= hi >> 32;
+    hi = hi.wrapping_mul(MULTIPLIER);
+    hi ^= hi >> 48;
+    hi.wrapping_mul(lo)
+}
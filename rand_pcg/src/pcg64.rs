@@ -11,7 +11,9 @@
 //! PCG random number generators
 
 use core::fmt;
-use rand_core::{impls, le, Error, RngCore, SeedableRng};
+use core::ops::{BitAnd, BitOr, Shl, Shr};
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
+use rand_core::{impls, le, Error, RngCore, SeedableRng, SeekableRng, SplittableRng};
 #[cfg(feature = "serde1")] use serde::{Deserialize, Serialize};
 
 // This is the default multiplier used by PCG for 64-bit state.
@@ -53,6 +55,28 @@ impl Lcg64Xsh32 {
         Lcg64Xsh32::from_state_incr(state, increment)
     }
 
+    /// Advance by `delta` steps, like calling `next_u32()` `delta` times.
+    #[inline]
+    pub fn advance(&mut self, delta: u64) {
+        self.state = advance_lcg(self.state, delta, MULTIPLIER, self.increment);
+    }
+
+    /// Number of steps from `self` to `other`.
+    ///
+    /// Returns the `delta` such that calling [`advance`](Self::advance)
+    /// with `delta` on `self` yields a generator equal to `other`, or `None`
+    /// if the generators use different streams (in which case the state of
+    /// `other` is never reached).
+    ///
+    /// This takes `O(log n)` time, using the bit-by-bit method of the PCG
+    /// C++ implementation.
+    pub fn distance(&self, other: &Self) -> Option<u64> {
+        if self.increment != other.increment {
+            return None;
+        }
+        Some(distance_lcg(self.state, other.state, MULTIPLIER, self.increment, 1))
+    }
+
     #[inline]
     fn from_state_incr(state: u64, increment: u64) -> Self {
         let mut pcg = Lcg64Xsh32 { state, increment };
@@ -98,16 +122,163 @@ impl RngCore for Lcg64Xsh32 {
     fn next_u32(&mut self) -> u32 {
         let state = self.state;
         self.step();
+        output_xsh_rr(state)
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The position is the number of steps from state zero of the current
+/// stream.
+impl SeekableRng for Lcg64Xsh32 {
+    type Position = u64;
 
-        // Output function XSH RR: xorshift high (bits), followed by a random rotate
-        // Constants are for 64-bit state, 32-bit output
-        const ROTATE: u32 = 59; // 64 - 5
-        const XSHIFT: u32 = 18; // (5 + 32) / 2
-        const SPARE: u32 = 27; // 64 - 32 - 5
+    #[inline]
+    fn get_position(&self) -> u64 {
+        let origin = Lcg64Xsh32 { state: 0, increment: self.increment };
+        // All states of the stream are reachable from state zero.
+        origin.distance(self).unwrap()
+    }
 
-        let rot = (state >> ROTATE) as u32;
-        let xsh = (((state >> XSHIFT) ^ state) >> SPARE) as u32;
-        xsh.rotate_right(rot)
+    #[inline]
+    fn set_position(&mut self, pos: u64) {
+        self.state = 0;
+        Lcg64Xsh32::advance(self, pos);
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        Lcg64Xsh32::advance(self, delta as u64);
+    }
+}
+
+/// Children use a stream and state derived from those of their parent and the
+/// index. 2<sup>63</sup> streams are available, so that children sharing a
+/// stream only become likely for trees of about 2<sup>31</sup> generators.
+impl SplittableRng for Lcg64Xsh32 {
+    fn fork(&self, index: u64) -> Self {
+        let key = impls::mix64(index ^ FORK_KEY);
+        Lcg64Xsh32::new(impls::mix64(self.state ^ key), impls::mix64((self.increment >> 1) ^ key))
+    }
+}
+
+/// The state consists of the LCG state and increment (as `u64`).
+impl SnapshotState for Lcg64Xsh32 {
+    const STATE_LEN: usize = 16;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        w.write_u64(self.state);
+        w.write_u64(self.increment);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let state = r.read_u64();
+        let increment = r.read_u64();
+        if increment & 1 == 0 {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Lcg64Xsh32 { state, increment })
+    }
+}
+
+impl Snapshot for Lcg64Xsh32 {
+    const ALGORITHM: &'static str = "Lcg64Xsh32";
+    const VERSION: u16 = 1;
+}
+
+
+/// A PCG random number generator (XSH RR 64/32 (MCG) variant).
+///
+/// Permuted Congruential Generator with 64-bit state, internal Multiplicative
+/// Congruential Generator, and 32-bit output via "xorshift high (bits),
+/// random rotation" output function.
+///
+/// This is a 64-bit MCG with the PCG-XSH-RR output function, corresponding to
+/// `pcg_mcg_64_xsh_rr_32` from the PCG C library. Compared to
+/// [`Lcg64Xsh32`], it uses only 8 bytes of state and is slightly faster, but
+/// has a shorter period (2^62) and no stream selection.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct Mcg64Xsh32 {
+    state: u64,
+}
+
+/// A friendly name for [`Mcg64Xsh32`].
+pub type Pcg32Mcg = Mcg64Xsh32;
+
+impl Mcg64Xsh32 {
+    /// Construct an instance compatible with PCG seed.
+    ///
+    /// Note that PCG specifies a default value for the parameter:
+    ///
+    /// - `state = 0xcafef00dd15ea5e5`
+    pub fn new(state: u64) -> Self {
+        // Force low bit to 1, as in C version (C++ uses `state | 3` instead).
+        Mcg64Xsh32 { state: state | 1 }
+    }
+
+    /// Advance by `delta` steps, like calling `next_u32()` `delta` times.
+    #[inline]
+    pub fn advance(&mut self, delta: u64) {
+        self.state = advance_lcg(self.state, delta, MULTIPLIER, 0);
+    }
+
+    /// Number of steps from `self` to `other`.
+    ///
+    /// Returns the `delta` such that calling [`advance`](Self::advance)
+    /// with `delta` on `self` yields a generator equal to `other`, or `None`
+    /// if `other` is not reachable from `self`.
+    ///
+    /// The two lowest bits of the state are never changed by the MCG, hence
+    /// its states form two disjoint cycles of length 2^62.
+    pub fn distance(&self, other: &Self) -> Option<u64> {
+        if self.state & 3 != other.state & 3 {
+            return None;
+        }
+        // The MCG multiplier is 5 modulo 8, so each doubling of the step size
+        // determines one bit of the state starting with the third.
+        Some(distance_lcg(self.state, other.state, MULTIPLIER, 0, 4) >> 2)
+    }
+}
+
+// Custom Debug implementation that does not expose the internal state
+impl fmt::Debug for Mcg64Xsh32 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mcg64Xsh32 {{}}")
+    }
+}
+
+/// We use a single 63-bit seed to initialise the state.
+/// One `seed` bit (lowest bit of `seed[0]`) is ignored.
+impl SeedableRng for Mcg64Xsh32 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Mcg64Xsh32::new(u64::from_le_bytes(seed))
+    }
+}
+
+impl RngCore for Mcg64Xsh32 {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = self.state.wrapping_mul(MULTIPLIER);
+        output_xsh_rr(state)
     }
 
     #[inline]
@@ -126,3 +297,161 @@ impl RngCore for Lcg64Xsh32 {
         Ok(())
     }
 }
+
+/// The position is the number of steps from the state with the same two
+/// lowest bits as the current state and all other bits zero. Positions are
+/// reduced modulo the period of 2^62.
+impl SeekableRng for Mcg64Xsh32 {
+    type Position = u64;
+
+    #[inline]
+    fn get_position(&self) -> u64 {
+        let origin = Mcg64Xsh32 { state: self.state & 3 };
+        // The two lowest bits are invariant, so `self` is reachable.
+        origin.distance(self).unwrap()
+    }
+
+    #[inline]
+    fn set_position(&mut self, pos: u64) {
+        self.state &= 3;
+        Mcg64Xsh32::advance(self, pos);
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[inline]
+    fn advance(&mut self, delta: u128) {
+        Mcg64Xsh32::advance(self, delta as u64);
+    }
+}
+
+/// An MCG has no streams: children start at a state derived from the state of
+/// their parent and the index, i.e. at a pseudo-random position in the cycle
+/// of 2<sup>62</sup> states. This is only suitable for small trees.
+impl SplittableRng for Mcg64Xsh32 {
+    fn fork(&self, index: u64) -> Self {
+        Mcg64Xsh32::new(impls::mix64(self.state ^ impls::mix64(index ^ FORK_KEY)))
+    }
+}
+
+/// The state consists of the MCG state (as `u64`).
+impl SnapshotState for Mcg64Xsh32 {
+    const STATE_LEN: usize = 8;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        w.write_u64(self.state);
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let state = r.read_u64();
+        if state & 1 == 0 {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Mcg64Xsh32 { state })
+    }
+}
+
+impl Snapshot for Mcg64Xsh32 {
+    const ALGORITHM: &'static str = "Mcg64Xsh32";
+    const VERSION: u16 = 1;
+}
+
+// Key mixed into the index of a child generator.
+pub(crate) const FORK_KEY: u64 = 0x9e3779b97f4a7c15;
+
+/// Unsigned integer type of the state of an LCG.
+pub(crate) trait LcgState:
+    Copy
+    + PartialEq
+    + fmt::Debug
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_lcg_state {
+    ($ty:ty) => {
+        impl LcgState for $ty {
+            const ZERO: $ty = 0;
+            const ONE: $ty = 1;
+
+            #[inline(always)]
+            fn wrapping_add(self, rhs: $ty) -> $ty {
+                <$ty>::wrapping_add(self, rhs)
+            }
+
+            #[inline(always)]
+            fn wrapping_mul(self, rhs: $ty) -> $ty {
+                <$ty>::wrapping_mul(self, rhs)
+            }
+        }
+    };
+}
+
+impl_lcg_state!(u64);
+#[cfg(not(target_os = "emscripten"))]
+impl_lcg_state!(u128);
+
+/// Compute the state after `delta` steps of the LCG with multiplier `mult`
+/// and increment `plus`.
+#[inline]
+pub(crate) fn advance_lcg<T: LcgState>(state: T, delta: T, mult: T, plus: T) -> T {
+    let mut acc_mult = T::ONE;
+    let mut acc_plus = T::ZERO;
+    let mut cur_mult = mult;
+    let mut cur_plus = plus;
+    let mut mdelta = delta;
+
+    while mdelta != T::ZERO {
+        if (mdelta & T::ONE) != T::ZERO {
+            acc_mult = acc_mult.wrapping_mul(cur_mult);
+            acc_plus = acc_plus.wrapping_mul(cur_mult).wrapping_add(cur_plus);
+        }
+        cur_plus = cur_mult.wrapping_add(T::ONE).wrapping_mul(cur_plus);
+        cur_mult = cur_mult.wrapping_mul(cur_mult);
+        mdelta = mdelta >> 1;
+    }
+    acc_mult.wrapping_mul(state).wrapping_add(acc_plus)
+}
+
+/// Compute the number of LCG steps from `state` to `target`, matching one
+/// bit per iteration starting at `the_bit`.
+///
+/// The caller must ensure `target` is reachable from `state`.
+pub(crate) fn distance_lcg<T: LcgState>(
+    mut state: T, target: T, mult: T, plus: T, mut the_bit: T,
+) -> T {
+    let mut cur_mult = mult;
+    let mut cur_plus = plus;
+    let mut distance = T::ZERO;
+    while state != target {
+        if (state & the_bit) != (target & the_bit) {
+            state = state.wrapping_mul(cur_mult).wrapping_add(cur_plus);
+            distance = distance | the_bit;
+        }
+        debug_assert_eq!(state & the_bit, target & the_bit);
+        the_bit = the_bit << 1;
+        cur_plus = cur_mult.wrapping_add(T::ONE).wrapping_mul(cur_plus);
+        cur_mult = cur_mult.wrapping_mul(cur_mult);
+    }
+    distance
+}
+
+#[inline(always)]
+fn output_xsh_rr(state: u64) -> u32 {
+    // Output function XSH RR: xorshift high (bits), followed by a random rotate
+    // Constants are for 64-bit state, 32-bit output
+    const ROTATE: u32 = 59; // 64 - 5
+    const XSHIFT: u32 = 18; // (5 + 32) / 2
+    const SPARE: u32 = 27; // 64 - 32 - 5
+
+    let rot = (state >> ROTATE) as u32;
+    let xsh = (((state >> XSHIFT) ^ state) >> SPARE) as u32;
+    xsh.rotate_right(rot)
+}
@@ -0,0 +1,127 @@
+// Tests shared by all PCG generators. The traits used by the tests must be in
+// scope where the macros are used.
+
+// Advancing is equivalent to stepping.
+macro_rules! test_advancing {
+    ($Rng:ty, $next:ident) => {
+        for seed in 0..20 {
+            let mut rng1 = <$Rng>::seed_from_u64(seed);
+            let mut rng2 = rng1.clone();
+            for _ in 0..20 {
+                rng1.$next();
+            }
+            rng2.advance(20);
+            assert_eq!(rng1, rng2);
+        }
+    };
+}
+
+// `distance` inverts `advance` modulo `$mask`, the period of the generator
+// minus one; `$other` and `$another` are not reachable from each other.
+macro_rules! test_distance {
+    ($Rng:ty, $next:ident, $Pos:ty, $mask:expr, $other:expr, $another:expr) => {
+        let rng1 = <$Rng>::seed_from_u64(0);
+        let mut rng2 = rng1.clone();
+        assert_eq!(rng1.distance(&rng2), Some(0));
+        for _ in 0..100 {
+            rng2.$next();
+        }
+        assert_eq!(rng1.distance(&rng2), Some(100));
+        // Going backwards wraps around the period
+        assert_eq!(
+            rng2.distance(&rng1),
+            Some((100 as $Pos).wrapping_neg() & $mask)
+        );
+
+        let mut rng3 = rng1.clone();
+        rng3.advance(0x1234_5678_9abc_def0);
+        assert_eq!(rng1.distance(&rng3), Some(0x1234_5678_9abc_def0));
+
+        let other: $Rng = $other;
+        let another: $Rng = $another;
+        assert_eq!(other.distance(&another), None);
+    };
+}
+
+// The position is the number of steps from the origin, modulo the period.
+macro_rules! test_seekable {
+    ($Rng:ty, $next:ident, $Pos:ty, $mask:expr) => {
+        let mut rng1 = <$Rng>::seed_from_u64(0);
+        let mut rng2 = rng1.clone();
+        let start = rng1.get_position();
+        for _ in 0..20 {
+            rng1.$next();
+        }
+        assert_eq!(rng1.get_position(), start.wrapping_add(20) & $mask);
+        SeekableRng::advance(&mut rng2, 20);
+        assert_eq!(rng1, rng2);
+
+        rng2.set_position(start);
+        assert_eq!(rng2, <$Rng>::seed_from_u64(0));
+        rng2.set_position(12345);
+        assert_eq!(rng2.get_position(), 12345);
+
+        // Positions wrap around the period
+        rng1.set_position(0);
+        SeekableRng::advance(&mut rng1, 100u128.wrapping_neg());
+        assert_eq!(rng1.get_position(), (100 as $Pos).wrapping_neg() & $mask);
+    };
+}
+
+macro_rules! test_fork {
+    ($Rng:ty) => {
+        let rng = <$Rng>::seed_from_u64(0);
+        let child = rng.fork(1);
+        assert_ne!(child, rng);
+        assert_ne!(child, rng.fork(2));
+
+        // `split` returns `fork(0)` and replaces the parent by `fork(1)`
+        let mut parent = rng.clone();
+        let mut child = parent.split();
+        assert_eq!(child, rng.fork(0));
+        assert_eq!(parent, rng.fork(1));
+        assert_ne!(child.next_u64(), parent.next_u64());
+    };
+}
+
+// The state ends with the increment, or with the state of an MCG, given as a
+// `$Pos`; both must be odd.
+macro_rules! test_snapshot {
+    ($Rng:ty, $Pos:ty) => {
+        let mut rng = <$Rng>::seed_from_u64(0);
+        rng.next_u64();
+        let mut buf = vec![0; <$Rng>::snapshot_len()];
+        assert_eq!(rng.export_snapshot(&mut buf), Ok(buf.len()));
+        let mut restored = <$Rng>::from_snapshot(&buf).unwrap();
+        assert_eq!(restored, rng);
+        assert_eq!(restored.next_u64(), rng.next_u64());
+
+        let len = buf.len();
+        buf[len - std::mem::size_of::<$Pos>()] ^= 1;
+        let result = <$Rng>::from_snapshot(&buf);
+        assert_eq!(result.unwrap_err(), SnapshotError::InvalidState);
+    };
+}
+
+#[cfg(feature = "serde1")]
+macro_rules! test_serde {
+    ($Rng:ty) => {
+        use bincode;
+        use std::io::{BufReader, BufWriter};
+
+        let mut rng = <$Rng>::seed_from_u64(0);
+
+        let buf: Vec<u8> = Vec::new();
+        let mut buf = BufWriter::new(buf);
+        bincode::serialize_into(&mut buf, &rng).expect("Could not serialize");
+
+        let buf = buf.into_inner().unwrap();
+        let mut read = BufReader::new(&buf[..]);
+        let mut deserialized: $Rng =
+            bincode::deserialize_from(&mut read).expect("Could not deserialize");
+
+        for _ in 0..16 {
+            assert_eq!(rng.next_u64(), deserialized.next_u64());
+        }
+    };
+}
@@ -0,0 +1,90 @@
+use rand_core::snapshot::{Snapshot, SnapshotError};
+use rand_core::{RngCore, SeedableRng, SeekableRng, SplittableRng};
+use rand_pcg::{Lcg128CmDxsm64, Pcg64Dxsm};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn test_lcg128cmdxsm64_construction() {
+    // Test that various construction techniques produce a working RNG.
+    #[rustfmt::skip]
+    let seed = [1,2,3,4, 5,6,7,8, 9,10,11,12, 13,14,15,16,
+            17,18,19,20, 21,22,23,24, 25,26,27,28, 29,30,31,32];
+    let mut rng1 = Lcg128CmDxsm64::from_seed(seed);
+    assert_eq!(rng1.next_u64(), 12201417210360370199);
+
+    let mut rng2 = Lcg128CmDxsm64::from_rng(&mut rng1).unwrap();
+    assert_eq!(rng2.next_u64(), 11487972556150888383);
+
+    let mut rng3 = Lcg128CmDxsm64::seed_from_u64(0);
+    assert_eq!(rng3.next_u64(), 4111470453933123814);
+
+    // This is the same as Lcg128CmDxsm64, so we only have a single test:
+    let mut rng4 = Pcg64Dxsm::seed_from_u64(0);
+    assert_eq!(rng4.next_u64(), 4111470453933123814);
+}
+
+#[test]
+fn test_lcg128cmdxsm64_true_values() {
+    // Numbers determined using `pcg_engines::cm_setseq_dxsm_128_64` from
+    // pcg-cpp, which is also used by NumPy's `PCG64DXSM`.
+    let mut rng = Lcg128CmDxsm64::new(42, 54);
+
+    let mut results = [0u64; 6];
+    for i in results.iter_mut() {
+        *i = rng.next_u64();
+    }
+    let expected: [u64; 6] = [
+        17331114245835578256,
+        10267467544499227306,
+        9726600296081716989,
+        10165951391103677450,
+        12131334649314727261,
+        10134094537930450875,
+    ];
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn test_lcg128cmdxsm64_advancing() {
+    test_advancing!(Lcg128CmDxsm64, next_u64);
+}
+
+#[test]
+fn test_lcg128cmdxsm64_distance() {
+    test_distance!(
+        Lcg128CmDxsm64,
+        next_u64,
+        u128,
+        !0,
+        Lcg128CmDxsm64::seed_from_u64(0),
+        Lcg128CmDxsm64::new(0, 1)
+    );
+}
+
+#[test]
+fn test_lcg128cmdxsm64_seekable() {
+    test_seekable!(Lcg128CmDxsm64, next_u64, u128, !0);
+}
+
+#[test]
+fn test_lcg128cmdxsm64_fork() {
+    test_fork!(Lcg128CmDxsm64);
+
+    // Children use different streams
+    let rng = Lcg128CmDxsm64::seed_from_u64(0);
+    assert_eq!(rng.distance(&rng.fork(1)), None);
+    assert_eq!(rng.fork(1).distance(&rng.fork(2)), None);
+}
+
+#[test]
+fn test_lcg128cmdxsm64_snapshot() {
+    test_snapshot!(Lcg128CmDxsm64, u128);
+}
+
+#[cfg(feature = "serde1")]
+#[test]
+fn test_lcg128cmdxsm64_serde() {
+    test_serde!(Lcg128CmDxsm64);
+}
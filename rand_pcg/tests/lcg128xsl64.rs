@@ -1,6 +1,10 @@
-use rand_core::{RngCore, SeedableRng};
+use rand_core::snapshot::{Snapshot, SnapshotError};
+use rand_core::{RngCore, SeedableRng, SeekableRng, SplittableRng};
 use rand_pcg::{Lcg128Xsl64, Pcg64};
 
+#[macro_use]
+mod common;
+
 #[test]
 fn test_lcg128xsl64_construction() {
     // Test that various construction techniques produce a working RNG.
@@ -41,24 +45,45 @@ fn test_lcg128xsl64_true_values() {
     assert_eq!(results, expected);
 }
 
-#[cfg(feature = "serde1")]
 #[test]
-fn test_lcg128xsl64_serde() {
-    use bincode;
-    use std::io::{BufReader, BufWriter};
+fn test_lcg128xsl64_advancing() {
+    test_advancing!(Lcg128Xsl64, next_u64);
+}
+
+#[test]
+fn test_lcg128xsl64_distance() {
+    test_distance!(
+        Lcg128Xsl64,
+        next_u64,
+        u128,
+        !0,
+        Lcg128Xsl64::seed_from_u64(0),
+        Lcg128Xsl64::new(0, 1)
+    );
+}
+
+#[test]
+fn test_lcg128xsl64_seekable() {
+    test_seekable!(Lcg128Xsl64, next_u64, u128, !0);
+}
 
-    let mut rng = Lcg128Xsl64::seed_from_u64(0);
+#[test]
+fn test_lcg128xsl64_fork() {
+    test_fork!(Lcg128Xsl64);
 
-    let buf: Vec<u8> = Vec::new();
-    let mut buf = BufWriter::new(buf);
-    bincode::serialize_into(&mut buf, &rng).expect("Could not serialize");
+    // Children use different streams
+    let rng = Lcg128Xsl64::seed_from_u64(0);
+    assert_eq!(rng.distance(&rng.fork(1)), None);
+    assert_eq!(rng.fork(1).distance(&rng.fork(2)), None);
+}
 
-    let buf = buf.into_inner().unwrap();
-    let mut read = BufReader::new(&buf[..]);
-    let mut deserialized: Lcg128Xsl64 =
-        bincode::deserialize_from(&mut read).expect("Could not deserialize");
+#[test]
+fn test_lcg128xsl64_snapshot() {
+    test_snapshot!(Lcg128Xsl64, u128);
+}
 
-    for _ in 0..16 {
-        assert_eq!(rng.next_u64(), deserialized.next_u64());
-    }
+#[cfg(feature = "serde1")]
+#[test]
+fn test_lcg128xsl64_serde() {
+    test_serde!(Lcg128Xsl64);
 }
@@ -1,6 +1,10 @@
-use rand_core::{RngCore, SeedableRng};
+use rand_core::snapshot::{Snapshot, SnapshotError};
+use rand_core::{RngCore, SeedableRng, SeekableRng, SplittableRng};
 use rand_pcg::{Lcg64Xsh32, Pcg32};
 
+#[macro_use]
+mod common;
+
 #[test]
 fn test_lcg64xsh32_construction() {
     // Test that various construction techniques produce a working RNG.
@@ -34,24 +38,46 @@ fn test_lcg64xsh32_true_values() {
     assert_eq!(results, expected);
 }
 
-#[cfg(feature = "serde1")]
 #[test]
-fn test_lcg64xsh32_serde() {
-    use bincode;
-    use std::io::{BufReader, BufWriter};
+fn test_lcg64xsh32_advancing() {
+    test_advancing!(Lcg64Xsh32, next_u32);
+}
+
+#[test]
+fn test_lcg64xsh32_distance() {
+    test_distance!(
+        Lcg64Xsh32,
+        next_u32,
+        u64,
+        !0,
+        Lcg64Xsh32::seed_from_u64(0),
+        Lcg64Xsh32::new(0, 1)
+    );
+}
+
+#[cfg(not(target_os = "emscripten"))]
+#[test]
+fn test_lcg64xsh32_seekable() {
+    test_seekable!(Lcg64Xsh32, next_u32, u64, !0);
+}
 
-    let mut rng = Lcg64Xsh32::seed_from_u64(0);
+#[test]
+fn test_lcg64xsh32_fork() {
+    test_fork!(Lcg64Xsh32);
 
-    let buf: Vec<u8> = Vec::new();
-    let mut buf = BufWriter::new(buf);
-    bincode::serialize_into(&mut buf, &rng).expect("Could not serialize");
+    // Children use different streams
+    let rng = Lcg64Xsh32::seed_from_u64(0);
+    assert_eq!(rng.distance(&rng.fork(1)), None);
+    assert_eq!(rng.fork(1).distance(&rng.fork(2)), None);
+}
 
-    let buf = buf.into_inner().unwrap();
-    let mut read = BufReader::new(&buf[..]);
-    let mut deserialized: Lcg64Xsh32 =
-        bincode::deserialize_from(&mut read).expect("Could not deserialize");
+#[test]
+fn test_lcg64xsh32_snapshot() {
+    test_snapshot!(Lcg64Xsh32, u64);
+}
 
-    for _ in 0..16 {
-        assert_eq!(rng.next_u64(), deserialized.next_u64());
-    }
+#[cfg(feature = "serde1")]
+#[test]
+fn test_lcg64xsh32_serde() {
+    test_serde!(Lcg64Xsh32);
 }
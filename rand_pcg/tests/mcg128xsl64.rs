@@ -1,6 +1,10 @@
-use rand_core::{RngCore, SeedableRng};
+use rand_core::snapshot::{Snapshot, SnapshotError};
+use rand_core::{RngCore, SeedableRng, SeekableRng, SplittableRng};
 use rand_pcg::{Mcg128Xsl64, Pcg64Mcg};
 
+#[macro_use]
+mod common;
+
 #[test]
 fn test_mcg128xsl64_construction() {
     // Test that various construction techniques produce a working RNG.
@@ -39,24 +43,40 @@ fn test_mcg128xsl64_true_values() {
     assert_eq!(results, expected);
 }
 
-#[cfg(feature = "serde1")]
 #[test]
-fn test_mcg128xsl64_serde() {
-    use bincode;
-    use std::io::{BufReader, BufWriter};
+fn test_mcg128xsl64_advancing() {
+    test_advancing!(Mcg128Xsl64, next_u64);
+}
 
-    let mut rng = Mcg128Xsl64::seed_from_u64(0);
+#[test]
+fn test_mcg128xsl64_distance() {
+    test_distance!(
+        Mcg128Xsl64,
+        next_u64,
+        u128,
+        (1 << 126) - 1,
+        Mcg128Xsl64::new(0),
+        Mcg128Xsl64::new(2)
+    );
+}
 
-    let buf: Vec<u8> = Vec::new();
-    let mut buf = BufWriter::new(buf);
-    bincode::serialize_into(&mut buf, &rng).expect("Could not serialize");
+#[test]
+fn test_mcg128xsl64_seekable() {
+    test_seekable!(Mcg128Xsl64, next_u64, u128, (1 << 126) - 1);
+}
 
-    let buf = buf.into_inner().unwrap();
-    let mut read = BufReader::new(&buf[..]);
-    let mut deserialized: Mcg128Xsl64 =
-        bincode::deserialize_from(&mut read).expect("Could not deserialize");
+#[test]
+fn test_mcg128xsl64_fork() {
+    test_fork!(Mcg128Xsl64);
+}
 
-    for _ in 0..16 {
-        assert_eq!(rng.next_u64(), deserialized.next_u64());
-    }
+#[test]
+fn test_mcg128xsl64_snapshot() {
+    test_snapshot!(Mcg128Xsl64, u128);
+}
+
+#[cfg(feature = "serde1")]
+#[test]
+fn test_mcg128xsl64_serde() {
+    test_serde!(Mcg128Xsl64);
 }
@@ -0,0 +1,79 @@
+use rand_core::snapshot::{Snapshot, SnapshotError};
+use rand_core::{RngCore, SeedableRng, SeekableRng, SplittableRng};
+use rand_pcg::{Mcg64Xsh32, Pcg32Mcg};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn test_mcg64xsh32_construction() {
+    // Test that various construction techniques produce a working RNG.
+    let seed = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut rng1 = Mcg64Xsh32::from_seed(seed);
+    assert_eq!(rng1.next_u64(), 3500434922188849228);
+
+    let mut rng2 = Mcg64Xsh32::from_rng(&mut rng1).unwrap();
+    assert_eq!(rng2.next_u64(), 7922396579116195733);
+
+    let mut rng3 = Mcg64Xsh32::seed_from_u64(0);
+    assert_eq!(rng3.next_u64(), 6896249467532063902);
+
+    // This is the same as Mcg64Xsh32, so we only have a single test:
+    let mut rng4 = Pcg32Mcg::seed_from_u64(0);
+    assert_eq!(rng4.next_u64(), 6896249467532063902);
+}
+
+#[test]
+fn test_mcg64xsh32_true_values() {
+    // Numbers determined using `pcg_mcg_64_xsh_rr_32_random_r` from the PCG C
+    // library (pcg_variants.h).
+    let mut rng = Mcg64Xsh32::new(0xcafef00dd15ea5e5);
+
+    let mut results = [0u32; 6];
+    for i in results.iter_mut() {
+        *i = rng.next_u32();
+    }
+    let expected: [u32; 6] = [
+        0xec2b26af, 0xa9a8319b, 0x9d740ac1, 0x88527ea0, 0x82d6a89f, 0x801b54ad,
+    ];
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn test_mcg64xsh32_advancing() {
+    test_advancing!(Mcg64Xsh32, next_u32);
+}
+
+#[test]
+fn test_mcg64xsh32_distance() {
+    test_distance!(
+        Mcg64Xsh32,
+        next_u32,
+        u64,
+        (1 << 62) - 1,
+        Mcg64Xsh32::new(0),
+        Mcg64Xsh32::new(2)
+    );
+}
+
+#[cfg(not(target_os = "emscripten"))]
+#[test]
+fn test_mcg64xsh32_seekable() {
+    test_seekable!(Mcg64Xsh32, next_u32, u64, (1 << 62) - 1);
+}
+
+#[test]
+fn test_mcg64xsh32_fork() {
+    test_fork!(Mcg64Xsh32);
+}
+
+#[test]
+fn test_mcg64xsh32_snapshot() {
+    test_snapshot!(Mcg64Xsh32, u64);
+}
+
+#[cfg(feature = "serde1")]
+#[test]
+fn test_mcg64xsh32_serde() {
+    test_serde!(Mcg64Xsh32);
+}
@@ -0,0 +1,175 @@
+// Copyright 2018-2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Number of blocks generated per call to `BlockRngCore::generate`.
+///
+/// NB. this must remain consistent with the `Results` type of the cores, which
+/// hold `BUF_BLOCKS * BLOCK_WORDS` words.
+pub(crate) const BUF_BLOCKS: usize = 4;
+/// Number of words per block; all generators in this crate are 4-word variants.
+pub(crate) const BLOCK_WORDS: usize = 4;
+
+/// Implement a counter-based core and its `BlockRng` wrapper.
+///
+/// `$block` must be a function `fn([$T; 4], [$T; $K]) -> [$T; 4]` computing the
+/// output block for a given counter and key.
+macro_rules! counter_rng_impl {
+    (
+        $(#[$rng_meta:meta])*
+        $Rng:ident, $Core:ident, $core_doc:expr,
+        $BlockRng:ident, $T:ident, key_words: $K:expr, seed: $Seed:ty,
+        $read_into:path, $block:path
+    ) => {
+        #[doc = $core_doc]
+        #[derive(Clone, PartialEq, Eq)]
+        pub struct $Core {
+            key: [$T; $K],
+            // counter of the next block to be generated
+            counter: [$T; 4],
+        }
+
+        // Custom Debug implementation that does not expose the internal state
+        impl fmt::Debug for $Core {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, concat!(stringify!($Core), " {{}}"))
+            }
+        }
+
+        impl BlockRngCore for $Core {
+            type Item = $T;
+            type Results = [$T; 16];
+
+            #[inline]
+            fn generate(&mut self, results: &mut Self::Results) {
+                for block in results.chunks_exact_mut(BLOCK_WORDS) {
+                    block.copy_from_slice(&$block(self.counter, self.key));
+                    for w in self.counter.iter_mut() {
+                        *w = w.wrapping_add(1);
+                        if *w != 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        impl SeedableRng for $Core {
+            type Seed = $Seed;
+
+            /// Create a new core using the little-endian words of `seed` as key,
+            /// starting at counter zero.
+            #[inline]
+            fn from_seed(seed: Self::Seed) -> Self {
+                let mut key = [0; $K];
+                $read_into(&seed, &mut key);
+                $Core {
+                    key,
+                    counter: [0; 4],
+                }
+            }
+        }
+
+        $(#[$rng_meta])*
+        #[derive(Clone, Debug)]
+        pub struct $Rng {
+            rng: $BlockRng<$Core>,
+        }
+
+        impl $Rng {
+            /// Construct a generator from a key and the counter of the first
+            /// block to output.
+            ///
+            /// Counter words are given least significant word first.
+            #[inline]
+            pub fn new(key: [$T; $K], counter: [$T; 4]) -> Self {
+                $Rng {
+                    rng: $BlockRng::new($Core { key, counter }),
+                }
+            }
+
+            /// Get the counter of the block the next output word is taken from.
+            ///
+            /// Words already consumed from that block are not accounted for:
+            /// passing the result to [`set_counter`](Self::set_counter)
+            /// rewinds to the start of the block.
+            #[inline]
+            pub fn get_counter(&self) -> [$T; 4] {
+                let mut counter = self.rng.core.counter;
+                let index = self.rng.index();
+                if index >= BUF_BLOCKS * BLOCK_WORDS {
+                    return counter;
+                }
+                // The buffer holds the `BUF_BLOCKS` blocks preceding `counter`.
+                let behind = (BUF_BLOCKS - index / BLOCK_WORDS) as $T;
+                let (w, mut borrow) = counter[0].overflowing_sub(behind);
+                counter[0] = w;
+                for w in counter[1..].iter_mut() {
+                    if !borrow {
+                        break;
+                    }
+                    let (v, b) = w.overflowing_sub(1);
+                    *w = v;
+                    borrow = b;
+                }
+                counter
+            }
+
+            /// Set the counter, such that the next output word is the first
+            /// word of the block at `counter`.
+            ///
+            /// Counter words are given least significant word first. The
+            /// counter wraps around at the end of its range.
+            #[inline]
+            pub fn set_counter(&mut self, counter: [$T; 4]) {
+                self.rng.core.counter = counter;
+                self.rng.reset();
+            }
+        }
+
+        impl SeedableRng for $Rng {
+            type Seed = $Seed;
+
+            #[inline]
+            fn from_seed(seed: Self::Seed) -> Self {
+                $Rng {
+                    rng: $BlockRng::new($Core::from_seed(seed)),
+                }
+            }
+        }
+
+        impl RngCore for $Rng {
+            #[inline]
+            fn next_u32(&mut self) -> u32 {
+                self.rng.next_u32()
+            }
+
+            #[inline]
+            fn next_u64(&mut self) -> u64 {
+                self.rng.next_u64()
+            }
+
+            #[inline]
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                self.rng.fill_bytes(dest)
+            }
+
+            #[inline]
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+                self.rng.try_fill_bytes(dest)
+            }
+        }
+
+        impl From<$Core> for $Rng {
+            fn from(core: $Core) -> Self {
+                $Rng {
+                    rng: $BlockRng::new(core),
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,58 @@
+// Copyright 2018-2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Philox and Threefry counter-based random number generators.
+//!
+//! These generators were introduced by Salmon, Moraes, Dror and Shaw in
+//! [*Parallel Random Numbers: As Easy as 1, 2, 3*][paper] together with the
+//! [Random123] library. Each output block is a pure function of a key (the
+//! seed) and a counter (the block's position in the stream); generating a
+//! stream simply means applying this function to successive counter values.
+//! This makes it cheap to jump to any position of the stream, and to
+//! reproduce a particular block of output independently of how the preceding
+//! blocks were consumed, e.g. when porting a GPU simulation to the CPU.
+//!
+//! The following generators are implemented:
+//!
+//! - [`Philox4x32Rng`]: Philox4x32-10, with a 64-bit key and 128-bit counter,
+//!   producing four `u32` words per block.
+//! - [`Philox4x64Rng`]: Philox4x64-10, with a 128-bit key and 256-bit counter,
+//!   producing four `u64` words per block.
+//! - [`Threefry4x64Rng`]: Threefry4x64-20, with a 256-bit key and 256-bit
+//!   counter, producing four `u64` words per block.
+//!
+//! The output of these generators is identical to that of the Random123
+//! reference implementation for the same key and counter. The counter words
+//! are given least significant word first, and the counter is incremented by
+//! one for each block.
+//!
+//! These generators are not suitable for cryptographic uses.
+//!
+//! [Random123]: https://www.deshawresearch.com/resources_random123.html
+//! [paper]: https://www.thesalmons.org/john/random123/papers/random123sc11.pdf
+
+#![doc(
+    html_logo_url = "https://www.rust-lang.org/logos/rust-logo-128x128-blk.png",
+    html_favicon_url = "https://www.rust-lang.org/favicon.ico",
+    html_root_url = "https://rust-random.github.io/rand/"
+)]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![doc(test(attr(allow(unused_variables), deny(warnings))))]
+#![allow(clippy::unreadable_literal)]
+#![no_std]
+
+#[macro_use]
+mod common;
+mod philox;
+mod threefry;
+
+pub use rand_core;
+
+pub use crate::philox::{Philox4x32Core, Philox4x32Rng, Philox4x64Core, Philox4x64Rng};
+pub use crate::threefry::{Threefry4x64Core, Threefry4x64Rng};
@@ -0,0 +1,293 @@
+// Copyright 2018-2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Philox random number generators.
+
+use crate::common::{BLOCK_WORDS, BUF_BLOCKS};
+use core::fmt;
+use rand_core::block::{BlockRng, BlockRng64, BlockRngCore};
+use rand_core::{le, Error, RngCore, SeedableRng};
+
+// Multipliers and Weyl sequence constants (key schedule), as in Random123.
+const PHILOX_M4X32: [u32; 2] = [0xD2511F53, 0xCD9E8D57];
+const PHILOX_W32: [u32; 2] = [0x9E3779B9, 0xBB67AE85];
+const PHILOX_M4X64: [u64; 2] = [0xD2E7470EE14C6C93, 0xCA5A826395121157];
+const PHILOX_W64: [u64; 2] = [0x9E3779B97F4A7C15, 0xBB67AE8584CAA73B];
+
+// Number of rounds recommended by the authors.
+const PHILOX_ROUNDS: usize = 10;
+
+#[inline(always)]
+fn mulhilo32(a: u32, b: u32) -> (u32, u32) {
+    let product = u64::from(a) * u64::from(b);
+    ((product >> 32) as u32, product as u32)
+}
+
+#[inline(always)]
+fn mulhilo64(a: u64, b: u64) -> (u64, u64) {
+    let product = u128::from(a) * u128::from(b);
+    ((product >> 64) as u64, product as u64)
+}
+
+#[inline(always)]
+fn philox4x32_round(ctr: [u32; 4], key: [u32; 2]) -> [u32; 4] {
+    let (hi0, lo0) = mulhilo32(PHILOX_M4X32[0], ctr[0]);
+    let (hi1, lo1) = mulhilo32(PHILOX_M4X32[1], ctr[2]);
+    [hi1 ^ ctr[1] ^ key[0], lo1, hi0 ^ ctr[3] ^ key[1], lo0]
+}
+
+#[inline(always)]
+fn philox4x64_round(ctr: [u64; 4], key: [u64; 2]) -> [u64; 4] {
+    let (hi0, lo0) = mulhilo64(PHILOX_M4X64[0], ctr[0]);
+    let (hi1, lo1) = mulhilo64(PHILOX_M4X64[1], ctr[2]);
+    [hi1 ^ ctr[1] ^ key[0], lo1, hi0 ^ ctr[3] ^ key[1], lo0]
+}
+
+/// The Philox4x32-10 block function.
+#[inline]
+fn philox4x32_10(mut ctr: [u32; 4], mut key: [u32; 2]) -> [u32; 4] {
+    ctr = philox4x32_round(ctr, key);
+    for _ in 1..PHILOX_ROUNDS {
+        key[0] = key[0].wrapping_add(PHILOX_W32[0]);
+        key[1] = key[1].wrapping_add(PHILOX_W32[1]);
+        ctr = philox4x32_round(ctr, key);
+    }
+    ctr
+}
+
+/// The Philox4x64-10 block function.
+#[inline]
+fn philox4x64_10(mut ctr: [u64; 4], mut key: [u64; 2]) -> [u64; 4] {
+    ctr = philox4x64_round(ctr, key);
+    for _ in 1..PHILOX_ROUNDS {
+        key[0] = key[0].wrapping_add(PHILOX_W64[0]);
+        key[1] = key[1].wrapping_add(PHILOX_W64[1]);
+        ctr = philox4x64_round(ctr, key);
+    }
+    ctr
+}
+
+counter_rng_impl!(
+    /// A Philox4x32-10 random number generator.
+    ///
+    /// Philox is a counter-based generator by Salmon et al.[^1] whose rounds
+    /// combine a wide multiplication with a simple key schedule. This variant
+    /// has a 64-bit key and a 128-bit counter, uses 10 rounds and produces
+    /// four `u32` words per counter value. It is the Philox variant most
+    /// commonly used on GPUs.
+    ///
+    /// The key is set from the seed (as two little-endian `u32` words), or by
+    /// [`Philox4x32Rng::new`]. The counter starts at zero unless given
+    /// explicitly, and may be queried and set with `get_counter` and
+    /// `set_counter`, analogous to `ChaCha20Rng::get_word_pos` and
+    /// `set_word_pos`. Block `n` consists of the four words `philox(n, key)`,
+    /// output in order.
+    ///
+    /// This implementation uses an output buffer of sixteen `u32` words, and
+    /// uses [`BlockRng`] to implement the [`RngCore`] methods.
+    ///
+    /// [^1]: J. K. Salmon, M. A. Moraes, R. O. Dror and D. E. Shaw,
+    ///       [*Parallel Random Numbers: As Easy as 1, 2, 3*](
+    ///       https://www.thesalmons.org/john/random123/papers/random123sc11.pdf)
+    Philox4x32Rng, Philox4x32Core, "Philox4x32-10, low-level interface",
+    BlockRng, u32, key_words: 2, seed: [u8; 8],
+    le::read_u32_into, philox4x32_10
+);
+
+counter_rng_impl!(
+    /// A Philox4x64-10 random number generator.
+    ///
+    /// Philox is a counter-based generator by Salmon et al.[^1] whose rounds
+    /// combine a wide multiplication with a simple key schedule. This variant
+    /// has a 128-bit key and a 256-bit counter, uses 10 rounds and produces
+    /// four `u64` words per counter value. It is the fastest Philox variant on
+    /// 64-bit CPUs.
+    ///
+    /// The key is set from the seed (as two little-endian `u64` words), or by
+    /// [`Philox4x64Rng::new`]. The counter starts at zero unless given
+    /// explicitly, and may be queried and set with `get_counter` and
+    /// `set_counter`, analogous to `ChaCha20Rng::get_word_pos` and
+    /// `set_word_pos`. Block `n` consists of the four words `philox(n, key)`,
+    /// output in order.
+    ///
+    /// This implementation uses an output buffer of sixteen `u64` words, and
+    /// uses [`BlockRng64`] to implement the [`RngCore`] methods.
+    ///
+    /// [^1]: J. K. Salmon, M. A. Moraes, R. O. Dror and D. E. Shaw,
+    ///       [*Parallel Random Numbers: As Easy as 1, 2, 3*](
+    ///       https://www.thesalmons.org/john/random123/papers/random123sc11.pdf)
+    Philox4x64Rng, Philox4x64Core, "Philox4x64-10, low-level interface",
+    BlockRng64, u64, key_words: 2, seed: [u8; 16],
+    le::read_u64_into, philox4x64_10
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_philox4x32_true_values() {
+        // Known-answer tests from Random123's `kat_vectors`.
+        let cases = [
+            (
+                [0, 0, 0, 0],
+                [0, 0],
+                [0x6627e8d5, 0xe169c58d, 0xbc57ac4c, 0x9b00dbd8],
+            ),
+            (
+                [0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff],
+                [0xffffffff, 0xffffffff],
+                [0x408f276d, 0x41c83b0e, 0xa20bc7c6, 0x6d5451fd],
+            ),
+            (
+                [0x243f6a88, 0x85a308d3, 0x13198a2e, 0x03707344],
+                [0xa4093822, 0x299f31d0],
+                [0xd16cfe09, 0x94fdcceb, 0x5001e420, 0x24126ea1],
+            ),
+        ];
+        for &(ctr, key, expected) in cases.iter() {
+            let mut rng = Philox4x32Rng::new(key, ctr);
+            let mut results = [0u32; 4];
+            for r in results.iter_mut() {
+                *r = rng.next_u32();
+            }
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn test_philox4x64_true_values() {
+        // Known-answer tests from Random123's `kat_vectors`.
+        let cases = [
+            (
+                [0, 0, 0, 0],
+                [0, 0],
+                [
+                    0x16554d9eca36314c,
+                    0xdb20fe9d672d0fdc,
+                    0xd7e772cee186176b,
+                    0x7e68b68aec7ba23b,
+                ],
+            ),
+            (
+                [
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                ],
+                [0xffffffffffffffff, 0xffffffffffffffff],
+                [
+                    0x87b092c3013fe90b,
+                    0x438c3c67be8d0224,
+                    0x9cc7d7c69cd777b6,
+                    0xa09caebf594f0ba0,
+                ],
+            ),
+            (
+                [
+                    0x243f6a8885a308d3,
+                    0x13198a2e03707344,
+                    0xa4093822299f31d0,
+                    0x082efa98ec4e6c89,
+                ],
+                [0x452821e638d01377, 0xbe5466cf34e90c6c],
+                [
+                    0xa528f45403e61d95,
+                    0x38c72dbd566e9788,
+                    0xa5a1610e72fd18b5,
+                    0x57bd43b5e52b7fe6,
+                ],
+            ),
+        ];
+        for &(ctr, key, expected) in cases.iter() {
+            let mut rng = Philox4x64Rng::new(key, ctr);
+            let mut results = [0u64; 4];
+            for r in results.iter_mut() {
+                *r = rng.next_u64();
+            }
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn test_philox_construction() {
+        let seed = [1, 0, 0, 0, 2, 0, 0, 0];
+        let mut rng1 = Philox4x32Rng::from_seed(seed);
+        let mut rng2 = Philox4x32Rng::new([1, 2], [0, 0, 0, 0]);
+        let mut rng3 = Philox4x32Rng::from(Philox4x32Core::from_seed(seed));
+        for _ in 0..20 {
+            let x = rng1.next_u64();
+            assert_eq!(x, rng2.next_u64());
+            assert_eq!(x, rng3.next_u64());
+        }
+
+        let seed = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0];
+        let mut rng1 = Philox4x64Rng::from_seed(seed);
+        let mut rng2 = Philox4x64Rng::new([1, 2], [0, 0, 0, 0]);
+        for _ in 0..20 {
+            assert_eq!(rng1.next_u64(), rng2.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_philox4x32_counter() {
+        let mut rng = Philox4x32Rng::seed_from_u64(0);
+        assert_eq!(rng.get_counter(), [0, 0, 0, 0]);
+        let mut results = [0u32; 40];
+        for r in results.iter_mut() {
+            *r = rng.next_u32();
+        }
+        assert_eq!(rng.get_counter(), [10, 0, 0, 0]);
+        rng.next_u32();
+        assert_eq!(rng.get_counter(), [10, 0, 0, 0]);
+
+        // Seek back to a block in a previous buffer window, and to a block
+        // not aligned with the buffer.
+        for &block in [0usize, 3, 5, 9].iter() {
+            rng.set_counter([block as u32, 0, 0, 0]);
+            assert_eq!(rng.get_counter(), [block as u32, 0, 0, 0]);
+            for &expected in results[block * 4..].iter() {
+                assert_eq!(rng.next_u32(), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::legacy_numeric_constants)] // `u64::MAX` requires Rust 1.43
+    fn test_philox4x64_counter_wrap() {
+        let key = [0x1234, 0x5678];
+        let max = u64::max_value();
+        let mut rng = Philox4x64Rng::new(key, [max - 1, max, max, max]);
+        rng.next_u64();
+        assert_eq!(rng.get_counter(), [max - 1, max, max, max]);
+        for _ in 0..4 {
+            rng.next_u64();
+        }
+        assert_eq!(rng.get_counter(), [max, max, max, max]);
+        for _ in 0..4 {
+            rng.next_u64();
+        }
+        assert_eq!(rng.get_counter(), [0, 0, 0, 0]);
+        let x = rng.next_u64();
+
+        // The second word of block zero
+        let mut rng = Philox4x64Rng::new(key, [0, 0, 0, 0]);
+        rng.next_u64();
+        assert_eq!(rng.next_u64(), x);
+
+        // Borrow across words when the buffer window straddles a carry.
+        let mut rng = Philox4x64Rng::new(key, [max - 1, 0, 1, 0]);
+        rng.next_u64();
+        assert_eq!(rng.get_counter(), [max - 1, 0, 1, 0]);
+        for _ in 0..8 {
+            rng.next_u64();
+        }
+        assert_eq!(rng.get_counter(), [0, 1, 1, 0]);
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2018-2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Threefry random number generator.
+
+use crate::common::{BLOCK_WORDS, BUF_BLOCKS};
+use core::fmt;
+use rand_core::block::{BlockRng64, BlockRngCore};
+use rand_core::{le, Error, RngCore, SeedableRng};
+
+// Rotation constants of Threefish-256, as used by Threefry4x64.
+const ROTATIONS: [[u32; 2]; 8] = [
+    [14, 16],
+    [52, 57],
+    [23, 40],
+    [5, 37],
+    [25, 33],
+    [46, 12],
+    [58, 22],
+    [32, 32],
+];
+
+// Key schedule parity constant of Skein/Threefish.
+const SKEIN_KS_PARITY: u64 = 0x1BD11BDAA9FC1A22;
+
+// Number of rounds recommended by the authors.
+const THREEFRY_ROUNDS: usize = 20;
+
+/// The Threefry4x64-20 block function.
+#[inline]
+fn threefry4x64_20(ctr: [u64; 4], key: [u64; 4]) -> [u64; 4] {
+    let ks = [
+        key[0],
+        key[1],
+        key[2],
+        key[3],
+        SKEIN_KS_PARITY ^ key[0] ^ key[1] ^ key[2] ^ key[3],
+    ];
+    let mut x = [
+        ctr[0].wrapping_add(ks[0]),
+        ctr[1].wrapping_add(ks[1]),
+        ctr[2].wrapping_add(ks[2]),
+        ctr[3].wrapping_add(ks[3]),
+    ];
+
+    for round in 0..THREEFRY_ROUNDS {
+        let rot = ROTATIONS[round % 8];
+        // Even rounds mix words (0, 1) and (2, 3), odd rounds (0, 3) and (2, 1).
+        let (a, b) = if round % 2 == 0 { (1, 3) } else { (3, 1) };
+        x[0] = x[0].wrapping_add(x[a]);
+        x[a] = x[a].rotate_left(rot[0]) ^ x[0];
+        x[2] = x[2].wrapping_add(x[b]);
+        x[b] = x[b].rotate_left(rot[1]) ^ x[2];
+
+        // Inject the key schedule every four rounds.
+        if round % 4 == 3 {
+            let s = (round + 1) / 4;
+            for (i, w) in x.iter_mut().enumerate() {
+                *w = w.wrapping_add(ks[(s + i) % 5]);
+            }
+            x[3] = x[3].wrapping_add(s as u64);
+        }
+    }
+    x
+}
+
+counter_rng_impl!(
+    /// A Threefry4x64-20 random number generator.
+    ///
+    /// Threefry is a counter-based generator by Salmon et al.[^1], derived
+    /// from the Threefish block cipher[^2] by simplifying its key schedule.
+    /// It only uses additions, rotations and xors, and is therefore a good
+    /// choice on platforms without fast wide multiplication. This variant has
+    /// a 256-bit key and a 256-bit counter, uses 20 rounds and produces four
+    /// `u64` words per counter value.
+    ///
+    /// The key is set from the seed (as four little-endian `u64` words), or by
+    /// [`Threefry4x64Rng::new`]. The counter starts at zero unless given
+    /// explicitly, and may be queried and set with `get_counter` and
+    /// `set_counter`, analogous to `ChaCha20Rng::get_word_pos` and
+    /// `set_word_pos`. Block `n` consists of the four words
+    /// `threefry(n, key)`, output in order.
+    ///
+    /// This implementation uses an output buffer of sixteen `u64` words, and
+    /// uses [`BlockRng64`] to implement the [`RngCore`] methods.
+    ///
+    /// [^1]: J. K. Salmon, M. A. Moraes, R. O. Dror and D. E. Shaw,
+    ///       [*Parallel Random Numbers: As Easy as 1, 2, 3*](
+    ///       https://www.thesalmons.org/john/random123/papers/random123sc11.pdf)
+    ///
+    /// [^2]: N. Ferguson et al., [*The Skein Hash Function Family*](
+    ///       https://www.schneier.com/academic/skein/)
+    Threefry4x64Rng, Threefry4x64Core, "Threefry4x64-20, low-level interface",
+    BlockRng64, u64, key_words: 4, seed: [u8; 32],
+    le::read_u64_into, threefry4x64_20
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_threefry4x64_true_values() {
+        // Known-answer tests from Random123's `kat_vectors`.
+        let cases = [
+            (
+                [0, 0, 0, 0],
+                [0, 0, 0, 0],
+                [
+                    0x09218ebde6c85537,
+                    0x55941f5266d86105,
+                    0x4bd25e16282434dc,
+                    0xee29ec846bd2e40b,
+                ],
+            ),
+            (
+                [
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                ],
+                [
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                    0xffffffffffffffff,
+                ],
+                [
+                    0x29c24097942bba1b,
+                    0x0371bbfb0f6f4e11,
+                    0x3c231ffa33f83a1c,
+                    0xcd29113fde32d168,
+                ],
+            ),
+            (
+                [
+                    0x243f6a8885a308d3,
+                    0x13198a2e03707344,
+                    0xa4093822299f31d0,
+                    0x082efa98ec4e6c89,
+                ],
+                [
+                    0x452821e638d01377,
+                    0xbe5466cf34e90c6c,
+                    0xbe5466cf34e90c6c,
+                    0xc0ac29b7c97c50dd,
+                ],
+                [
+                    0xa7e8fde591651bd9,
+                    0xbaafd0c30138319b,
+                    0x84a5c1a729e685b9,
+                    0x901d406ccebc1ba4,
+                ],
+            ),
+        ];
+        for &(ctr, key, expected) in cases.iter() {
+            let mut rng = Threefry4x64Rng::new(key, ctr);
+            let mut results = [0u64; 4];
+            for r in results.iter_mut() {
+                *r = rng.next_u64();
+            }
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn test_threefry4x64_counter() {
+        let mut rng = Threefry4x64Rng::seed_from_u64(0);
+        let mut results = [0u64; 24];
+        for r in results.iter_mut() {
+            *r = rng.next_u64();
+        }
+        assert_eq!(rng.get_counter(), [6, 0, 0, 0]);
+        for &block in [1usize, 4].iter() {
+            rng.set_counter([block as u64, 0, 0, 0]);
+            for &expected in results[block * 4..].iter() {
+                assert_eq!(rng.next_u64(), expected);
+            }
+        }
+
+        // Consuming half of a `u64` counts as consuming the word.
+        rng.set_counter([3, 0, 0, 0]);
+        for _ in 0..6 {
+            rng.next_u32();
+        }
+        assert_eq!(rng.get_counter(), [3, 0, 0, 0]);
+        rng.next_u32();
+        assert_eq!(rng.get_counter(), [4, 0, 0, 0]);
+    }
+}
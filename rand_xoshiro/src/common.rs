@@ -0,0 +1,756 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rand_core::{RngCore, SeedableRng};
+
+/// Initialize a RNG from a `u64` seed using `SplitMix64`.
+macro_rules! from_splitmix {
+    ($seed:expr) => { {
+        let mut rng = crate::SplitMix64::seed_from_u64($seed);
+        Self::from_rng(&mut rng).unwrap()
+    } }
+}
+
+/// Apply the ** scrambler used by some RNGs from the xoshiro family.
+macro_rules! starstar_u64 {
+    ($x:expr) => {
+        $x.wrapping_mul(5).rotate_left(7).wrapping_mul(9)
+    }
+}
+
+/// Apply the ** scrambler used by some RNGs from the xoshiro family.
+macro_rules! starstar_u32 {
+    ($x:expr) => {
+        $x.wrapping_mul(0x9E3779BB).rotate_left(5).wrapping_mul(5)
+    }
+}
+
+/// Apply the ++ scrambler used by some RNGs from the xoshiro family.
+macro_rules! plusplus_u64 {
+    ($x:expr, $y:expr, $rot:expr) => {
+        $x.wrapping_add($y).rotate_left($rot).wrapping_add($x)
+    }
+}
+
+/// Apply the ++ scrambler used by some RNGs from the xoshiro family.
+macro_rules! plusplus_u32 {
+    ($x:expr, $y:expr) => {
+        $x.wrapping_add($y).rotate_left(7).wrapping_add($x)
+    }
+}
+
+/// Implement a jump function for an RNG from the xoshiro family.
+macro_rules! impl_jump {
+    (u32, $self:expr, [$j0:expr, $j1:expr]) => {
+        const JUMP: [u32; 2] = [$j0, $j1];
+        let mut s0 = 0;
+        let mut s1 = 0;
+        for j in &JUMP {
+            for b in 0..32 {
+                if (j & 1 << b) != 0 {
+                    s0 ^= $self.s0;
+                    s1 ^= $self.s1;
+                }
+                $self.next_u32();
+            }
+        }
+        $self.s0 = s0;
+        $self.s1 = s1;
+    };
+    (u64, $self:expr, [$j0:expr, $j1:expr]) => {
+        const JUMP: [u64; 2] = [$j0, $j1];
+        let mut s0 = 0;
+        let mut s1 = 0;
+        for j in &JUMP {
+            for b in 0..64 {
+                if (j & 1 << b) != 0 {
+                    s0 ^= $self.s0;
+                    s1 ^= $self.s1;
+                }
+                $self.next_u64();
+            }
+        }
+        $self.s0 = s0;
+        $self.s1 = s1;
+    };
+    (u32, $self:expr, [$j0:expr, $j1:expr, $j2:expr, $j3:expr]) => {
+        const JUMP: [u32; 4] = [$j0, $j1, $j2, $j3];
+        let mut s0 = 0;
+        let mut s1 = 0;
+        let mut s2 = 0;
+        let mut s3 = 0;
+        for j in &JUMP {
+            for b in 0..32 {
+                if (j & 1 << b) != 0 {
+                    s0 ^= $self.s[0];
+                    s1 ^= $self.s[1];
+                    s2 ^= $self.s[2];
+                    s3 ^= $self.s[3];
+                }
+                $self.next_u32();
+            }
+        }
+        $self.s[0] = s0;
+        $self.s[1] = s1;
+        $self.s[2] = s2;
+        $self.s[3] = s3;
+    };
+    (u64, $self:expr, [$j0:expr, $j1:expr, $j2:expr, $j3:expr]) => {
+        const JUMP: [u64; 4] = [$j0, $j1, $j2, $j3];
+        let mut s0 = 0;
+        let mut s1 = 0;
+        let mut s2 = 0;
+        let mut s3 = 0;
+        for j in &JUMP {
+            for b in 0..64 {
+                if (j & 1 << b) != 0 {
+                    s0 ^= $self.s[0];
+                    s1 ^= $self.s[1];
+                    s2 ^= $self.s[2];
+                    s3 ^= $self.s[3];
+                }
+                $self.next_u64();
+            }
+        }
+        $self.s[0] = s0;
+        $self.s[1] = s1;
+        $self.s[2] = s2;
+        $self.s[3] = s3;
+    };
+    (u64, $self:expr, [$j0:expr, $j1:expr, $j2:expr, $j3:expr,
+                       $j4:expr, $j5:expr, $j6:expr, $j7:expr]) => {
+        const JUMP: [u64; 8] = [$j0, $j1, $j2, $j3, $j4, $j5, $j6, $j7];
+        let mut s = [0; 8];
+        for j in &JUMP {
+            for b in 0..64 {
+                if (j & 1 << b) != 0 {
+                    s[0] ^= $self.s[0];
+                    s[1] ^= $self.s[1];
+                    s[2] ^= $self.s[2];
+                    s[3] ^= $self.s[3];
+                    s[4] ^= $self.s[4];
+                    s[5] ^= $self.s[5];
+                    s[6] ^= $self.s[6];
+                    s[7] ^= $self.s[7];
+                }
+                $self.next_u64();
+            }
+        }
+        $self.s = s;
+    };
+}
+
+/// Implement the xoroshiro iteration.
+macro_rules! impl_xoroshiro_u32 {
+    ($self:expr) => {
+        $self.s1 ^= $self.s0;
+        $self.s0 = $self.s0.rotate_left(26) ^ $self.s1 ^ ($self.s1 << 9);
+        $self.s1 = $self.s1.rotate_left(13);
+    }
+}
+
+/// Implement the xoroshiro iteration.
+macro_rules! impl_xoroshiro_u64 {
+    ($self:expr) => {
+        $self.s1 ^= $self.s0;
+        $self.s0 = $self.s0.rotate_left(24) ^ $self.s1 ^ ($self.s1 << 16);
+        $self.s1 = $self.s1.rotate_left(37);
+    }
+}
+
+/// Implement the xoroshiro iteration for the ++ scrambler.
+macro_rules! impl_xoroshiro_u64_plusplus {
+    ($self:expr) => {
+        $self.s1 ^= $self.s0;
+        $self.s0 = $self.s0.rotate_left(49) ^ $self.s1 ^ ($self.s1 << 21);
+        $self.s1 = $self.s1.rotate_left(28);
+    }
+}
+
+/// Implement the xoshiro iteration for `u32` output.
+macro_rules! impl_xoshiro_u32 {
+    ($self:expr) => {
+        let t = $self.s[1] << 9;
+
+        $self.s[2] ^= $self.s[0];
+        $self.s[3] ^= $self.s[1];
+        $self.s[1] ^= $self.s[2];
+        $self.s[0] ^= $self.s[3];
+
+        $self.s[2] ^= t;
+
+        $self.s[3] = $self.s[3].rotate_left(11);
+    }
+}
+
+/// Implement the xoshiro iteration for `u64` output.
+macro_rules! impl_xoshiro_u64 {
+    ($self:expr) => {
+        let t = $self.s[1] << 17;
+
+        $self.s[2] ^= $self.s[0];
+        $self.s[3] ^= $self.s[1];
+        $self.s[1] ^= $self.s[2];
+        $self.s[0] ^= $self.s[3];
+
+        $self.s[2] ^= t;
+
+        $self.s[3] = $self.s[3].rotate_left(45);
+    }
+}
+
+/// Implement the large-state xoshiro iteration.
+macro_rules! impl_xoshiro_large {
+    ($self:expr) => {
+        let t = $self.s[1] << 11;
+
+        $self.s[2] ^= $self.s[0];
+        $self.s[5] ^= $self.s[1];
+        $self.s[1] ^= $self.s[2];
+        $self.s[7] ^= $self.s[3];
+        $self.s[3] ^= $self.s[4];
+        $self.s[4] ^= $self.s[5];
+        $self.s[0] ^= $self.s[6];
+        $self.s[6] ^= $self.s[7];
+
+        $self.s[6] ^= t;
+
+        $self.s[7] = $self.s[7].rotate_left(21);
+    }
+}
+
+// Characteristic polynomials of the linear engines of the xoshiro family,
+// without the leading term, least significant word first. A jump polynomial
+// for `n` steps is `x^n` modulo the characteristic polynomial.
+pub(crate) const XOSHIRO128_CHARPOLY: [u64; 2] = [
+    0x1b489db6de18fc01, 0x00fc65a2006254b1,
+];
+pub(crate) const XOSHIRO256_CHARPOLY: [u64; 4] = [
+    0x9d116f2bb0f0f001, 0x0280002bcefd1a5e,
+    0x04b4edcf26259f85, 0x0003c03c3f3ecb19,
+];
+pub(crate) const XOSHIRO512_CHARPOLY: [u64; 8] = [
+    0xcf3cff0c00000001, 0x7fdc78d886f00c63, 0xf05e63fca6d7b781,
+    0x7a67058e7bbab6f0, 0xf11eef832e32518f, 0x51ba7c47edc758ad,
+    0x8f2d27268ce4b20b, 0x0000500055d8b77f,
+];
+pub(crate) const XOROSHIRO64_CHARPOLY: [u64; 1] = [0x053be9da6e2286c1];
+pub(crate) const XOROSHIRO128_CHARPOLY: [u64; 2] = [
+    0x095b8f76579aa001, 0x0008828e513b43d5,
+];
+pub(crate) const XOROSHIRO128PLUSPLUS_CHARPOLY: [u64; 2] = [
+    0x8dae70779760b081, 0x0031bcf2f855d6e5,
+];
+
+// Jump polynomials used to fork: those of `jump()`, and for xoroshiro64 (for
+// which none is published) that for 2^32 steps.
+pub(crate) const XOSHIRO128_JUMP: [u64; 2] = [
+    0xf542d2d38764000b, 0x77f2db5b6fa035c3,
+];
+pub(crate) const XOSHIRO256_JUMP: [u64; 4] = [
+    0x180ec6d33cfd0aba, 0xd5a61266f0c9392c,
+    0xa9582618e03fc9aa, 0x39abdc4529b1661c,
+];
+pub(crate) const XOSHIRO512_JUMP: [u64; 8] = [
+    0x33ed89b6e7a353f9, 0x760083d7955323be, 0x2837f2fbb5f22fae,
+    0x4b8c5674d309511c, 0xb11ac47a7ba28c25, 0xf1be7667092bcc1c,
+    0x53851efdb6df0aaf, 0x1ebbc8b23eaf25db,
+];
+pub(crate) const XOROSHIRO64_JUMP: [u64; 1] = [0x4cbf99bd77fcd1a0];
+pub(crate) const XOROSHIRO128_JUMP: [u64; 2] = [
+    0xdf900294d8f554a5, 0x170865df4b3201fc,
+];
+pub(crate) const XOROSHIRO128PLUSPLUS_JUMP: [u64; 2] = [
+    0x2bd7a6a6e99c2ddc, 0x0992ccaf6a6fca05,
+];
+
+// Largest characteristic polynomial, in words.
+const MAX_POLY_WORDS: usize = 8;
+
+/// Multiply `r` by `x` modulo `charpoly`.
+fn mul_x_mod(r: &mut [u64], charpoly: &[u64]) {
+    let n = r.len();
+    let carry = r[n - 1] >> 63;
+    for k in (1..n).rev() {
+        r[k] = r[k] << 1 | r[k - 1] >> 63;
+    }
+    r[0] <<= 1;
+    if carry != 0 {
+        for (x, p) in r.iter_mut().zip(charpoly) {
+            *x ^= p;
+        }
+    }
+}
+
+/// Compute `a * b` modulo `charpoly` over GF(2), writing the result to `out`.
+fn mul_mod(a: &[u64], b: &[u64], charpoly: &[u64], out: &mut [u64]) {
+    let n = charpoly.len();
+    // `mul[t]` is `t * b` and `reduce[t]` is `t * x^(64 * n)`, modulo
+    // `charpoly`, for all polynomials `t` of degree less than four.
+    let mut mul = [[0u64; MAX_POLY_WORDS]; 16];
+    let mut reduce = [[0u64; MAX_POLY_WORDS]; 16];
+    mul[1][..n].copy_from_slice(b);
+    reduce[1][..n].copy_from_slice(charpoly);
+    for t in 2..16 {
+        if t & (t - 1) == 0 {
+            mul[t] = mul[t / 2];
+            mul_x_mod(&mut mul[t][..n], charpoly);
+            reduce[t] = reduce[t / 2];
+            mul_x_mod(&mut reduce[t][..n], charpoly);
+        } else {
+            let high = 1 << (63 - (t as u64).leading_zeros());
+            for k in 0..n {
+                mul[t][k] = mul[high][k] ^ mul[t - high][k];
+                reduce[t][k] = reduce[high][k] ^ reduce[t - high][k];
+            }
+        }
+    }
+
+    let mut r = [0u64; MAX_POLY_WORDS];
+    let r = &mut r[..n];
+    // Horner's method, four coefficients of `a` at a time, from the highest
+    for i in (0..n * 16).rev() {
+        let top = (r[n - 1] >> 60) as usize;
+        for k in (1..n).rev() {
+            r[k] = r[k] << 4 | r[k - 1] >> 60;
+        }
+        r[0] <<= 4;
+        let t = (a[i / 16] >> (i % 16 * 4)) as usize & 15;
+        for k in 0..n {
+            r[k] ^= reduce[top][k] ^ mul[t][k];
+        }
+    }
+    out.copy_from_slice(r);
+}
+
+/// Compute `base^exp` modulo `charpoly`, writing the result to `out`.
+pub(crate) fn pow_mod(base: &[u64], exp: u128, charpoly: &[u64], out: &mut [u64]) {
+    let n = charpoly.len();
+    let mut pow = [0u64; MAX_POLY_WORDS];
+    let mut tmp = [0u64; MAX_POLY_WORDS];
+    let pow = &mut pow[..n];
+    let tmp = &mut tmp[..n];
+    pow.copy_from_slice(base);
+    for x in out.iter_mut() {
+        *x = 0;
+    }
+    out[0] = 1;
+    let mut exp = exp;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            mul_mod(out, pow, charpoly, tmp);
+            out.copy_from_slice(tmp);
+        }
+        mul_mod(pow, pow, charpoly, tmp);
+        pow.copy_from_slice(tmp);
+        exp >>= 1;
+    }
+}
+
+/// Compute the jump polynomial for `delta` steps, i.e. `x^delta` modulo
+/// `charpoly`, writing it to `jump` in the format of the published jump
+/// polynomials.
+#[cfg(not(target_os = "emscripten"))]
+pub(crate) fn jump_poly(charpoly: &[u64], delta: u128, jump: &mut [u64]) {
+    let mut x = [0u64; MAX_POLY_WORDS];
+    x[0] = 2;
+    pow_mod(&x[..charpoly.len()], delta, charpoly, jump);
+}
+
+/// Derive the number of jumps from a parent to its child with the given
+/// index.
+///
+/// This is injective in `index` for a given state and never zero, and
+/// depends on the state so that forking is not commutative: the children of
+/// a child differ from those of its siblings.
+pub(crate) fn fork_jumps<T: Copy + Into<u64>>(state: &[T], index: u64) -> u128 {
+    // Each step is a bijection of `m`
+    let mut m = index;
+    for &w in state {
+        m = crate::SplitMix64::seed_from_u64(m ^ w.into()).next_u64();
+    }
+    u128::from(m) + 1
+}
+
+/// Implement `SeekableRng` for an RNG from the xoshiro family, using the
+/// state as position and jump polynomials to advance.
+macro_rules! impl_seekable {
+    ($Rng:ident, $next:ident, [$T:ty; 2], s0 s1, $charpoly:ident) => {
+        impl $Rng {
+            /// Apply a jump polynomial computed at run time.
+            pub(crate) fn apply_jump_poly(&mut self, jump: &[u64]) {
+                let mut s0 = 0;
+                let mut s1 = 0;
+                for j in jump {
+                    for b in 0..64 {
+                        if (j >> b) & 1 != 0 {
+                            s0 ^= self.s0;
+                            s1 ^= self.s1;
+                        }
+                        self.$next();
+                    }
+                }
+                self.s0 = s0;
+                self.s1 = s1;
+            }
+        }
+
+        /// The position is the state of the generator: it can only be used to
+        /// return to a position recorded with `get_position`.
+        impl rand_core::SeekableRng for $Rng {
+            type Position = [$T; 2];
+
+            #[inline]
+            fn get_position(&self) -> [$T; 2] {
+                [self.s0, self.s1]
+            }
+
+            #[inline]
+            fn set_position(&mut self, pos: [$T; 2]) {
+                self.s0 = pos[0];
+                self.s1 = pos[1];
+            }
+
+            #[cfg(not(target_os = "emscripten"))]
+            fn advance(&mut self, delta: u128) {
+                let charpoly = crate::common::$charpoly;
+                let mut jump = charpoly;
+                crate::common::jump_poly(&charpoly, delta, &mut jump);
+                self.apply_jump_poly(&jump);
+            }
+        }
+    };
+    ($Rng:ident, $next:ident, [$T:ty; $N:expr], s, $charpoly:ident) => {
+        impl $Rng {
+            /// Apply a jump polynomial computed at run time.
+            pub(crate) fn apply_jump_poly(&mut self, jump: &[u64]) {
+                let mut s = [0; $N];
+                for j in jump {
+                    for b in 0..64 {
+                        if (j >> b) & 1 != 0 {
+                            for (x, y) in s.iter_mut().zip(self.s.iter()) {
+                                *x ^= y;
+                            }
+                        }
+                        self.$next();
+                    }
+                }
+                self.s = s;
+            }
+        }
+
+        /// The position is the state of the generator: it can only be used to
+        /// return to a position recorded with `get_position`.
+        impl rand_core::SeekableRng for $Rng {
+            type Position = [$T; $N];
+
+            #[inline]
+            fn get_position(&self) -> [$T; $N] {
+                self.s
+            }
+
+            #[inline]
+            fn set_position(&mut self, pos: [$T; $N]) {
+                self.s = pos;
+            }
+
+            #[cfg(not(target_os = "emscripten"))]
+            fn advance(&mut self, delta: u128) {
+                let charpoly = crate::common::$charpoly;
+                let mut jump = charpoly;
+                crate::common::jump_poly(&charpoly, delta, &mut jump);
+                self.apply_jump_poly(&jump);
+            }
+        }
+    };
+}
+
+/// Implement `SplittableRng` for an RNG from the xoshiro family, which must
+/// also implement `SeekableRng` with `impl_seekable!`.
+macro_rules! impl_splittable {
+    ($Rng:ident, $charpoly:ident, $jump:ident) => {
+        /// A child is its parent jumped ahead by a number of jumps derived
+        /// from the state of the parent and the index, i.e. it starts a
+        /// subsequence of the length of a jump at a pseudo-random number of
+        /// jumps from its parent. Siblings do not share a subsequence, except
+        /// for the xoroshiro64 generators, whose period is too short.
+        impl rand_core::SplittableRng for $Rng {
+            fn fork(&self, index: u64) -> Self {
+                let state = rand_core::SeekableRng::get_position(self);
+                let jumps = crate::common::fork_jumps(&state, index);
+                let charpoly = crate::common::$charpoly;
+                let mut jump = charpoly;
+                let base = crate::common::$jump;
+                crate::common::pow_mod(&base, jumps, &charpoly, &mut jump);
+                let mut child = self.clone();
+                child.apply_jump_poly(&jump);
+                child
+            }
+        }
+    };
+}
+
+/// Implement `SnapshotState` and `Snapshot` for a generator, based on its
+/// `SeekableRng` implementation.
+macro_rules! impl_snapshot {
+    ($Rng:ident, [$T:ty; $N:expr]) => {
+        /// The state consists of the state words, which must not all be zero.
+        impl rand_core::snapshot::SnapshotState for $Rng {
+            const STATE_LEN: usize = $N * core::mem::size_of::<$T>();
+
+            fn write_state(&self, w: &mut rand_core::snapshot::StateWriter) {
+                for x in rand_core::SeekableRng::get_position(self).iter() {
+                    w.write_bytes(&x.to_le_bytes());
+                }
+            }
+
+            fn read_state(
+                r: &mut rand_core::snapshot::StateReader,
+            ) -> Result<Self, rand_core::snapshot::SnapshotError> {
+                let mut s: [$T; $N] = [0; $N];
+                for x in s.iter_mut() {
+                    let mut buf = [0; core::mem::size_of::<$T>()];
+                    r.read_bytes(&mut buf);
+                    *x = <$T>::from_le_bytes(buf);
+                }
+                if s.iter().all(|&x| x == 0) {
+                    return Err(rand_core::snapshot::SnapshotError::InvalidState);
+                }
+                let mut rng = <$Rng as SeedableRng>::seed_from_u64(0);
+                rand_core::SeekableRng::set_position(&mut rng, s);
+                Ok(rng)
+            }
+        }
+
+        impl rand_core::snapshot::Snapshot for $Rng {
+            const ALGORITHM: &'static str = stringify!($Rng);
+            const VERSION: u16 = 1;
+        }
+    };
+}
+
+/// Map an all-zero seed to a different one.
+macro_rules! deal_with_zero_seed {
+    ($seed:expr, $Self:ident) => {
+        if $seed.iter().all(|&x| x == 0) {
+            return $Self::seed_from_u64(0);
+        }
+    }
+}
+
+/// 512-bit seed for a generator.
+///
+/// This wrapper is necessary, because some traits required for a seed are not
+/// implemented on large arrays.
+#[derive(Clone)]
+pub struct Seed512(pub [u8; 64]);
+
+impl Seed512 {
+    /// Return an iterator over the seed.
+    pub fn iter(&self) -> core::slice::Iter<'_, u8> {
+        self.0.iter()
+    }
+}
+
+impl core::fmt::Debug for Seed512 {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0[..].fmt(f)
+    }
+}
+
+impl Default for Seed512 {
+    fn default() -> Seed512 {
+        Seed512([0; 64])
+    }
+}
+
+impl AsMut<[u8]> for Seed512 {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::{RngCore, SeedableRng, SeekableRng};
+
+    // Compute `x^(2^k)` modulo `charpoly` by repeated squaring.
+    fn pow2k(charpoly: &[u64], k: usize) -> [u64; MAX_POLY_WORDS] {
+        let n = charpoly.len();
+        let mut r = [0; MAX_POLY_WORDS];
+        let mut tmp = [0; MAX_POLY_WORDS];
+        r[0] = 2;
+        for _ in 0..k {
+            mul_mod(&r[..n], &r[..n], charpoly, &mut tmp[..n]);
+            r = tmp;
+        }
+        r
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn test_jump_poly() {
+        // Jump polynomials published with the reference implementations
+        let cases: [(&[u64], u128, [u64; 2]); 5] = [
+            (&XOSHIRO128_CHARPOLY, 1 << 64, [0xf542d2d38764000b, 0x77f2db5b6fa035c3]),
+            (&XOSHIRO128_CHARPOLY, 1 << 96, [0x0b6f099fb523952e, 0x1c580662ccf5a0ef]),
+            (&XOROSHIRO128_CHARPOLY, 1 << 64, [0xdf900294d8f554a5, 0x170865df4b3201fc]),
+            (&XOROSHIRO128_CHARPOLY, 1 << 96, [0xd2a98b26625eee7b, 0xdddf9b1090aa7ac1]),
+            (&XOROSHIRO128PLUSPLUS_CHARPOLY, 1 << 96, [0x360fd5f2cf8d5d99, 0x9c6e6877736c46e3]),
+        ];
+        for &(charpoly, delta, expected) in cases.iter() {
+            let mut jump = [0; 2];
+            jump_poly(charpoly, delta, &mut jump);
+            assert_eq!(jump, expected);
+        }
+
+        // Jumps beyond the range of `delta`
+        let jump = pow2k(&XOSHIRO256_CHARPOLY, 128);
+        assert_eq!(jump[..4], [
+            0x180ec6d33cfd0aba, 0xd5a61266f0c9392c,
+            0xa9582618e03fc9aa, 0x39abdc4529b1661c,
+        ]);
+        let jump = pow2k(&XOSHIRO512_CHARPOLY, 256);
+        assert_eq!(jump, [
+            0x33ed89b6e7a353f9, 0x760083d7955323be, 0x2837f2fbb5f22fae,
+            0x4b8c5674d309511c, 0xb11ac47a7ba28c25, 0xf1be7667092bcc1c,
+            0x53851efdb6df0aaf, 0x1ebbc8b23eaf25db,
+        ]);
+
+        let mut jump = [0; 4];
+        jump_poly(&XOSHIRO256_CHARPOLY, 1 << 127, &mut jump);
+        let mut squared = [0; 4];
+        mul_mod(&jump, &jump, &XOSHIRO256_CHARPOLY, &mut squared);
+        assert_eq!(squared, pow2k(&XOSHIRO256_CHARPOLY, 128)[..4]);
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    macro_rules! test_advance {
+        ($Rng:ty, $next:ident) => {{
+            for &delta in [0u128, 1, 2, 63, 64, 65, 1000].iter() {
+                let mut rng1 = <$Rng>::seed_from_u64(delta as u64);
+                let mut rng2 = rng1.clone();
+                for _ in 0..delta {
+                    rng1.$next();
+                }
+                rng2.advance(delta);
+                assert_eq!(rng1, rng2);
+            }
+
+            let mut rng = <$Rng>::seed_from_u64(0);
+            let pos = rng.get_position();
+            let x = rng.$next();
+            rng.advance(12345);
+            rng.set_position(pos);
+            assert_eq!(rng.$next(), x);
+        }};
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn test_advance() {
+        test_advance!(crate::Xoshiro128Plus, next_u32);
+        test_advance!(crate::Xoshiro128PlusPlus, next_u32);
+        test_advance!(crate::Xoshiro128StarStar, next_u32);
+        test_advance!(crate::Xoshiro256Plus, next_u64);
+        test_advance!(crate::Xoshiro256PlusPlus, next_u64);
+        test_advance!(crate::Xoshiro256StarStar, next_u64);
+        test_advance!(crate::Xoshiro512Plus, next_u64);
+        test_advance!(crate::Xoshiro512PlusPlus, next_u64);
+        test_advance!(crate::Xoshiro512StarStar, next_u64);
+        test_advance!(crate::Xoroshiro128Plus, next_u64);
+        test_advance!(crate::Xoroshiro128PlusPlus, next_u64);
+        test_advance!(crate::Xoroshiro128StarStar, next_u64);
+        test_advance!(crate::Xoroshiro64Star, next_u32);
+        test_advance!(crate::Xoroshiro64StarStar, next_u32);
+        test_advance!(crate::SplitMix64, next_u64);
+    }
+
+    macro_rules! test_snapshot {
+        ($Rng:ty, $next:ident) => {{
+            let mut rng = <$Rng>::seed_from_u64(0);
+            rng.$next();
+            let mut buf = [0; 128];
+            let len = rng.export_snapshot(&mut buf).unwrap();
+            assert_eq!(len, <$Rng>::snapshot_len());
+            let mut restored = <$Rng>::from_snapshot(&buf[..len]).unwrap();
+            assert_eq!(restored, rng);
+            assert_eq!(restored.$next(), rng.$next());
+
+            // The all-zero state is invalid
+            let state_len = <$Rng as SnapshotState>::STATE_LEN;
+            for x in buf[len - state_len..len].iter_mut() {
+                *x = 0;
+            }
+            let result = <$Rng>::from_snapshot(&buf[..len]);
+            assert_eq!(result.unwrap_err(), SnapshotError::InvalidState);
+        }};
+    }
+
+    #[test]
+    fn test_snapshot() {
+        use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState};
+
+        test_snapshot!(crate::Xoshiro128Plus, next_u32);
+        test_snapshot!(crate::Xoshiro128PlusPlus, next_u32);
+        test_snapshot!(crate::Xoshiro128StarStar, next_u32);
+        test_snapshot!(crate::Xoshiro256Plus, next_u64);
+        test_snapshot!(crate::Xoshiro256PlusPlus, next_u64);
+        test_snapshot!(crate::Xoshiro256StarStar, next_u64);
+        test_snapshot!(crate::Xoshiro512Plus, next_u64);
+        test_snapshot!(crate::Xoshiro512PlusPlus, next_u64);
+        test_snapshot!(crate::Xoshiro512StarStar, next_u64);
+        test_snapshot!(crate::Xoroshiro128Plus, next_u64);
+        test_snapshot!(crate::Xoroshiro128PlusPlus, next_u64);
+        test_snapshot!(crate::Xoroshiro128StarStar, next_u64);
+        test_snapshot!(crate::Xoroshiro64Star, next_u32);
+        test_snapshot!(crate::Xoroshiro64StarStar, next_u32);
+
+        // Generators with the same state layout are different algorithms
+        let rng = crate::Xoshiro256PlusPlus::seed_from_u64(0);
+        let mut buf = [0; 128];
+        let len = rng.export_snapshot(&mut buf).unwrap();
+        let result = crate::Xoshiro256StarStar::from_snapshot(&buf[..len]);
+        assert_eq!(result.unwrap_err(), SnapshotError::AlgorithmMismatch);
+    }
+
+    #[test]
+    fn test_fork() {
+        use crate::Xoshiro256PlusPlus;
+        use rand_core::SplittableRng;
+
+        // A power of the jump polynomial is equivalent to repeated jumps
+        let mut rng1 = Xoshiro256PlusPlus::seed_from_u64(0);
+        let mut rng2 = rng1.clone();
+        let mut jump = [0; 4];
+        pow_mod(&XOSHIRO256_JUMP, 3, &XOSHIRO256_CHARPOLY, &mut jump);
+        rng1.apply_jump_poly(&jump);
+        for _ in 0..3 {
+            rng2.jump();
+        }
+        assert_eq!(rng1, rng2);
+
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(1);
+        let parent = rng.clone();
+        let a = rng.fork(0);
+        assert_eq!(rng, parent);
+        assert_eq!(a, rng.fork(0));
+        let b = rng.fork(1);
+        assert_ne!(a, b);
+        assert_ne!(a, rng);
+
+        // Forking is not commutative.
+        assert_ne!(a.fork(1), b.fork(0));
+
+        let child = rng.split();
+        assert_eq!(child, a);
+        assert_eq!(rng, b);
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature="serde1")] use serde::{Serialize, Deserialize};
+use rand_core::le::read_u64_into;
+use rand_core::impls::fill_bytes_via_next;
+use rand_core::{RngCore, SeedableRng};
+
+/// A xoroshiro128++ random number generator.
+///
+/// The xoroshiro128++ algorithm is not suitable for cryptographic purposes, but
+/// is very fast and has excellent statistical properties.
+///
+/// The algorithm used here is translated from [the `xoroshiro128plusplus.c`
+/// reference source code](http://xoshiro.di.unimi.it/xoroshiro128plusplus.c) by
+/// David Blackman and Sebastiano Vigna.
+#[allow(missing_copy_implementations)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="serde1", derive(Serialize, Deserialize))]
+pub struct Xoroshiro128PlusPlus {
+    s0: u64,
+    s1: u64,
+}
+
+impl Xoroshiro128PlusPlus {
+    /// Jump forward, equivalently to 2^64 calls to `next_u64()`.
+    pub fn jump(&mut self) {
+        impl_jump!(u64, self, [0x2bd7a6a6e99c2ddc, 0x0992ccaf6a6fca05]);
+    }
+
+    /// Jump forward, equivalently to 2^96 calls to `next_u64()`.
+    pub fn long_jump(&mut self) {
+        impl_jump!(u64, self, [0x360fd5f2cf8d5d99, 0x9c6e6877736c46e3]);
+    }
+}
+
+impl RngCore for Xoroshiro128PlusPlus {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let r = plusplus_u64!(self.s0, self.s1, 17);
+        impl_xoroshiro_u64_plusplus!(self);
+        r
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next(self, dest);
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Xoroshiro128PlusPlus {
+    type Seed = [u8; 16];
+
+    /// Create a new `Xoroshiro128PlusPlus`.  If `seed` is entirely 0, it will be
+    /// mapped to a different seed.
+    fn from_seed(seed: [u8; 16]) -> Xoroshiro128PlusPlus {
+        deal_with_zero_seed!(seed, Self);
+        let mut s = [0; 2];
+        read_u64_into(&seed, &mut s);
+
+        Xoroshiro128PlusPlus {
+            s0: s[0],
+            s1: s[1],
+        }
+    }
+
+    /// Seed a `Xoroshiro128PlusPlus` from a `u64` using `SplitMix64`.
+    fn seed_from_u64(seed: u64) -> Xoroshiro128PlusPlus {
+        from_splitmix!(seed)
+    }
+}
+
+impl_seekable!(Xoroshiro128PlusPlus, next_u64, [u64; 2], s0 s1, XOROSHIRO128PLUSPLUS_CHARPOLY);
+impl_splittable!(Xoroshiro128PlusPlus, XOROSHIRO128PLUSPLUS_CHARPOLY, XOROSHIRO128PLUSPLUS_JUMP);
+impl_snapshot!(Xoroshiro128PlusPlus, [u64; 2]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference() {
+        let mut rng = Xoroshiro128PlusPlus::from_seed(
+            [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]);
+        // These values were produced with the reference implementation:
+        // http://xoshiro.di.unimi.it/xoshiro128plusplus.c
+        let expected = [
+            393217, 669327710093319, 1732421326133921491, 11394790081659126983,
+            9555452776773192676, 3586421180005889563, 1691397964866707553,
+            10735626796753111697, 15216282715349408991, 14247243556711267923,
+        ];
+        for &e in &expected {
+            assert_eq!(rng.next_u64(), e);
+        }
+    }
+
+    #[test]
+    fn reference_jump() {
+        let seed = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0];
+        // These values were produced with the `jump` and `long_jump`
+        // functions of the reference implementation:
+        // http://xoshiro.di.unimi.it/xoroshiro128plusplus.c
+        let mut rng = Xoroshiro128PlusPlus::from_seed(seed);
+        rng.jump();
+        let expected = [
+            6995778298204176446, 17606341508358386873, 18268233585225622342,
+            1634122034616564957, 1545814713924780798,
+        ];
+        for &e in &expected {
+            assert_eq!(rng.next_u64(), e);
+        }
+
+        let mut rng = Xoroshiro128PlusPlus::from_seed(seed);
+        rng.long_jump();
+        let expected = [
+            13476878559037916028, 4599739792799904096, 9592342027630475676,
+            16396948912373680941, 3683122285862085747,
+        ];
+        for &e in &expected {
+            assert_eq!(rng.next_u64(), e);
+        }
+    }
+}
@@ -208,4 +208,22 @@ mod test {
             true, false, false, true, false, false, true, true, true, true
         ]);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption() {
+        use crate::test::consumption;
+
+        // One `u64` per sample
+        let distr = Bernoulli::new(0.3).unwrap();
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            distr.sample(rng);
+        }), (0, 100, 0));
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            rng.gen_bool(0.3);
+        }), (0, 100, 0));
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            rng.gen_ratio(2, 3);
+        }), (0, 100, 0));
+    }
 }
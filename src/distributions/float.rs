@@ -309,4 +309,23 @@ mod tests {
             ]);
         }
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption() {
+        use crate::test::consumption;
+
+        // One `u32` per `f32` and one `u64` per `f64`
+        fn test<T, D: Distribution<T>>(distr: D, expected: (u64, u64, u64)) {
+            assert_eq!(consumption(415, |rng| for _ in 0..100 {
+                distr.sample(rng);
+            }), expected);
+        }
+        test::<f32, _>(Standard, (100, 0, 0));
+        test::<f64, _>(Standard, (0, 100, 0));
+        test::<f32, _>(Open01, (100, 0, 0));
+        test::<f64, _>(Open01, (0, 100, 0));
+        test::<f32, _>(OpenClosed01, (100, 0, 0));
+        test::<f64, _>(OpenClosed01, (0, 100, 0));
+    }
 }
@@ -276,4 +276,24 @@ mod tests {
             ]);
         }
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption() {
+        use crate::test::consumption;
+
+        // Types of at most 32 bits use one `u32`, larger ones `u64`s
+        fn test<T>(expected: (u64, u64, u64))
+        where Standard: Distribution<T> {
+            assert_eq!(consumption(415, |rng| for _ in 0..100 {
+                Distribution::<T>::sample(&Standard, rng);
+            }), expected);
+        }
+        test::<u8>((100, 0, 0));
+        test::<i16>((100, 0, 0));
+        test::<u32>((100, 0, 0));
+        test::<i64>((0, 100, 0));
+        test::<u128>((0, 200, 0));
+        test::<NonZeroU32>((100, 0, 0));
+    }
 }
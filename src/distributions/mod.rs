@@ -94,15 +94,18 @@
 //! [`statrs`]: https://crates.io/crates/statrs
 
 use crate::Rng;
+#[cfg(feature = "rayon")] use crate::SplittableRng;
 use core::iter;
 
 pub use self::bernoulli::{Bernoulli, BernoulliError};
 pub use self::float::{Open01, OpenClosed01};
 pub use self::other::Alphanumeric;
-#[doc(inline)] pub use self::uniform::Uniform;
+#[doc(inline)] pub use self::uniform::{Uniform, UniformError};
 
 #[cfg(feature = "alloc")]
 pub use self::weighted_index::{WeightedError, WeightedIndex};
+#[cfg(feature = "rayon")]
+pub use self::par::ParDistIter;
 
 mod bernoulli;
 pub mod uniform;
@@ -123,6 +126,7 @@ pub mod hidden_export {
 }
 mod integer;
 mod other;
+#[cfg(feature = "rayon")] mod par;
 mod utils;
 
 /// Types (distributions) that can be used to create a random instance of `T`.
@@ -192,6 +196,68 @@ pub trait Distribution<T> {
             phantom: ::core::marker::PhantomData,
         }
     }
+
+    /// Create a parallel iterator that generates `len` random values of `T`,
+    /// using generators forked from `rng` as the source of randomness.
+    ///
+    /// The values are generated in chunks of a fixed length (currently
+    /// 2<sup>14</sup>); chunk `k` is sampled sequentially from `rng.fork(k)`.
+    /// The result is thus reproducible for a seeded `rng`, independently of
+    /// the number of threads used. Forking should be cheap compared to
+    /// sampling a chunk, which is the case for `StdRng` and the PCG
+    /// generators.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::SeedableRng;
+    /// use rand::distributions::{Distribution, Standard};
+    /// use rand::rngs::StdRng;
+    /// use rayon::iter::ParallelIterator;
+    ///
+    /// let rng = StdRng::seed_from_u64(42);
+    /// let v: Vec<f64> = Standard.par_sample_iter(rng, 1_000_000).collect();
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "rayon")))]
+    fn par_sample_iter<R>(self, rng: R, len: usize) -> ParDistIter<Self, R, T>
+    where
+        R: SplittableRng,
+        Self: Sized,
+    {
+        ParDistIter::new(self, rng, len)
+    }
+
+    /// Fill `dest` with random values of `T`, in parallel.
+    ///
+    /// This [splits](SplittableRng::split) `rng` and fills `dest` with the
+    /// values [`par_sample_iter`] would generate from the child, so that
+    /// `rng` is advanced and the result is reproducible for a seeded `rng`,
+    /// independently of the number of threads used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::SeedableRng;
+    /// use rand::distributions::{Distribution, Uniform};
+    /// use rand::rngs::StdRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let mut v = vec![0u32; 1_000_000];
+    /// Uniform::new(0, 100).par_fill(&mut rng, &mut v);
+    /// ```
+    ///
+    /// [`par_sample_iter`]: Distribution::par_sample_iter
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "rayon")))]
+    fn par_fill<R>(&self, rng: &mut R, dest: &mut [T])
+    where
+        R: SplittableRng + Sync,
+        Self: Sync,
+        T: Send,
+    {
+        par::fill(self, &rng.split(), dest)
+    }
 }
 
 impl<'a, T, D: Distribution<T>> Distribution<T> for &'a D {
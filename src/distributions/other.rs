@@ -310,4 +310,22 @@ mod tests {
             [149, 207, 29],
         ]);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption() {
+        use crate::test::consumption;
+
+        fn test<T, D: Distribution<T>>(distr: D, expected: (u64, u64, u64)) {
+            assert_eq!(consumption(415, |rng| for _ in 0..100 {
+                distr.sample(rng);
+            }), expected);
+        }
+        test::<bool, _>(Standard, (100, 0, 0));
+        test::<char, _>(Standard, (100, 0, 0));
+        test::<u8, _>(Alphanumeric, (100, 0, 0));
+        // Arrays and tuples sample each element
+        test::<[u8; 16], _>(Standard, (1600, 0, 0));
+        test::<(u8, u64), _>(Standard, (100, 100, 0));
+    }
 }
@@ -0,0 +1,251 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parallel sampling with `rayon`.
+//!
+//! The output is split into chunks of `CHUNK_LEN` values; chunk `k` is
+//! sampled sequentially from `rng.fork(k)`. Since the chunking does not depend
+//! on how `rayon` schedules the work, the result only depends on the
+//! distribution, the generator and the length.
+
+use super::Distribution;
+use crate::SplittableRng;
+use core::marker::PhantomData;
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+
+/// Number of values sampled from each forked generator.
+///
+/// NB. changing this changes the output of `par_sample_iter` and `par_fill`.
+const CHUNK_LEN: usize = 1 << 14;
+
+/// A parallel iterator that generates `len` random values of `T` with
+/// distribution `D`, using generators forked from `R`.
+///
+/// This `struct` is created by the [`par_sample_iter`] method on
+/// [`Distribution`]. See its documentation for more.
+///
+/// [`par_sample_iter`]: Distribution::par_sample_iter
+#[derive(Debug)]
+pub struct ParDistIter<D, R, T> {
+    distr: D,
+    rng: R,
+    len: usize,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<D, R, T> ParDistIter<D, R, T> {
+    pub(crate) fn new(distr: D, rng: R, len: usize) -> Self {
+        ParDistIter {
+            distr,
+            rng,
+            len,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<D, R, T> ParallelIterator for ParDistIter<D, R, T>
+where
+    D: Distribution<T> + Send + Sync,
+    R: SplittableRng + Send + Sync,
+    T: Send,
+{
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where C: UnindexedConsumer<T> {
+        let producer = ChunkProducer {
+            distr: &self.distr,
+            rng: &self.rng,
+            start: 0,
+            end: self.len,
+            phantom: PhantomData,
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+/// Produces the values with indices `start..end`, where `start` is a multiple
+/// of `CHUNK_LEN`. Splits are only made on chunk boundaries.
+struct ChunkProducer<'a, D, R, T> {
+    distr: &'a D,
+    rng: &'a R,
+    start: usize,
+    end: usize,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<'a, D, R, T> UnindexedProducer for ChunkProducer<'a, D, R, T>
+where
+    D: Distribution<T> + Sync,
+    R: SplittableRng + Sync,
+    T: Send,
+{
+    type Item = T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        let len = self.end - self.start;
+        if len <= CHUNK_LEN {
+            return (self, None);
+        }
+        let chunks = 1 + (len - 1) / CHUNK_LEN;
+        let mid = self.start + chunks / 2 * CHUNK_LEN;
+        let right = ChunkProducer {
+            distr: self.distr,
+            rng: self.rng,
+            start: mid,
+            end: self.end,
+            phantom: PhantomData,
+        };
+        let left = ChunkProducer { end: mid, ..self };
+        (left, Some(right))
+    }
+
+    fn fold_with<F>(self, mut folder: F) -> F
+    where F: Folder<T> {
+        let mut start = self.start;
+        while start < self.end && !folder.full() {
+            let end = self.end.min(start + CHUNK_LEN);
+            let mut rng = self.rng.fork((start / CHUNK_LEN) as u64);
+            let distr = self.distr;
+            folder = folder.consume_iter((start..end).map(|_| distr.sample(&mut rng)));
+            start = end;
+        }
+        folder
+    }
+}
+
+/// Fill `dest` with the values `ParDistIter` would produce.
+pub(crate) fn fill<D, R, T>(distr: &D, rng: &R, dest: &mut [T])
+where
+    D: Distribution<T> + Sync + ?Sized,
+    R: SplittableRng + Sync,
+    T: Send,
+{
+    dest.par_chunks_mut(CHUNK_LEN)
+        .enumerate()
+        .for_each(|(k, chunk)| {
+            let mut rng = rng.fork(k as u64);
+            for x in chunk {
+                *x = distr.sample(&mut rng);
+            }
+        });
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use crate::distributions::{Standard, Uniform};
+    use crate::rngs::mock::StepRng;
+    use crate::Rng;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use rand_core::{Error, RngCore};
+
+    // A splittable generator that is cheap to fork
+    #[derive(Clone, Debug, PartialEq)]
+    struct ForkRng(StepRng);
+
+    impl RngCore for ForkRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.0.fill_bytes(dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+            self.0.try_fill_bytes(dest)
+        }
+    }
+
+    impl SplittableRng for ForkRng {
+        fn fork(&self, index: u64) -> Self {
+            let mut rng = self.clone();
+            let initial = ((index + 1) << 40) ^ rng.next_u64();
+            ForkRng(StepRng::new(initial, 1))
+        }
+    }
+
+    fn with_threads<F: FnOnce() -> Vec<u64> + Send>(threads: usize, f: F) -> Vec<u64> {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(f)
+    }
+
+    #[test]
+    fn test_par_sample_iter() {
+        let rng = ForkRng(StepRng::new(7, 3));
+        let len = 3 * CHUNK_LEN + 5;
+        let v: Vec<u64> = Standard.par_sample_iter(rng.clone(), len).collect();
+        assert_eq!(v.len(), len);
+
+        // Chunk `k` is sampled sequentially from `fork(k)`
+        for (k, chunk) in v.chunks(CHUNK_LEN).enumerate() {
+            let expected: Vec<u64> = rng
+                .fork(k as u64)
+                .sample_iter(Standard)
+                .take(chunk.len())
+                .collect();
+            assert_eq!(chunk, &expected[..]);
+        }
+
+        // The result does not depend on the number of threads
+        for &threads in [1, 2, 5].iter() {
+            let w = with_threads(threads, || {
+                Standard.par_sample_iter(rng.clone(), len).collect()
+            });
+            assert_eq!(v, w);
+        }
+
+        // Early termination
+        let first = Standard
+            .par_sample_iter(rng.clone(), len)
+            .find_first(|&x: &u64| x >> 40 == 2);
+        assert_eq!(first, Some(v[CHUNK_LEN]));
+
+        let v: Vec<u64> = Standard.par_sample_iter(rng, 0).collect();
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_par_fill() {
+        let distr = Uniform::new(0u64, 1000);
+        let len = 2 * CHUNK_LEN + 1;
+        let mut rng = ForkRng(StepRng::new(0, 1));
+        let parent = rng.clone();
+
+        let mut v = vec![0; len];
+        distr.par_fill(&mut rng, &mut v);
+        // `par_fill` uses the first half of a split, and advances `rng`
+        let mut expected = parent.clone();
+        let child = expected.split();
+        assert_eq!(rng, expected);
+        let w: Vec<u64> = distr.par_sample_iter(child, len).collect();
+        assert_eq!(v, w);
+
+        for &threads in [1, 3].iter() {
+            let w = with_threads(threads, || {
+                let mut rng = parent.clone();
+                let mut v = vec![0; len];
+                distr.par_fill(&mut rng, &mut v);
+                v
+            });
+            assert_eq!(v, w);
+        }
+    }
+}
@@ -105,6 +105,7 @@
 
 #[cfg(not(feature = "std"))] use core::time::Duration;
 #[cfg(feature = "std")] use std::time::Duration;
+use core::fmt;
 use core::ops::{Range, RangeInclusive};
 
 use crate::distributions::float::IntoFloat;
@@ -121,12 +122,36 @@ use crate::distributions::utils::Float;
 #[cfg(feature = "serde1")]
 use serde::{Serialize, Deserialize};
 
+/// Error type returned from [`Uniform::try_new`] and related fallible
+/// constructors and samplers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UniformError {
+    /// `low > high`, or `low == high` for an exclusive range.
+    EmptyRange,
+    /// `low` or `high` is not finite (only for floating-point types).
+    NonFinite,
+}
+
+impl fmt::Display for UniformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UniformError::EmptyRange => "low > high (or low == high if exclusive) in uniform distribution",
+            UniformError::NonFinite => "non-finite range in uniform distribution",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for UniformError {}
+
 /// Sample values uniformly between two bounds.
 ///
 /// [`Uniform::new`] and [`Uniform::new_inclusive`] construct a uniform
 /// distribution sampling from the given range; these functions may do extra
 /// work up front to make sampling of multiple values faster. If only one sample
 /// from the range is required, [`Rng::gen_range`] can be more efficient.
+/// [`Uniform::try_new`] and [`Uniform::try_new_inclusive`] return an error
+/// instead of panicking on invalid bounds.
 ///
 /// When sampling from a constant range, many calculations can happen at
 /// compile-time and all methods should be fast; for floating-point ranges and
@@ -194,6 +219,40 @@ impl<X: SampleUniform> Uniform<X> {
     {
         Uniform(X::Sampler::new_inclusive(low, high))
     }
+
+    /// Create a new `Uniform` instance which samples uniformly from the half
+    /// open range `[low, high)` (excluding `high`).
+    ///
+    /// Returns [`UniformError::EmptyRange`] if `low >= high`, and
+    /// [`UniformError::NonFinite`] if `low` or `high` is not finite.
+    ///
+    /// ```
+    /// use rand::distributions::uniform::{Uniform, UniformError};
+    ///
+    /// assert!(Uniform::try_new(0, 10).is_ok());
+    /// assert_eq!(Uniform::try_new(5u32, 5).unwrap_err(), UniformError::EmptyRange);
+    /// assert_eq!(Uniform::try_new(0.0, std::f64::INFINITY).unwrap_err(), UniformError::NonFinite);
+    /// ```
+    pub fn try_new<B1, B2>(low: B1, high: B2) -> Result<Uniform<X>, UniformError>
+    where
+        B1: SampleBorrow<X> + Sized,
+        B2: SampleBorrow<X> + Sized,
+    {
+        X::Sampler::try_new(low, high).map(Uniform)
+    }
+
+    /// Create a new `Uniform` instance which samples uniformly from the closed
+    /// range `[low, high]` (inclusive).
+    ///
+    /// Returns [`UniformError::EmptyRange`] if `low > high`, and
+    /// [`UniformError::NonFinite`] if `low` or `high` is not finite.
+    pub fn try_new_inclusive<B1, B2>(low: B1, high: B2) -> Result<Uniform<X>, UniformError>
+    where
+        B1: SampleBorrow<X> + Sized,
+        B2: SampleBorrow<X> + Sized,
+    {
+        X::Sampler::try_new_inclusive(low, high).map(Uniform)
+    }
 }
 
 impl<X: SampleUniform> Distribution<X> for Uniform<X> {
@@ -222,8 +281,17 @@ pub trait SampleUniform: Sized {
 /// Implementation of [`sample_single`] is optional, and is only useful when
 /// the implementation can be faster than `Self::new(low, high).sample(rng)`.
 ///
+/// The fallible methods [`try_new`], [`try_new_inclusive`],
+/// [`try_sample_single`] and [`try_sample_single_inclusive`] are implemented
+/// via their panicking counterparts by default; implementations should
+/// override `try_new` and `try_new_inclusive` to validate the bounds.
+///
 /// [module documentation]: crate::distributions::uniform
 /// [`sample_single`]: UniformSampler::sample_single
+/// [`try_new`]: UniformSampler::try_new
+/// [`try_new_inclusive`]: UniformSampler::try_new_inclusive
+/// [`try_sample_single`]: UniformSampler::try_sample_single
+/// [`try_sample_single_inclusive`]: UniformSampler::try_sample_single_inclusive
 pub trait UniformSampler: Sized {
     /// The type sampled by this implementation.
     type X;
@@ -295,6 +363,60 @@ pub trait UniformSampler: Sized {
         let uniform: Self = UniformSampler::new_inclusive(low, high);
         uniform.sample(rng)
     }
+
+    /// Construct self, with inclusive lower bound and exclusive upper bound
+    /// `[low, high)`, returning an error if the bounds are invalid.
+    ///
+    /// By default this calls `UniformSampler::new`, which may panic.
+    fn try_new<B1, B2>(low: B1, high: B2) -> Result<Self, UniformError>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Ok(UniformSampler::new(low, high))
+    }
+
+    /// Construct self, with inclusive bounds `[low, high]`, returning an error
+    /// if the bounds are invalid.
+    ///
+    /// By default this calls `UniformSampler::new_inclusive`, which may panic.
+    fn try_new_inclusive<B1, B2>(low: B1, high: B2) -> Result<Self, UniformError>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Ok(UniformSampler::new_inclusive(low, high))
+    }
+
+    /// Sample a single value uniformly from a range with inclusive lower bound
+    /// and exclusive upper bound `[low, high)`, returning an error if the
+    /// bounds are invalid.
+    ///
+    /// By default this is implemented using
+    /// `UniformSampler::try_new(low, high)?.sample(rng)`.
+    fn try_sample_single<R: Rng + ?Sized, B1, B2>(low: B1, high: B2, rng: &mut R)
+        -> Result<Self::X, UniformError>
+        where B1: SampleBorrow<Self::X> + Sized,
+              B2: SampleBorrow<Self::X> + Sized
+    {
+        let uniform: Self = UniformSampler::try_new(low, high)?;
+        Ok(uniform.sample(rng))
+    }
+
+    /// Sample a single value uniformly from a range with inclusive lower bound
+    /// and inclusive upper bound `[low, high]`, returning an error if the
+    /// bounds are invalid.
+    ///
+    /// By default this is implemented using
+    /// `UniformSampler::try_new_inclusive(low, high)?.sample(rng)`.
+    fn try_sample_single_inclusive<R: Rng + ?Sized, B1, B2>(low: B1, high: B2, rng: &mut R)
+        -> Result<Self::X, UniformError>
+        where B1: SampleBorrow<Self::X> + Sized,
+              B2: SampleBorrow<Self::X> + Sized
+    {
+        let uniform: Self = UniformSampler::try_new_inclusive(low, high)?;
+        Ok(uniform.sample(rng))
+    }
 }
 
 impl<X: SampleUniform> From<Range<X>> for Uniform<X> {
@@ -348,6 +470,18 @@ pub trait SampleRange<T> {
 
     /// Check whether the range is empty.
     fn is_empty(&self) -> bool;
+
+    /// Generate a sample from the given range, returning an error instead of
+    /// panicking if the range is invalid.
+    ///
+    /// By default this only checks whether the range is empty.
+    fn try_sample_single<R: RngCore + ?Sized>(self, rng: &mut R) -> Result<T, UniformError>
+    where Self: Sized {
+        if self.is_empty() {
+            return Err(UniformError::EmptyRange);
+        }
+        Ok(self.sample_single(rng))
+    }
 }
 
 impl<T: SampleUniform + PartialOrd> SampleRange<T> for Range<T> {
@@ -360,6 +494,11 @@ impl<T: SampleUniform + PartialOrd> SampleRange<T> for Range<T> {
     fn is_empty(&self) -> bool {
         !(self.start < self.end)
     }
+
+    #[inline]
+    fn try_sample_single<R: RngCore + ?Sized>(self, rng: &mut R) -> Result<T, UniformError> {
+        T::Sampler::try_sample_single(self.start, self.end, rng)
+    }
 }
 
 impl<T: SampleUniform + PartialOrd> SampleRange<T> for RangeInclusive<T> {
@@ -372,6 +511,11 @@ impl<T: SampleUniform + PartialOrd> SampleRange<T> for RangeInclusive<T> {
     fn is_empty(&self) -> bool {
         !(self.start() <= self.end())
     }
+
+    #[inline]
+    fn try_sample_single<R: RngCore + ?Sized>(self, rng: &mut R) -> Result<T, UniformError> {
+        T::Sampler::try_sample_single_inclusive(self.start(), self.end(), rng)
+    }
 }
 
 
@@ -548,6 +692,64 @@ macro_rules! uniform_int_impl {
                     }
                 }
             }
+
+            #[inline]
+            fn try_new<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if low >= high {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(UniformSampler::new_inclusive(low, high - 1))
+            }
+
+            #[inline]
+            fn try_new_inclusive<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if low > high {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(UniformSampler::new_inclusive(low, high))
+            }
+
+            #[inline]
+            fn try_sample_single<R: Rng + ?Sized, B1, B2>(low_b: B1, high_b: B2, rng: &mut R)
+                -> Result<Self::X, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if low >= high {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(Self::sample_single_inclusive(low, high - 1, rng))
+            }
+
+            #[inline]
+            fn try_sample_single_inclusive<R: Rng + ?Sized, B1, B2>(low_b: B1, high_b: B2, rng: &mut R)
+                -> Result<Self::X, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if low > high {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(Self::sample_single_inclusive(low, high, rng))
+            }
         }
     };
 }
@@ -662,6 +864,30 @@ macro_rules! uniform_simd_int_impl {
                     v = mask.select(v, rng.gen());
                 }
             }
+
+            fn try_new<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+                where B1: SampleBorrow<Self::X> + Sized,
+                      B2: SampleBorrow<Self::X> + Sized
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if !low.lt(high).all() {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(UniformSampler::new_inclusive(low, high - 1))
+            }
+
+            fn try_new_inclusive<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+                where B1: SampleBorrow<Self::X> + Sized,
+                      B2: SampleBorrow<Self::X> + Sized
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if !low.le(high).all() {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(UniformSampler::new_inclusive(low, high))
+            }
         }
     };
 
@@ -773,6 +999,30 @@ impl UniformSampler for UniformChar {
         UniformChar { sampler }
     }
 
+    #[inline]
+    fn try_new<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        let low = char_to_comp_u32(*low_b.borrow());
+        let high = char_to_comp_u32(*high_b.borrow());
+        let sampler = UniformInt::<u32>::try_new(low, high)?;
+        Ok(UniformChar { sampler })
+    }
+
+    #[inline]
+    fn try_new_inclusive<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        let low = char_to_comp_u32(*low_b.borrow());
+        let high = char_to_comp_u32(*high_b.borrow());
+        let sampler = UniformInt::<u32>::try_new_inclusive(low, high)?;
+        Ok(UniformChar { sampler })
+    }
+
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::X {
         let mut x = self.sampler.sample(rng);
         if x >= CHAR_SURROGATE_START {
@@ -970,6 +1220,56 @@ macro_rules! uniform_float_impl {
                     }
                 }
             }
+
+            fn try_new<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if !(low.all_finite() && high.all_finite()) {
+                    return Err(UniformError::NonFinite);
+                }
+                if !low.all_lt(high) {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(UniformSampler::new(low, high))
+            }
+
+            fn try_new_inclusive<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if !(low.all_finite() && high.all_finite()) {
+                    return Err(UniformError::NonFinite);
+                }
+                if !low.all_le(high) {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(UniformSampler::new_inclusive(low, high))
+            }
+
+            #[inline]
+            fn try_sample_single<R: Rng + ?Sized, B1, B2>(low_b: B1, high_b: B2, rng: &mut R)
+                -> Result<Self::X, UniformError>
+            where
+                B1: SampleBorrow<Self::X> + Sized,
+                B2: SampleBorrow<Self::X> + Sized,
+            {
+                let low = *low_b.borrow();
+                let high = *high_b.borrow();
+                if !(low.all_finite() && high.all_finite()) {
+                    return Err(UniformError::NonFinite);
+                }
+                if !low.all_lt(high) {
+                    return Err(UniformError::EmptyRange);
+                }
+                Ok(Self::sample_single(low, high, rng))
+            }
         }
     };
 }
@@ -1124,6 +1424,34 @@ impl UniformSampler for UniformDuration {
             }
         }
     }
+
+    #[inline]
+    fn try_new<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        let low = *low_b.borrow();
+        let high = *high_b.borrow();
+        if low >= high {
+            return Err(UniformError::EmptyRange);
+        }
+        Ok(UniformDuration::new_inclusive(low, high - Duration::new(0, 1)))
+    }
+
+    #[inline]
+    fn try_new_inclusive<B1, B2>(low_b: B1, high_b: B2) -> Result<Self, UniformError>
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        let low = *low_b.borrow();
+        let high = *high_b.borrow();
+        if low > high {
+            return Err(UniformError::EmptyRange);
+        }
+        Ok(UniformDuration::new_inclusive(low, high))
+    }
 }
 
 #[cfg(test)]
@@ -1202,6 +1530,22 @@ mod tests {
         Uniform::new(10, 5);
     }
 
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_integer_rejection() {
+        use crate::rngs::mock::{RecordingRng, ReplayRng};
+
+        // For a range of 3, only the `u32` value for which `3 * v` wraps to
+        // `u32::MAX` is rejected.
+        let dist = Uniform::new(0u32, 3);
+        let mut rng = RecordingRng::new(ReplayRng::from_words(alloc::vec![0x5555_5555, 0]));
+        assert_eq!(dist.sample(&mut rng), 0);
+        assert_eq!(rng.calls().len(), 2);
+        let mut rng = ReplayRng::from_words(alloc::vec![0x5555_5556, 0]);
+        assert_eq!(dist.sample(&mut rng), 1);
+        assert_eq!(rng.remaining(), 1);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)] // Miri is too slow
     fn test_integers() {
@@ -1425,6 +1769,10 @@ mod tests {
             let mut rng = crate::test::rng(253);
             T::Sampler::sample_single(low, high, &mut rng);
         }
+        fn try_range<T: SampleUniform>(low: T, high: T) -> Result<T, UniformError> {
+            let mut rng = crate::test::rng(253);
+            T::Sampler::try_sample_single(low, high, &mut rng)
+        }
 
         macro_rules! t {
             ($ty:ident, $f_scalar:ident) => {{
@@ -1454,6 +1802,11 @@ mod tests {
                         assert!(catch_unwind(|| Uniform::new_inclusive(low, high)).is_err());
                         assert!(catch_unwind(|| range(low, low)).is_err());
                         assert!(catch_unwind(|| Uniform::new(low, low)).is_err());
+                        assert!(Uniform::try_new(low, high).is_err());
+                        assert!(Uniform::try_new_inclusive(low, high).is_err());
+                        assert!(Uniform::try_new(low, low).is_err());
+                        assert!(try_range(low, high).is_err());
+                        assert!(try_range(low, low).is_err());
                     }
                 }
             }};
@@ -1473,6 +1826,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_new() {
+        #[cfg(not(feature = "std"))] use core::time::Duration;
+        #[cfg(feature = "std")] use std::time::Duration;
+        use core::f64::{INFINITY, NAN};
+
+        let mut rng = crate::test::rng(254);
+
+        let u = Uniform::try_new(-3i32, 4).unwrap();
+        assert!((-3..4).contains(&u.sample(&mut rng)));
+        assert_eq!(Uniform::try_new_inclusive(7u8, 7).unwrap().sample(&mut rng), 7);
+        assert_eq!(Uniform::try_new(7u8, 7).unwrap_err(), UniformError::EmptyRange);
+        assert_eq!(Uniform::try_new_inclusive(8u64, 7).unwrap_err(), UniformError::EmptyRange);
+        assert!(Uniform::try_new_inclusive(0u64, u64::max_value()).is_ok());
+
+        let u = Uniform::try_new(1.0, 2.0).unwrap();
+        assert!((1.0..2.0).contains(&u.sample(&mut rng)));
+        assert!(Uniform::try_new_inclusive(1.0f32, 1.0).is_ok());
+        assert_eq!(Uniform::try_new(2.0, 1.0).unwrap_err(), UniformError::EmptyRange);
+        assert_eq!(Uniform::try_new(0.0, INFINITY).unwrap_err(), UniformError::NonFinite);
+        assert_eq!(Uniform::try_new_inclusive(NAN, 1.0).unwrap_err(), UniformError::NonFinite);
+
+        assert_eq!(Uniform::try_new('a', 'a').unwrap_err(), UniformError::EmptyRange);
+        assert_eq!(Uniform::try_new_inclusive('a', 'a').unwrap().sample(&mut rng), 'a');
+        // The surrogate range is skipped
+        let u = Uniform::try_new('\u{D7FF}', '\u{E000}').unwrap();
+        assert_eq!(u.sample(&mut rng), '\u{D7FF}');
+        assert!(Uniform::try_new('\u{E000}', '\u{D7FF}').is_err());
+
+        let (low, high) = (Duration::new(1, 5), Duration::new(1, 6));
+        assert_eq!(Uniform::try_new(low, high).unwrap().sample(&mut rng), low);
+        assert_eq!(Uniform::try_new(high, low).unwrap_err(), UniformError::EmptyRange);
+        assert_eq!(Uniform::try_new(low, low).unwrap_err(), UniformError::EmptyRange);
+        assert_eq!(Uniform::try_new_inclusive(low, low).unwrap().sample(&mut rng), low);
+    }
 
     #[test]
     #[cfg_attr(miri, ignore)] // Miri is too slow
@@ -1622,4 +2010,48 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption() {
+        use crate::test::consumption;
+
+        // `sample` uses one value of the (widened) integer type per attempt,
+        // and `sample_single` rejects more often
+        fn test_int<T: SampleUniform + From<u8>>(expected: (u64, u64, u64)) {
+            let dist = Uniform::new(T::from(0), T::from(3));
+            assert_eq!(consumption(415, |rng| for _ in 0..100 {
+                dist.sample(rng);
+            }), expected);
+        }
+        test_int::<u8>((100, 0, 0));
+        test_int::<u16>((100, 0, 0));
+        test_int::<u32>((100, 0, 0));
+        test_int::<u64>((0, 100, 0));
+        test_int::<u128>((0, 200, 0));
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            rng.gen_range(0u32..3);
+        }), (128, 0, 0));
+
+        // Floats, `char` and `Duration` never reject for these ranges
+        let dist = Uniform::new(0f32, 3.0);
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            dist.sample(rng);
+        }), (100, 0, 0));
+        let dist = Uniform::new(0f64, 3.0);
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            dist.sample(rng);
+        }), (0, 100, 0));
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            rng.gen_range(0f64..3.0);
+        }), (0, 100, 0));
+        let dist = Uniform::new('a', 'z');
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            dist.sample(rng);
+        }), (100, 0, 0));
+        let dist = Uniform::new(Duration::from_secs(1), Duration::from_secs(3));
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            dist.sample(rng);
+        }), (0, 100, 0));
+    }
 }
@@ -418,6 +418,22 @@ mod test {
             2, 2, 1, 3, 2, 1, 3, 3, 2, 1,
         ]);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption() {
+        use crate::test::consumption;
+
+        // One value of the weight type per sample
+        let distr = WeightedIndex::new(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            distr.sample(rng);
+        }), (100, 0, 0));
+        let distr = WeightedIndex::new(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(consumption(415, |rng| for _ in 0..100 {
+            distr.sample(rng);
+        }), (0, 100, 0));
+    }
 }
 
 /// Error type returned from `WeightedIndex::new`.
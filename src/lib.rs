@@ -92,7 +92,9 @@ macro_rules! error { ($($x:tt)*) => (
 ) }
 
 // Re-exports from rand_core
-pub use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
+pub use rand_core::{CryptoRng, Error, RngCore, SeedableRng, SeekableRng, SplittableRng};
+pub use rand_core::entropy;
+pub use rand_core::snapshot;
 
 // Public modules
 pub mod distributions;
@@ -198,6 +200,16 @@ mod test {
         rand_pcg::Pcg32::new(seed, INC)
     }
 
+    /// Run `f` with a deterministic RNG with the given seed, returning the
+    /// number of calls to `next_u32`, `next_u64` and `fill_bytes`
+    #[cfg(feature = "std")]
+    pub fn consumption<F: FnOnce(&mut dyn RngCore)>(seed: u64, f: F) -> (u64, u64, u64) {
+        let mut rng = crate::rngs::adapter::CountingRng::new(rng(seed));
+        f(&mut rng);
+        let usage = rng.snapshot();
+        (usage.next_u32, usage.next_u64, usage.fill_bytes)
+    }
+
     #[test]
     #[cfg(all(feature = "std", feature = "std_rng"))]
     fn test_random() {
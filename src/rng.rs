@@ -10,8 +10,8 @@
 //! [`Rng`] trait
 
 use rand_core::{Error, RngCore};
-use crate::distributions::uniform::{SampleRange, SampleUniform};
-use crate::distributions::{self, Distribution, Standard};
+use crate::distributions::uniform::{SampleRange, SampleUniform, UniformError};
+use crate::distributions::{self, BernoulliError, Distribution, Standard};
 use core::num::Wrapping;
 use core::{mem, slice};
 
@@ -103,7 +103,8 @@ pub trait Rng: RngCore {
     ///
     /// # Panics
     ///
-    /// Panics if the range is empty.
+    /// Panics if the range is empty. See [`try_gen_range`] for a variant
+    /// returning an error instead.
     ///
     /// # Example
     ///
@@ -124,6 +125,7 @@ pub trait Rng: RngCore {
     /// ```
     ///
     /// [`Uniform`]: distributions::uniform::Uniform
+    /// [`try_gen_range`]: Rng::try_gen_range
     fn gen_range<T, R>(&mut self, range: R) -> T
     where
         T: SampleUniform,
@@ -133,6 +135,35 @@ pub trait Rng: RngCore {
         range.sample_single(self)
     }
 
+    /// Generate a random value in the given range, returning an error instead
+    /// of panicking if the range is invalid.
+    ///
+    /// This is the fallible variant of [`gen_range`]; it returns
+    /// [`UniformError::EmptyRange`] if the range is empty and
+    /// [`UniformError::NonFinite`] if a floating-point bound is not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::{thread_rng, Rng};
+    /// use rand::distributions::UniformError;
+    ///
+    /// let mut rng = thread_rng();
+    /// let n: u32 = rng.try_gen_range(0..10).unwrap();
+    /// println!("{}", n);
+    /// assert_eq!(rng.try_gen_range(10..10u32), Err(UniformError::EmptyRange));
+    /// assert_eq!(rng.try_gen_range(0.0..std::f64::NAN), Err(UniformError::NonFinite));
+    /// ```
+    ///
+    /// [`gen_range`]: Rng::gen_range
+    fn try_gen_range<T, R>(&mut self, range: R) -> Result<T, UniformError>
+    where
+        T: SampleUniform,
+        R: SampleRange<T>
+    {
+        range.try_sample_single(self)
+    }
+
     /// Sample a new value, using the given distribution.
     ///
     /// ### Example
@@ -195,6 +226,32 @@ pub trait Rng: RngCore {
         distr.sample_iter(self)
     }
 
+    /// Create a parallel iterator that generates `len` values using the given
+    /// distribution, and generators forked from this one.
+    ///
+    /// See [`Distribution::par_sample_iter`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::{Rng, SeedableRng};
+    /// use rand::distributions::Uniform;
+    /// use rand::rngs::StdRng;
+    /// use rayon::iter::ParallelIterator;
+    ///
+    /// let rng = StdRng::seed_from_u64(42);
+    /// let sum: u64 = rng.par_sample_iter(Uniform::new(0u64, 6), 1_000_000).sum();
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "rayon")))]
+    fn par_sample_iter<T, D>(self, distr: D, len: usize) -> distributions::ParDistIter<D, Self, T>
+    where
+        D: Distribution<T>,
+        Self: crate::SplittableRng,
+    {
+        distr.par_sample_iter(self, len)
+    }
+
     /// Fill any type implementing [`Fill`] with random data
     ///
     /// The distribution is expected to be uniform with portable results, but
@@ -261,9 +318,11 @@ pub trait Rng: RngCore {
     ///
     /// # Panics
     ///
-    /// If `p < 0` or `p > 1`.
+    /// If `p < 0` or `p > 1`. See [`try_gen_bool`] for a variant returning an
+    /// error instead.
     ///
     /// [`Bernoulli`]: distributions::Bernoulli
+    /// [`try_gen_bool`]: Rng::try_gen_bool
     #[inline]
     fn gen_bool(&mut self, p: f64) -> bool {
         let d = distributions::Bernoulli::new(p).unwrap();
@@ -281,7 +340,8 @@ pub trait Rng: RngCore {
     ///
     /// # Panics
     ///
-    /// If `denominator == 0` or `numerator > denominator`.
+    /// If `denominator == 0` or `numerator > denominator`. See
+    /// [`try_gen_ratio`] for a variant returning an error instead.
     ///
     /// # Example
     ///
@@ -293,11 +353,56 @@ pub trait Rng: RngCore {
     /// ```
     ///
     /// [`Bernoulli`]: distributions::Bernoulli
+    /// [`try_gen_ratio`]: Rng::try_gen_ratio
     #[inline]
     fn gen_ratio(&mut self, numerator: u32, denominator: u32) -> bool {
         let d = distributions::Bernoulli::from_ratio(numerator, denominator).unwrap();
         self.sample(d)
     }
+
+    /// Return a bool with a probability `p` of being true, or an error if
+    /// `p < 0`, `p > 1` or `p` is NaN.
+    ///
+    /// This is the fallible variant of [`gen_bool`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::{thread_rng, Rng};
+    ///
+    /// let mut rng = thread_rng();
+    /// println!("{}", rng.try_gen_bool(1.0 / 3.0).unwrap());
+    /// assert!(rng.try_gen_bool(1.5).is_err());
+    /// ```
+    ///
+    /// [`gen_bool`]: Rng::gen_bool
+    #[inline]
+    fn try_gen_bool(&mut self, p: f64) -> Result<bool, BernoulliError> {
+        let d = distributions::Bernoulli::new(p)?;
+        Ok(self.sample(d))
+    }
+
+    /// Return a bool with a probability of `numerator/denominator` of being
+    /// true, or an error if `denominator == 0` or `numerator > denominator`.
+    ///
+    /// This is the fallible variant of [`gen_ratio`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rand::{thread_rng, Rng};
+    ///
+    /// let mut rng = thread_rng();
+    /// println!("{}", rng.try_gen_ratio(2, 3).unwrap());
+    /// assert!(rng.try_gen_ratio(1, 0).is_err());
+    /// ```
+    ///
+    /// [`gen_ratio`]: Rng::gen_ratio
+    #[inline]
+    fn try_gen_ratio(&mut self, numerator: u32, denominator: u32) -> Result<bool, BernoulliError> {
+        let d = distributions::Bernoulli::from_ratio(numerator, denominator)?;
+        Ok(self.sample(d))
+    }
 }
 
 impl<R: RngCore + ?Sized> Rng for R {}
@@ -532,6 +637,44 @@ mod test {
         r.gen_range(5..2);
     }
 
+    #[test]
+    fn test_try_gen_range() {
+        use crate::distributions::UniformError;
+        let mut r = rng(104);
+        for _ in 0..100 {
+            let a = r.try_gen_range(-4711..17).unwrap();
+            assert!((-4711..17).contains(&a));
+            let a = r.try_gen_range(3.0..=3.5f32).unwrap();
+            assert!((3.0..=3.5).contains(&a));
+        }
+        assert_eq!(r.try_gen_range(5..5), Err(UniformError::EmptyRange));
+        assert_eq!(r.try_gen_range(5..2usize), Err(UniformError::EmptyRange));
+        assert_eq!(r.try_gen_range(5..=5).unwrap(), 5);
+        assert_eq!(r.try_gen_range(5..=4), Err(UniformError::EmptyRange));
+        assert_eq!(r.try_gen_range(1.0..0.0), Err(UniformError::EmptyRange));
+        let inf = core::f64::INFINITY;
+        assert_eq!(r.try_gen_range(-inf..0.0), Err(UniformError::NonFinite));
+        assert_eq!(r.try_gen_range(0.0..=inf), Err(UniformError::NonFinite));
+        assert_eq!(r.try_gen_range('a'..='b').map(|c| c == 'a' || c == 'b'), Ok(true));
+    }
+
+    #[test]
+    fn test_try_gen_bool() {
+        use crate::distributions::BernoulliError;
+        let mut r = rng(106);
+        for _ in 0..5 {
+            assert_eq!(r.try_gen_bool(0.0), Ok(false));
+            assert_eq!(r.try_gen_bool(1.0), Ok(true));
+            assert_eq!(r.try_gen_ratio(0, 3), Ok(false));
+            assert_eq!(r.try_gen_ratio(3, 3), Ok(true));
+        }
+        assert_eq!(r.try_gen_bool(-0.5), Err(BernoulliError::InvalidProbability));
+        assert_eq!(r.try_gen_bool(1.5), Err(BernoulliError::InvalidProbability));
+        assert_eq!(r.try_gen_bool(core::f64::NAN), Err(BernoulliError::InvalidProbability));
+        assert_eq!(r.try_gen_ratio(4, 3), Err(BernoulliError::InvalidProbability));
+        assert_eq!(r.try_gen_ratio(0, 0), Err(BernoulliError::InvalidProbability));
+    }
+
     #[test]
     fn test_gen_bool() {
         let mut r = rng(105);
@@ -0,0 +1,220 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An adapter buffering the output of an expensive RNG.
+
+use core::fmt;
+use std::vec;
+use std::vec::Vec;
+
+use rand_core::{CryptoRng, Error, RngCore};
+
+use super::zeroize;
+
+// Default number of bytes fetched from the wrapped RNG at once.
+const DEFAULT_CHUNK_SIZE: usize = 256;
+
+/// A wrapper around an RNG which fetches its output in chunks.
+///
+/// Sources such as [`OsRng`] and [`ReadRng`] make a system call or read for
+/// every request, which dominates the cost of small requests like
+/// `OsRng.gen::<u32>()`. `BufferedRng` requests a whole chunk at once, and
+/// serves small requests from its buffer. Requests of at least the chunk size
+/// go directly to the wrapped RNG, once the buffer is used up.
+///
+/// Every byte is zeroed in the buffer as soon as it has been returned, and the
+/// remaining bytes are zeroed when the `BufferedRng` is dropped, so no output
+/// is left behind in memory. For the same reason `BufferedRng` does not
+/// implement `Clone`: the clone would repeat the buffered output.
+///
+/// Errors of the wrapped RNG are returned by [`try_fill_bytes`]; the other
+/// methods panic on error, like those of `OsRng`. After an error the buffer is
+/// empty, and the next request fetches a new chunk.
+///
+/// # Example
+///
+/// ```
+/// use rand::Rng;
+/// use rand::rngs::OsRng;
+/// use rand::rngs::adapter::BufferedRng;
+///
+/// let mut rng = BufferedRng::new(OsRng);
+/// let x: u32 = rng.gen(); // fetches 256 bytes from `OsRng`
+/// let y: u32 = rng.gen(); // served from the buffer
+/// ```
+///
+/// [`OsRng`]: crate::rngs::OsRng
+/// [`ReadRng`]: crate::rngs::adapter::ReadRng
+/// [`try_fill_bytes`]: RngCore::try_fill_bytes
+pub struct BufferedRng<R: RngCore> {
+    rng: R,
+    buf: Vec<u8>,
+    // Index of the first byte in `buf` which has not been returned
+    pos: usize,
+}
+
+impl<R: RngCore> BufferedRng<R> {
+    /// Wrap `rng`, fetching 256 bytes at once.
+    pub fn new(rng: R) -> Self {
+        Self::with_chunk_size(rng, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Wrap `rng`, fetching `chunk_size` bytes at once.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn with_chunk_size(rng: R, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "BufferedRng: chunk size must be positive");
+        BufferedRng {
+            rng,
+            buf: vec![0; chunk_size],
+            pos: chunk_size,
+        }
+    }
+
+    /// The number of bytes fetched from the wrapped RNG at once.
+    pub fn chunk_size(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// The number of buffered bytes which have not been returned yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Get a reference to the wrapped RNG.
+    pub fn get_ref(&self) -> &R {
+        &self.rng
+    }
+
+    fn fill(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let mut filled = self.take(dest);
+        if filled == dest.len() {
+            return Ok(());
+        }
+        // The buffer is used up
+        let rest = dest.len() - filled;
+        if rest >= self.buf.len() {
+            return self.rng.try_fill_bytes(&mut dest[filled..]);
+        }
+        if let Err(e) = self.rng.try_fill_bytes(&mut self.buf) {
+            // Don't leave partial output behind
+            zeroize(&mut self.buf);
+            return Err(e);
+        }
+        self.pos = 0;
+        filled += self.take(&mut dest[filled..]);
+        debug_assert_eq!(filled, dest.len());
+        Ok(())
+    }
+
+    // Copy buffered bytes to `dest`, zeroing them in the buffer.
+    fn take(&mut self, dest: &mut [u8]) -> usize {
+        let n = self.buffered().min(dest.len());
+        let src = &mut self.buf[self.pos..self.pos + n];
+        dest[..n].copy_from_slice(src);
+        zeroize(src);
+        self.pos += n;
+        n
+    }
+}
+
+impl<R: RngCore> RngCore for BufferedRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.fill(dest) {
+            panic!("Error: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill(dest)
+    }
+}
+
+impl<R: RngCore + CryptoRng> CryptoRng for BufferedRng<R> {}
+
+impl<R: RngCore> Drop for BufferedRng<R> {
+    fn drop(&mut self) {
+        zeroize(&mut self.buf[self.pos..]);
+    }
+}
+
+// Custom Debug implementation that does not expose the internal state
+impl<R: RngCore + fmt::Debug> fmt::Debug for BufferedRng<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferedRng")
+            .field("rng", &self.rng)
+            .field("chunk_size", &self.buf.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BufferedRng;
+    use crate::rngs::adapter::{CountingRng, ReadRng};
+    use crate::rngs::mock::StepRng;
+    use crate::RngCore;
+
+    #[test]
+    fn test_buffered_rng() {
+        let mut rng = BufferedRng::with_chunk_size(CountingRng::new(StepRng::new(1, 1)), 16);
+        let mut reference = StepRng::new(1, 1);
+        let mut expected = [0u8; 64];
+        reference.fill_bytes(&mut expected);
+
+        // Small requests are served from chunks of 16 bytes
+        assert_eq!(rng.next_u32(), 1);
+        assert_eq!(rng.buffered(), 12);
+        assert_eq!(rng.next_u64(), 2 << 32);
+        let mut buf = [0u8; 12];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(&buf[..], &expected[12..24]);
+        assert_eq!(rng.buffered(), 8);
+        assert_eq!(rng.get_ref().snapshot().fill_bytes, 2);
+        // Large requests go directly to the wrapped RNG
+        let mut buf = [0u8; 40];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(&buf[..], &expected[24..64]);
+        assert_eq!(rng.buffered(), 0);
+        assert_eq!(rng.get_ref().snapshot().fill_bytes, 3);
+        assert_eq!(rng.get_ref().snapshot().bytes, 64);
+    }
+
+    #[test]
+    fn test_buffered_rng_zeroize() {
+        let mut rng = BufferedRng::with_chunk_size(StepRng::new(!0, 0), 16);
+        rng.next_u64();
+        assert!(rng.buf[..8].iter().all(|&x| x == 0));
+        assert!(rng.buf[8..].iter().all(|&x| x == 0xff));
+    }
+
+    #[test]
+    fn test_buffered_rng_error() {
+        let v = [1u8, 2, 3, 4, 5, 6];
+        let mut rng = BufferedRng::with_chunk_size(ReadRng::new(&v[..]), 4);
+        assert_eq!(rng.next_u32(), 0x0403_0201);
+        // Only 2 bytes are left for the next chunk
+        let mut buf = [0u8; 2];
+        assert!(rng.try_fill_bytes(&mut buf).is_err());
+        assert_eq!(rng.buffered(), 0);
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An adapter counting the use of another RNG.
+
+use rand_core::{CryptoRng, Error, RngCore};
+
+/// A count of the calls made to a [`CountingRng`] and the bytes generated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RngUsage {
+    /// The number of calls to `next_u32`.
+    pub next_u32: u64,
+    /// The number of calls to `next_u64`.
+    pub next_u64: u64,
+    /// The number of calls to `fill_bytes` and `try_fill_bytes`.
+    pub fill_bytes: u64,
+    /// The total number of bytes generated by all calls.
+    pub bytes: u64,
+}
+
+/// A wrapper around any RNG, counting the calls made to it and the number of
+/// bytes it generates.
+///
+/// This measures how much randomness an algorithm consumes. `next_u32` and
+/// `next_u64` count as 4 and 8 bytes; `try_fill_bytes` counts as
+/// `fill_bytes`, even if it fails.
+///
+/// # Example
+///
+/// ```
+/// use rand::Rng;
+/// use rand::rngs::adapter::{CountingRng, RngUsage};
+/// use rand::rngs::mock::StepRng;
+///
+/// let mut rng = CountingRng::new(StepRng::new(0, 1));
+/// let _: u64 = rng.gen();
+/// let _: [u8; 3] = rng.gen();
+/// assert_eq!(rng.snapshot(), RngUsage {
+///     next_u32: 3,
+///     next_u64: 1,
+///     fill_bytes: 0,
+///     bytes: 20,
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountingRng<R> {
+    rng: R,
+    usage: RngUsage,
+}
+
+impl<R: RngCore> CountingRng<R> {
+    /// Wrap `rng`, with all counts zero.
+    pub fn new(rng: R) -> Self {
+        CountingRng {
+            rng,
+            usage: RngUsage::default(),
+        }
+    }
+
+    /// The counts since creation or the last [`reset`].
+    ///
+    /// [`reset`]: CountingRng::reset
+    pub fn snapshot(&self) -> RngUsage {
+        self.usage
+    }
+
+    /// Reset all counts to zero.
+    pub fn reset(&mut self) {
+        self.usage = RngUsage::default();
+    }
+
+    /// Get a reference to the wrapped RNG.
+    pub fn get_ref(&self) -> &R {
+        &self.rng
+    }
+
+    /// Consume the `CountingRng`, returning the wrapped RNG.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+impl<R: RngCore> RngCore for CountingRng<R> {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.usage.next_u32 += 1;
+        self.usage.bytes += 4;
+        self.rng.next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.usage.next_u64 += 1;
+        self.usage.bytes += 8;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.usage.fill_bytes += 1;
+        self.usage.bytes += dest.len() as u64;
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.usage.fill_bytes += 1;
+        self.usage.bytes += dest.len() as u64;
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl<R: RngCore + CryptoRng> CryptoRng for CountingRng<R> {}
+
+#[cfg(test)]
+mod test {
+    use super::{CountingRng, RngUsage};
+    use crate::rngs::mock::StepRng;
+    use crate::RngCore;
+
+    #[test]
+    fn test_counting_rng() {
+        let mut rng = CountingRng::new(StepRng::new(1, 1));
+        assert_eq!(rng.next_u32(), 1);
+        assert_eq!(rng.next_u64(), 2);
+        let mut buf = [0u8; 5];
+        rng.fill_bytes(&mut buf);
+        rng.try_fill_bytes(&mut buf).unwrap();
+        assert_eq!(rng.snapshot(), RngUsage {
+            next_u32: 1,
+            next_u64: 1,
+            fill_bytes: 2,
+            bytes: 22,
+        });
+
+        rng.reset();
+        assert_eq!(rng.snapshot(), RngUsage::default());
+        assert_eq!(rng.next_u64(), 5);
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fork detection, and an adapter reseeding any PRNG after a fork.
+
+use rand_core::entropy::DefaultEntropy;
+use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
+
+/// The fork epoch of the current process: the number of times the process
+/// or one of its ancestors has been forked since fork detection was enabled.
+///
+/// The first call (or the first creation of a [`ForkGuard`], [`ReseedingRng`]
+/// or `ThreadRng`) enables fork detection, so forks before that are not
+/// counted. Comparing a value stored before a fork with the current one tells
+/// whether the code is running in a child process. The epoch may wrap around.
+///
+/// Fork detection uses `pthread_atfork` and is only available on Unix (except
+/// Emscripten); on other platforms the epoch is always zero.
+///
+/// [`ReseedingRng`]: crate::rngs::adapter::ReseedingRng
+pub fn fork_epoch() -> usize {
+    register_fork_handler();
+    get_fork_counter()
+}
+
+/// A wrapper around any PRNG that reseeds it in the child after a fork.
+///
+/// After a fork the parent and child process have identical copies of every
+/// PRNG, so without reseeding both would generate the same values. `ForkGuard`
+/// checks the [`fork_epoch`] on every call, and after a fork reseeds the
+/// wrapped PRNG from [`DefaultEntropy`] before producing further output. This
+/// costs one atomic load per call.
+///
+/// [`ReseedingRng`] and `ThreadRng` have the same protection built in.
+///
+/// # Error handling
+///
+/// If reseeding after a fork fails, [`try_fill_bytes`] returns the error and
+/// the other methods panic: continuing with the state of the parent would
+/// defeat the purpose. Reseeding is attempted again on the next call.
+///
+/// # Example
+///
+/// ```
+/// use rand::prelude::*;
+/// use rand::rngs::adapter::ForkGuard;
+///
+/// // Created before forking the workers of a pre-fork server
+/// let mut rng = ForkGuard::new(StdRng::from_entropy());
+/// println!("{}", rng.gen::<u64>());
+/// ```
+///
+/// [`ReseedingRng`]: crate::rngs::adapter::ReseedingRng
+/// [`DefaultEntropy`]: rand_core::entropy::DefaultEntropy
+/// [`try_fill_bytes`]: RngCore::try_fill_bytes
+#[derive(Debug, Clone)]
+pub struct ForkGuard<R> {
+    rng: R,
+    epoch: usize,
+}
+
+impl<R: RngCore + SeedableRng> ForkGuard<R> {
+    /// Wrap `rng`, enabling fork detection.
+    pub fn new(rng: R) -> Self {
+        ForkGuard {
+            rng,
+            epoch: fork_epoch(),
+        }
+    }
+
+    /// Reseed the wrapped PRNG from [`DefaultEntropy`].
+    ///
+    /// [`DefaultEntropy`]: rand_core::entropy::DefaultEntropy
+    pub fn reseed(&mut self) -> Result<(), Error> {
+        let epoch = get_fork_counter();
+        self.rng = R::from_entropy_source(&mut DefaultEntropy)?;
+        self.epoch = epoch;
+        Ok(())
+    }
+
+    /// Get a reference to the wrapped PRNG.
+    pub fn get_ref(&self) -> &R {
+        &self.rng
+    }
+
+    /// Consume the `ForkGuard`, returning the wrapped PRNG.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+
+    #[inline(always)]
+    fn check(&mut self) -> Result<(), Error> {
+        if self.epoch != get_fork_counter() {
+            return self.reseed_after_fork();
+        }
+        Ok(())
+    }
+
+    #[inline(never)]
+    fn reseed_after_fork(&mut self) -> Result<(), Error> {
+        info!("Fork detected, reseeding RNG");
+        self.reseed()
+    }
+
+    #[inline(always)]
+    fn check_or_panic(&mut self) {
+        if let Err(err) = self.check() {
+            panic!("ForkGuard: reseeding after fork failed: {}", err);
+        }
+    }
+}
+
+impl<R: RngCore + SeedableRng> RngCore for ForkGuard<R> {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        self.check_or_panic();
+        self.rng.next_u32()
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.check_or_panic();
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.check_or_panic();
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.check()?;
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl<R: RngCore + SeedableRng + CryptoRng> CryptoRng for ForkGuard<R> {}
+
+
+#[cfg(all(unix, not(target_os = "emscripten")))]
+mod imp {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Once;
+
+    // Fork protection
+    //
+    // We implement fork protection on Unix using `pthread_atfork`.
+    // When the process is forked, we increment `RESEEDING_RNG_FORK_COUNTER`.
+    // Every `ReseedingRng` and `ForkGuard` stores the last known value of the
+    // static in `fork_counter`. If the cached `fork_counter` is less than
+    // `RESEEDING_RNG_FORK_COUNTER`, it is time to reseed this RNG.
+    //
+    // If reseeding fails, we don't deal with this by setting a delay, but just
+    // don't update `fork_counter`, so a reseed is attempted as soon as
+    // possible.
+
+    static RESEEDING_RNG_FORK_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    pub fn get_fork_counter() -> usize {
+        RESEEDING_RNG_FORK_COUNTER.load(Ordering::Relaxed)
+    }
+
+    extern "C" fn fork_handler() {
+        // Note: fetch_add is defined to wrap on overflow
+        // (which is what we want).
+        RESEEDING_RNG_FORK_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn register_fork_handler() {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| unsafe {
+            libc::pthread_atfork(None, None, Some(fork_handler));
+        });
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "emscripten"))))]
+mod imp {
+    pub fn get_fork_counter() -> usize {
+        0
+    }
+    pub fn register_fork_handler() {}
+}
+
+pub(crate) use self::imp::{get_fork_counter, register_fork_handler};
+
+
+#[cfg(feature = "std_rng")]
+#[cfg(test)]
+mod test {
+    use super::{fork_epoch, ForkGuard};
+    use crate::rngs::StdRng;
+    use crate::{RngCore, SeedableRng};
+
+    #[test]
+    fn test_fork_guard() {
+        // Without a fork, the wrapped PRNG is used as is
+        let mut rng = ForkGuard::new(StdRng::seed_from_u64(42));
+        let mut reference = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), reference.next_u64());
+        }
+        assert_eq!(fork_epoch(), fork_epoch());
+
+        // Reseeding replaces the state
+        rng.reseed().unwrap();
+        assert_ne!(rng.next_u64(), reference.next_u64());
+    }
+}
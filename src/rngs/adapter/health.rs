@@ -0,0 +1,344 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An adapter running the health tests of NIST SP 800-90B on a noise source.
+
+use core::num::NonZeroU32;
+
+use rand_core::{impls, CryptoRng, Error, RngCore};
+
+// The false positive probability of each test, as recommended by
+// SP 800-90B: `2^-ALPHA_EXP`.
+const ALPHA_EXP: u32 = 20;
+
+// Window size of the adaptive proportion test for non-binary samples.
+const APT_WINDOW: u32 = 512;
+
+// Number of samples tested (and discarded) before the first output.
+const STARTUP_SAMPLES: usize = 1024;
+
+/// An RNG wrapper running the continuous health tests of NIST SP 800-90B
+/// (section 4.4) on the output of a noise source.
+///
+/// Each output byte is treated as one sample. Two tests are run:
+///
+/// - The *repetition count test* fails when a sample is repeated
+///   `1 + ⌈20 / H⌉` times in a row, with [`Error::ENTROPY_REPETITION_COUNT_FAILURE`].
+/// - The *adaptive proportion test* fails when the first sample of a window of
+///   512 samples occurs too often within the window, with
+///   [`Error::ENTROPY_ADAPTIVE_PROPORTION_FAILURE`]. The cutoff is the
+///   critical value of the binomial distribution for the probability `2^-H`.
+///
+/// `H` is the assessed min-entropy per sample, in bits, given to
+/// [`HealthTestedRng::new`]. Both tests use the false positive probability
+/// `α = 2^-20` recommended by SP 800-90B, per sample: a source with full
+/// entropy will occasionally fail them.
+///
+/// Before the first output, 1024 samples are tested and discarded, as
+/// required for start-up testing. Once a test fails, all requests fail with
+/// the same error until [`reset`] is called; the output of the failing
+/// request is zeroed. Errors of the wrapped source are passed through.
+///
+/// # Example
+///
+/// ```
+/// use rand::rngs::adapter::HealthTestedRng;
+/// use rand::rngs::OsRng;
+/// use rand::RngCore;
+///
+/// // A source with full entropy: 8 bits per byte
+/// let mut rng = HealthTestedRng::new(OsRng, 8.0);
+/// let mut seed = [0u8; 32];
+/// if let Err(err) = rng.try_fill_bytes(&mut seed) {
+///     panic!("noise source failure: {}", err);
+/// }
+/// ```
+///
+/// [`reset`]: HealthTestedRng::reset
+#[derive(Debug, Clone)]
+pub struct HealthTestedRng<R> {
+    rng: R,
+    rct_cutoff: u32,
+    apt_cutoff: u32,
+    started: bool,
+    failure: Option<u32>,
+    // Repetition count test: last sample and the length of its run
+    rct_sample: u8,
+    rct_count: u32,
+    // Adaptive proportion test: reference sample, its count and the number of
+    // samples seen in the current window (zero to start a new window)
+    apt_sample: u8,
+    apt_count: u32,
+    apt_seen: u32,
+}
+
+impl<R: RngCore> HealthTestedRng<R> {
+    /// Create a new `HealthTestedRng`, for a source with `min_entropy` bits
+    /// of min-entropy per byte.
+    ///
+    /// # Panics
+    ///
+    /// If `min_entropy` is not in the range `(0, 8]`.
+    pub fn new(rng: R, min_entropy: f64) -> Self {
+        assert!(
+            min_entropy > 0.0 && min_entropy <= 8.0,
+            "HealthTestedRng::new called with min_entropy not in (0, 8]"
+        );
+        HealthTestedRng {
+            rng,
+            rct_cutoff: rct_cutoff(min_entropy),
+            apt_cutoff: apt_cutoff(min_entropy),
+            started: false,
+            failure: None,
+            rct_sample: 0,
+            rct_count: 0,
+            apt_sample: 0,
+            apt_count: 0,
+            apt_seen: 0,
+        }
+    }
+
+    /// The cutoff of the repetition count test: the test fails when a sample
+    /// is repeated this many times in a row.
+    pub fn rct_cutoff(&self) -> u32 {
+        self.rct_cutoff
+    }
+
+    /// The cutoff of the adaptive proportion test: the test fails when the
+    /// first sample of a window occurs this many times within the window.
+    pub fn apt_cutoff(&self) -> u32 {
+        self.apt_cutoff
+    }
+
+    /// Clear a failure and the state of the tests.
+    ///
+    /// Start-up testing is repeated before the next output.
+    pub fn reset(&mut self) {
+        self.started = false;
+        self.failure = None;
+        self.rct_count = 0;
+        self.apt_seen = 0;
+    }
+
+    /// Unwrap the source.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+
+    fn check(&mut self, sample: u8) -> Result<(), u32> {
+        if self.rct_count > 0 && sample == self.rct_sample {
+            self.rct_count += 1;
+            if self.rct_count >= self.rct_cutoff {
+                return Err(Error::ENTROPY_REPETITION_COUNT_FAILURE);
+            }
+        } else {
+            self.rct_sample = sample;
+            self.rct_count = 1;
+        }
+
+        if self.apt_seen == 0 {
+            self.apt_sample = sample;
+            self.apt_count = 1;
+        } else if sample == self.apt_sample {
+            self.apt_count += 1;
+            if self.apt_count >= self.apt_cutoff {
+                return Err(Error::ENTROPY_ADAPTIVE_PROPORTION_FAILURE);
+            }
+        }
+        self.apt_seen += 1;
+        if self.apt_seen == APT_WINDOW {
+            self.apt_seen = 0;
+        }
+        Ok(())
+    }
+
+    fn check_all(&mut self, samples: &[u8]) -> Result<(), Error> {
+        if let Some(code) = self.failure {
+            return Err(NonZeroU32::new(code).unwrap().into());
+        }
+        for &sample in samples {
+            if let Err(code) = self.check(sample) {
+                self.failure = Some(code);
+                return Err(NonZeroU32::new(code).unwrap().into());
+            }
+        }
+        Ok(())
+    }
+
+    fn fill(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        if !self.started && self.failure.is_none() {
+            let mut startup = [0u8; STARTUP_SAMPLES];
+            self.rng.try_fill_bytes(&mut startup)?;
+            self.check_all(&startup)?;
+            self.started = true;
+        }
+        self.rng.try_fill_bytes(dest)?;
+        let result = self.check_all(dest);
+        if result.is_err() {
+            for b in dest.iter_mut() {
+                *b = 0;
+            }
+        }
+        result
+    }
+}
+
+// The repetition count test cutoff `1 + ⌈-log2(α) / H⌉`.
+fn rct_cutoff(min_entropy: f64) -> u32 {
+    1 + (f64::from(ALPHA_EXP) / min_entropy).ceil() as u32
+}
+
+// The adaptive proportion test cutoff: the smallest `c` such that
+// `P(X >= c) <= α` for `X ~ Binomial(W, 2^-H)`, i.e.
+// `1 + CRITBINOM(W, 2^-H, 1 - α)` in SP 800-90B.
+fn apt_cutoff(min_entropy: f64) -> u32 {
+    let p = (-min_entropy).exp2();
+    let ln_alpha = -f64::from(ALPHA_EXP) * core::f64::consts::LN_2;
+    let n = APT_WINDOW;
+    // Sum the upper tail of the distribution of `X ~ Binomial(n, p)` in log
+    // space, from `X = n` downwards, since the terms may underflow.
+    let ln_odds = ((1.0 - p) / p).ln();
+    let mut ln_pmf = f64::from(n) * p.ln();
+    let mut ln_tail = ln_pmf;
+    let mut k = n;
+    while k > 0 {
+        // P(X = k - 1) from P(X = k)
+        ln_pmf += f64::from(k).ln() - f64::from(n - k + 1).ln() + ln_odds;
+        let (hi, lo) = if ln_tail > ln_pmf { (ln_tail, ln_pmf) } else { (ln_pmf, ln_tail) };
+        let ln_next = hi + (lo - hi).exp().ln_1p();
+        if ln_next > ln_alpha {
+            break;
+        }
+        ln_tail = ln_next;
+        k -= 1;
+    }
+    k
+}
+
+impl<R: RngCore> RngCore for HealthTestedRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.fill(dest)
+            .unwrap_or_else(|err| panic!("HealthTestedRng: health test failed: {}", err));
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill(dest)
+    }
+}
+
+impl<R: RngCore + CryptoRng> CryptoRng for HealthTestedRng<R> {}
+
+#[cfg(test)]
+mod test {
+    use super::HealthTestedRng;
+    use crate::rngs::mock::StepRng;
+    use crate::{Error, RngCore};
+
+    // Alternates between zero and a counter, starting with zero: no runs, but
+    // zero is half of all samples
+    struct Biased(u8);
+
+    impl RngCore for Biased {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for b in dest.iter_mut() {
+                self.0 = self.0.wrapping_add(1);
+                *b = if self.0 % 2 == 1 { 0 } else { self.0 };
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    fn code(err: Error) -> Option<u32> {
+        err.code().map(|c| c.get())
+    }
+
+    #[test]
+    fn test_cutoffs() {
+        // Cutoffs for W = 512 from SP 800-90B, table 2
+        let rng = HealthTestedRng::new(StepRng::new(0, 1), 8.0);
+        assert_eq!((rng.rct_cutoff(), rng.apt_cutoff()), (4, 13));
+        let rng = HealthTestedRng::new(StepRng::new(0, 1), 1.0);
+        assert_eq!((rng.rct_cutoff(), rng.apt_cutoff()), (21, 311));
+        let rng = HealthTestedRng::new(StepRng::new(0, 1), 0.5);
+        assert_eq!((rng.rct_cutoff(), rng.apt_cutoff()), (41, 410));
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        let mut rng = HealthTestedRng::new(StepRng::new(0, 0), 8.0);
+        let mut buf = [1u8; 16];
+        let err = rng.try_fill_bytes(&mut buf).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_REPETITION_COUNT_FAILURE));
+
+        // The failure persists until reset
+        let err = rng.try_fill_bytes(&mut buf).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_REPETITION_COUNT_FAILURE));
+        assert_eq!(buf, [0u8; 16]);
+        rng.reset();
+        assert!(rng.try_fill_bytes(&mut buf).is_err());
+
+        // Each `u64` is a run of 8 equal bytes, which passes with the cutoff 11
+        let step = 0x0101_0101_0101_0101;
+        let mut rng = HealthTestedRng::new(StepRng::new(0, step), 2.0);
+        assert!(rng.try_fill_bytes(&mut [0u8; 4096]).is_ok());
+        let mut rng = HealthTestedRng::new(StepRng::new(0, step), 8.0);
+        let err = rng.try_fill_bytes(&mut [0u8; 16]).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_REPETITION_COUNT_FAILURE));
+    }
+
+    #[test]
+    fn test_adaptive_proportion() {
+        let mut rng = HealthTestedRng::new(Biased(0), 8.0);
+        let err = rng.try_fill_bytes(&mut [0u8; 16]).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_ADAPTIVE_PROPORTION_FAILURE));
+
+        // With a low enough entropy assessment, the source passes
+        let mut rng = HealthTestedRng::new(Biased(0), 0.5);
+        assert!(rng.try_fill_bytes(&mut [0u8; 4096]).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "std_rng")]
+    fn test_healthy_source() {
+        use crate::rngs::StdRng;
+        use crate::SeedableRng;
+
+        let mut rng = HealthTestedRng::new(StdRng::seed_from_u64(1), 8.0);
+        let mut buf = [0u8; 1000];
+        for _ in 0..100 {
+            rng.fill_bytes(&mut buf);
+        }
+        assert!(buf.iter().any(|&x| x != 0));
+
+        // Output is passed through after start-up testing
+        let mut src = StdRng::seed_from_u64(1);
+        let mut rng = HealthTestedRng::new(src.clone(), 8.0);
+        src.fill_bytes(&mut [0u8; 1024]);
+        assert_eq!(rng.next_u64(), src.next_u64());
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An adapter mixing several sources of entropy.
+
+use core::num::NonZeroU32;
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+use std::boxed::Box;
+use std::fmt;
+use std::io::Read;
+use std::vec::Vec;
+
+use rand_chacha::ChaCha20Rng;
+use rand_core::entropy::EntropySource;
+use rand_core::{impls, CryptoRng, Error, RngCore, SeedableRng};
+
+use super::{zeroize, HealthTestedRng, ReadRng};
+
+// Number of bytes read from each source per request.
+const SAMPLE_LEN: usize = 32;
+
+// Stream used to derive the output and the next pool key. Sources use the
+// streams starting at 1.
+const OUTPUT_STREAM: u64 = 0;
+
+type Source = HealthTestedRng<Box<dyn RngCore + Send>>;
+
+/// An RNG combining the output of several entropy sources.
+///
+/// For each request, `EntropyMixer` reads 32 bytes from each of its sources
+/// and absorbs them into a 256-bit pool key with a ChaCha20-based extractor:
+/// each sample is XORed into the key, which is then replaced by the ChaCha20
+/// keystream under the result. The output, and the pool key for the next
+/// request, are taken from the ChaCha20 keystream under the final key.
+/// As long as one source provides secret, unpredictable samples (now or in an
+/// earlier request), the output is unpredictable, however biased or malicious
+/// the other sources are.
+///
+/// # Health checks
+///
+/// Each source is wrapped in a [`HealthTestedRng`], which runs the continuous
+/// health tests of NIST SP 800-90B on its samples, given the min-entropy
+/// assessment passed when adding the source. A source which fails a test
+/// keeps failing with the same error. Errors of a source are also treated as
+/// failures. Failed samples are not absorbed.
+///
+/// By default, a request fails if any source fails, with the error of the
+/// first failing source. [`with_min_healthy`] allows degraded operation as
+/// long as a minimum number of sources are healthy; the number of failures is
+/// reported by [`health_failures`].
+///
+/// # Performance
+///
+/// Every request reads from all sources, so this adapter is slow for small
+/// requests. It is meant for seeding other generators, e.g. via
+/// [`SeedableRng::from_rng`], or as the reseeder of [`ReseedingRng`].
+///
+/// # Example
+///
+/// ```
+/// use rand::rngs::adapter::EntropyMixer;
+/// use rand::rngs::{OsRng, StdRng};
+/// use rand::SeedableRng;
+///
+/// let mut mixer = EntropyMixer::new().with_source(OsRng, 8.0);
+/// if let Ok(file) = std::fs::File::open("/dev/urandom") {
+///     mixer = mixer.with_reader(file, 8.0).with_min_healthy(1);
+/// }
+/// let rng = StdRng::from_rng(&mut mixer).unwrap();
+/// ```
+///
+/// [`with_min_healthy`]: EntropyMixer::with_min_healthy
+/// [`health_failures`]: EntropyMixer::health_failures
+/// [`SeedableRng::from_rng`]: rand_core::SeedableRng::from_rng
+/// [`ReseedingRng`]: crate::rngs::adapter::ReseedingRng
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std_rng")))]
+pub struct EntropyMixer {
+    sources: Vec<Source>,
+    min_healthy: Option<usize>,
+    pool: [u8; 32],
+    health_failures: u64,
+}
+
+impl EntropyMixer {
+    /// Create a new `EntropyMixer` without sources.
+    ///
+    /// Requests fail with [`Error::ENTROPY_UNAVAILABLE`] until a source is
+    /// added.
+    pub fn new() -> Self {
+        EntropyMixer {
+            sources: Vec::new(),
+            min_healthy: None,
+            pool: [0; 32],
+            health_failures: 0,
+        }
+    }
+
+    /// Add a source with `min_entropy` bits of min-entropy per byte.
+    ///
+    /// # Panics
+    ///
+    /// If `min_entropy` is not in the range `(0, 8]`.
+    pub fn with_source<R: RngCore + Send + 'static>(mut self, rng: R, min_entropy: f64) -> Self {
+        let rng: Box<dyn RngCore + Send> = Box::new(rng);
+        self.sources.push(HealthTestedRng::new(rng, min_entropy));
+        self
+    }
+
+    /// Add a source reading from `reader`, via [`ReadRng`], with
+    /// `min_entropy` bits of min-entropy per byte.
+    ///
+    /// # Panics
+    ///
+    /// If `min_entropy` is not in the range `(0, 8]`.
+    pub fn with_reader<T: Read + Send + 'static>(self, reader: T, min_entropy: f64) -> Self {
+        self.with_source(ReadRng::new(reader), min_entropy)
+    }
+
+    /// Allow requests to succeed if at least `min` sources are healthy.
+    ///
+    /// By default, all sources must be healthy.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is zero.
+    pub fn with_min_healthy(mut self, min: usize) -> Self {
+        assert!(min > 0, "EntropyMixer: min_healthy must be at least 1");
+        self.min_healthy = Some(min);
+        self
+    }
+
+    /// The number of sources.
+    pub fn num_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// The total number of failed source requests, over all sources.
+    ///
+    /// Each request reads one sample from every source; a source whose read
+    /// fails, e.g. because a health test failed, counts once.
+    pub fn health_failures(&self) -> u64 {
+        self.health_failures
+    }
+
+    fn mix(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let min_healthy = self.min_healthy.unwrap_or(self.sources.len());
+        let mut key = self.pool;
+        let mut healthy = 0;
+        let mut first_err = None;
+        let mut sample = [0; SAMPLE_LEN];
+        for (i, source) in self.sources.iter_mut().enumerate() {
+            match source.try_fill_bytes(&mut sample) {
+                Ok(()) => {
+                    absorb(&mut key, &sample, i as u64 + 1);
+                    healthy += 1;
+                }
+                Err(err) => {
+                    self.health_failures += 1;
+                    first_err = first_err.or(Some(err));
+                }
+            }
+        }
+        zeroize(&mut sample);
+        if healthy == 0 || healthy < min_healthy {
+            zeroize(&mut key);
+            return Err(first_err.unwrap_or_else(|| {
+                NonZeroU32::new(Error::ENTROPY_UNAVAILABLE).unwrap().into()
+            }));
+        }
+
+        let mut output = ChaCha20Rng::from_seed(key);
+        zeroize(&mut key);
+        output.set_stream(OUTPUT_STREAM);
+        output.fill_bytes(&mut self.pool);
+        output.fill_bytes(dest);
+        wipe(&mut output);
+        Ok(())
+    }
+}
+
+// Absorb `sample` into `key`, separating sources by `stream`.
+fn absorb(key: &mut [u8; 32], sample: &[u8; SAMPLE_LEN], stream: u64) {
+    for (k, s) in key.iter_mut().zip(sample.iter()) {
+        *k ^= s;
+    }
+    let mut rng = ChaCha20Rng::from_seed(*key);
+    rng.set_stream(stream);
+    rng.fill_bytes(key);
+    wipe(&mut rng);
+}
+
+// Overwrite the key and buffered keystream of `rng`.
+fn wipe(rng: &mut ChaCha20Rng) {
+    // SAFETY: `rng` is valid for writes, and the old value needs no drop
+    unsafe { ptr::write_volatile(rng, ChaCha20Rng::from_seed([0; 32])) }
+    compiler_fence(Ordering::SeqCst);
+}
+
+impl Drop for EntropyMixer {
+    fn drop(&mut self) {
+        zeroize(&mut self.pool);
+    }
+}
+
+impl Default for EntropyMixer {
+    fn default() -> Self {
+        EntropyMixer::new()
+    }
+}
+
+// Custom Debug implementation that does not expose the internal state
+impl fmt::Debug for EntropyMixer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EntropyMixer")
+            .field("num_sources", &self.sources.len())
+            .field("min_healthy", &self.min_healthy)
+            .field("health_failures", &self.health_failures)
+            .finish()
+    }
+}
+
+impl EntropySource for EntropyMixer {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.mix(dest)
+    }
+}
+
+impl RngCore for EntropyMixer {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.mix(dest).unwrap_or_else(|err| panic!("EntropyMixer failed: {}", err));
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.mix(dest)
+    }
+}
+
+impl CryptoRng for EntropyMixer {}
+
+#[cfg(test)]
+mod test {
+    use super::EntropyMixer;
+    use crate::rngs::mock::StepRng;
+    use crate::rngs::StdRng;
+    use crate::{Error, RngCore, SeedableRng};
+
+    fn code(err: Error) -> Option<u32> {
+        err.code().map(|c| c.get())
+    }
+
+    #[test]
+    fn test_mixer_deterministic() {
+        let new_mixer = || {
+            EntropyMixer::new()
+                .with_source(StdRng::seed_from_u64(1), 8.0)
+                .with_source(StdRng::seed_from_u64(2), 8.0)
+        };
+        let mut mixer1 = new_mixer();
+        let mut mixer2 = new_mixer();
+        let mut buf1 = [0u8; 100];
+        let mut buf2 = [0u8; 100];
+        mixer1.fill_bytes(&mut buf1);
+        mixer2.fill_bytes(&mut buf2);
+        assert_eq!(&buf1[..], &buf2[..]);
+
+        // The pool changes the output
+        let mut buf3 = [0u8; 100];
+        mixer1.fill_bytes(&mut buf3);
+        assert_ne!(&buf1[..], &buf3[..]);
+
+        // Every source affects the output
+        let mut mixer3 = EntropyMixer::new()
+            .with_source(StdRng::seed_from_u64(1), 8.0)
+            .with_source(StdRng::seed_from_u64(3), 8.0);
+        mixer3.fill_bytes(&mut buf3);
+        assert_ne!(&buf1[..], &buf3[..]);
+        assert_eq!(mixer1.health_failures(), 0);
+    }
+
+    #[test]
+    fn test_mixer_health_checks() {
+        // A stuck source fails the repetition count test
+        let mut mixer = EntropyMixer::new()
+            .with_source(StdRng::seed_from_u64(1), 8.0)
+            .with_source(StepRng::new(0, 0), 8.0);
+        let err = mixer.try_fill_bytes(&mut [0u8; 8]).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_REPETITION_COUNT_FAILURE));
+
+        // Runs of 8 equal bytes only pass with a low entropy assessment
+        let step = 0x0101_0101_0101_0101;
+        let mut mixer = EntropyMixer::new().with_source(StepRng::new(0, step), 8.0);
+        assert!(mixer.try_fill_bytes(&mut [0u8; 8]).is_err());
+        let mut mixer = EntropyMixer::new().with_source(StepRng::new(0, step), 2.0);
+        assert!(mixer.try_fill_bytes(&mut [0u8; 8]).is_ok());
+
+        // Degraded operation with one healthy source; the failing source
+        // keeps failing
+        let mut mixer = EntropyMixer::new()
+            .with_source(StepRng::new(0, 0), 8.0)
+            .with_source(StdRng::seed_from_u64(1), 8.0)
+            .with_min_healthy(1);
+        for _ in 0..3 {
+            assert!(mixer.try_fill_bytes(&mut [0u8; 8]).is_ok());
+        }
+        assert_eq!(mixer.health_failures(), 3);
+    }
+
+    #[test]
+    fn test_mixer_source_errors() {
+        // Source errors are passed through
+        let mut mixer = EntropyMixer::new()
+            .with_source(StdRng::seed_from_u64(1), 8.0)
+            .with_reader(&[1u8, 2, 3][..], 8.0);
+        let err = mixer.try_fill_bytes(&mut [0u8; 8]).unwrap_err();
+        assert_eq!(code(err), None);
+
+        let mut mixer = EntropyMixer::new();
+        let err = mixer.try_fill_bytes(&mut [0u8; 8]).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_UNAVAILABLE));
+
+        // More healthy sources are required than exist
+        let mut mixer = EntropyMixer::new()
+            .with_source(StdRng::seed_from_u64(1), 8.0)
+            .with_min_healthy(2);
+        let err = mixer.try_fill_bytes(&mut [0u8; 8]).unwrap_err();
+        assert_eq!(code(err), Some(Error::ENTROPY_UNAVAILABLE));
+    }
+}
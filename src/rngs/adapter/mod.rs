@@ -8,8 +8,29 @@
 
 //! Wrappers / adapters forming RNGs
 
+mod buffered;
+mod counting;
+mod fork;
+mod health;
+#[cfg(feature = "std_rng")] mod mixer;
 mod read;
 mod reseeding;
 
+#[cfg(feature = "std_rng")] pub use self::mixer::EntropyMixer;
+pub use self::buffered::BufferedRng;
+pub use self::counting::{CountingRng, RngUsage};
+pub use self::fork::{fork_epoch, ForkGuard};
+pub use self::health::HealthTestedRng;
 pub use self::read::{ReadError, ReadRng};
-pub use self::reseeding::ReseedingRng;
+pub use self::reseeding::{ReseedStats, ReseedingRng};
+
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+// Zero `buf` using volatile writes, which the compiler may not remove.
+fn zeroize(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
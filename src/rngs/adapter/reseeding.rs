@@ -10,11 +10,17 @@
 //! A wrapper around another PRNG that reseeds it after it
 //! generates a certain number of random bytes.
 
+use core::cmp::min;
+use core::fmt;
 use core::mem::size_of;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use rand_core::block::{BlockRng, BlockRngCore};
 use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
 
+use super::fork;
+
 /// A wrapper around any PRNG that implements [`BlockRngCore`], that adds the
 /// ability to reseed it.
 ///
@@ -27,6 +33,8 @@ use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
 ///   underlying PRNG. For ChaCha and Hc128 this is a maximum of
 ///   15 `u32` values before reseeding.
 /// - After the PRNG has generated a configurable number of random bytes.
+/// - Optionally, according to the policies configured with
+///   [`with_max_age`], [`with_reseed_every`] and [`with_reseed_predicate`].
 ///
 /// # When should reseeding after a fixed number of generated bytes be used?
 ///
@@ -43,13 +51,37 @@ use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
 /// Use [`ReseedingRng::new`] with a `threshold` of `0` to disable reseeding
 /// after a fixed number of generated bytes.
 ///
+/// # Reseed policies
+///
+/// The byte threshold is checked whenever the wrapped PRNG generates a new
+/// block. The additional policies are checked at the start of every call to
+/// one of the [`RngCore`] methods, and when due the PRNG is reseeded before
+/// any further output is produced, discarding the remaining buffered values:
+///
+/// - [`with_max_age`] bounds the wall-clock time since the last reseed.
+/// - [`with_reseed_every`] bounds the number of calls since the last reseed.
+/// - [`with_reseed_predicate`] reseeds whenever a callback, given the current
+///   [`ReseedStats`], returns `true`.
+///
+/// These checks only cost a branch when no policy is configured.
+///
+/// # Choice of reseeder
+///
+/// Any RNG may be used as the reseeder, but it should normally be a source of
+/// entropy, such as [`OsRng`] or one of the sources in [`rand_core::entropy`].
+/// [`DefaultEntropy`] follows the process-wide default source, and
+/// [`TestEntropy`] makes reseeding deterministic in tests.
+///
 /// # Error handling
 ///
 /// Although unlikely, reseeding the wrapped PRNG can fail. `ReseedingRng` will
 /// never panic but try to handle the error intelligently through some
 /// combination of retrying and delaying reseeding until later.
 /// If handling the source error fails `ReseedingRng` will continue generating
-/// data from the wrapped PRNG without reseeding.
+/// data from the wrapped PRNG without reseeding. A reseed required by one of
+/// the reseed policies is retried on every following call until it succeeds.
+/// The error of the last failed automatic reseed is available from
+/// [`last_reseed_error`].
 ///
 /// Manually calling [`reseed()`] will not have this retry or delay logic, but
 /// reports the error.
@@ -73,8 +105,15 @@ use rand_core::{CryptoRng, Error, RngCore, SeedableRng};
 /// ```
 ///
 /// [`BlockRngCore`]: rand_core::block::BlockRngCore
+/// [`OsRng`]: crate::rngs::OsRng
+/// [`DefaultEntropy`]: rand_core::entropy::DefaultEntropy
+/// [`TestEntropy`]: rand_core::entropy::TestEntropy
 /// [`ReseedingRng::new`]: ReseedingRng::new
 /// [`reseed()`]: ReseedingRng::reseed
+/// [`with_max_age`]: ReseedingRng::with_max_age
+/// [`with_reseed_every`]: ReseedingRng::with_reseed_every
+/// [`with_reseed_predicate`]: ReseedingRng::with_reseed_predicate
+/// [`last_reseed_error`]: ReseedingRng::last_reseed_error
 #[derive(Debug)]
 pub struct ReseedingRng<R, Rsdr>(BlockRng<ReseedingCore<R, Rsdr>>)
 where
@@ -96,10 +135,162 @@ where
         ReseedingRng(BlockRng::new(ReseedingCore::new(rng, threshold, reseeder)))
     }
 
+    /// Reseed the PRNG when more than `max_age` has passed since the last
+    /// reseed.
+    ///
+    /// Without this policy the clock is never read, as `Instant::now` is not
+    /// available on every platform.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.0.core.policy.max_age = Some(max_age);
+        if self.0.core.stats.last_reseed.is_none() {
+            self.0.core.stats.last_reseed = Some(Instant::now());
+        }
+        self
+    }
+
+    /// Reseed the PRNG after every `calls` calls to one of the [`RngCore`]
+    /// methods. Set it to zero to never reseed based on the number of calls.
+    pub fn with_reseed_every(mut self, calls: u64) -> Self {
+        self.0.core.policy.calls = calls;
+        self
+    }
+
+    /// Reseed the PRNG whenever `predicate` returns `true`.
+    ///
+    /// The predicate is evaluated at the start of every call to one of the
+    /// [`RngCore`] methods, so it should be cheap.
+    pub fn with_reseed_predicate<F>(mut self, predicate: F) -> Self
+    where F: Fn(&ReseedStats) -> bool + Send + Sync + 'static {
+        self.0.core.policy.predicate = Some(ReseedPredicate(Arc::new(predicate)));
+        self
+    }
+
     /// Reseed the internal PRNG.
     pub fn reseed(&mut self) -> Result<(), Error> {
         self.0.core.reseed()
     }
+
+    /// Statistics since the last successful reseed.
+    pub fn stats(&self) -> ReseedStats {
+        self.0.core.stats
+    }
+
+    /// The error of the last failed automatic reseed, or `None` if there was
+    /// none or the PRNG has been reseeded successfully since.
+    ///
+    /// Errors of manual calls to [`reseed()`] are returned to the caller
+    /// instead.
+    ///
+    /// [`reseed()`]: ReseedingRng::reseed
+    pub fn last_reseed_error(&self) -> Option<&Error> {
+        self.0.core.last_error.as_ref()
+    }
+
+    // Check the reseed policies at the start of a call.
+    #[inline(always)]
+    fn check_policies(&mut self) {
+        if self.0.core.policy.is_enabled() {
+            self.check_policies_slow();
+        }
+    }
+
+    #[inline(never)]
+    fn check_policies_slow(&mut self) {
+        let core = &mut self.0.core;
+        if core.policy.is_due(&core.stats, core.calls_since_reseed) {
+            trace!("Reseeding RNG (reseed policy)");
+            core.auto_reseed();
+            // Don't return values generated before the reseed.
+            self.0.reset();
+        }
+        self.0.core.calls_since_reseed += 1;
+    }
+}
+
+/// Statistics of a [`ReseedingRng`], as returned by [`ReseedingRng::stats`].
+#[derive(Clone, Copy, Debug)]
+pub struct ReseedStats {
+    bytes_since_reseed: u64,
+    reseed_count: u64,
+    last_reseed: Option<Instant>,
+}
+
+impl ReseedStats {
+    fn new() -> Self {
+        ReseedStats {
+            bytes_since_reseed: 0,
+            reseed_count: 0,
+            last_reseed: None,
+        }
+    }
+
+    /// The number of bytes generated by the wrapped PRNG since the last
+    /// successful reseed, including values that are still buffered.
+    pub fn bytes_since_reseed(&self) -> u64 {
+        self.bytes_since_reseed
+    }
+
+    /// The number of successful reseeds, not counting the initial seed.
+    pub fn reseed_count(&self) -> u64 {
+        self.reseed_count
+    }
+
+    /// The time of the last successful reseed, or of setting the
+    /// [`with_max_age`] policy if there has been none.
+    ///
+    /// The time is only recorded with a [`with_max_age`] policy; otherwise
+    /// this is `None`.
+    ///
+    /// [`with_max_age`]: ReseedingRng::with_max_age
+    pub fn last_reseed(&self) -> Option<Instant> {
+        self.last_reseed
+    }
+
+    /// The time elapsed since [`last_reseed`](ReseedStats::last_reseed), or
+    /// `None` if it is not recorded.
+    pub fn time_since_reseed(&self) -> Option<Duration> {
+        self.last_reseed.map(|time| time.elapsed())
+    }
+}
+
+#[derive(Clone)]
+struct ReseedPredicate(Arc<dyn Fn(&ReseedStats) -> bool + Send + Sync>);
+
+impl fmt::Debug for ReseedPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ReseedPredicate { .. }")
+    }
+}
+
+// Reseed policies checked on every call, in addition to the byte threshold.
+#[derive(Clone, Debug, Default)]
+struct ReseedPolicy {
+    max_age: Option<Duration>,
+    calls: u64,
+    predicate: Option<ReseedPredicate>,
+}
+
+impl ReseedPolicy {
+    #[inline(always)]
+    fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.calls != 0 || self.predicate.is_some()
+    }
+
+    fn is_due(&self, stats: &ReseedStats, calls_since_reseed: u64) -> bool {
+        if self.calls != 0 && calls_since_reseed >= self.calls {
+            return true;
+        }
+        if let Some(max_age) = self.max_age {
+            match stats.time_since_reseed() {
+                Some(age) if age >= max_age => return true,
+                _ => {}
+            }
+        }
+        match self.predicate {
+            Some(ReseedPredicate(ref predicate)) => predicate(stats),
+            None => false,
+        }
+    }
 }
 
 // TODO: this should be implemented for any type where the inner type
@@ -111,19 +302,23 @@ where
 {
     #[inline(always)]
     fn next_u32(&mut self) -> u32 {
+        self.check_policies();
         self.0.next_u32()
     }
 
     #[inline(always)]
     fn next_u64(&mut self) -> u64 {
+        self.check_policies();
         self.0.next_u64()
     }
 
     fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.check_policies();
         self.0.fill_bytes(dest)
     }
 
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.check_policies();
         self.0.try_fill_bytes(dest)
     }
 }
@@ -154,6 +349,10 @@ struct ReseedingCore<R, Rsdr> {
     threshold: i64,
     bytes_until_reseed: i64,
     fork_counter: usize,
+    policy: ReseedPolicy,
+    stats: ReseedStats,
+    calls_since_reseed: u64,
+    last_error: Option<Error>,
 }
 
 impl<R, Rsdr> BlockRngCore for ReseedingCore<R, Rsdr>
@@ -174,8 +373,23 @@ where
         }
         let num_bytes = results.as_ref().len() * size_of::<Self::Item>();
         self.bytes_until_reseed -= num_bytes as i64;
+        self.stats.bytes_since_reseed += num_bytes as u64;
         self.inner.generate(results);
     }
+
+    fn generate_into(&mut self, dest: &mut [u8]) -> usize {
+        if self.bytes_until_reseed <= 0 || self.is_forked(fork::get_fork_counter()) {
+            // Leave reseeding to `generate`.
+            return 0;
+        }
+        // Only write the blocks which `generate` would produce before the
+        // next reseed; the block crossing the threshold is left to `generate`.
+        let len = min(dest.len() as u64, self.bytes_until_reseed as u64) as usize;
+        let num_bytes = self.inner.generate_into(&mut dest[..len]);
+        self.bytes_until_reseed -= num_bytes as i64;
+        self.stats.bytes_since_reseed += num_bytes as u64;
+        num_bytes
+    }
 }
 
 impl<R, Rsdr> ReseedingCore<R, Rsdr>
@@ -206,6 +420,10 @@ where
             threshold: threshold as i64,
             bytes_until_reseed: threshold as i64,
             fork_counter: 0,
+            policy: ReseedPolicy::default(),
+            stats: ReseedStats::new(),
+            calls_since_reseed: 0,
+            last_error: None,
         }
     }
 
@@ -213,10 +431,25 @@ where
     fn reseed(&mut self) -> Result<(), Error> {
         R::from_rng(&mut self.reseeder).map(|result| {
             self.bytes_until_reseed = self.threshold;
-            self.inner = result
+            self.inner = result;
+            self.stats.bytes_since_reseed = 0;
+            self.stats.reseed_count += 1;
+            if self.policy.max_age.is_some() {
+                self.stats.last_reseed = Some(Instant::now());
+            }
+            self.calls_since_reseed = 0;
+            self.last_error = None;
         })
     }
 
+    /// Reseed the internal PRNG, recording instead of returning any error.
+    fn auto_reseed(&mut self) {
+        if let Err(e) = self.reseed() {
+            warn!("Reseeding RNG failed: {}", e);
+            self.last_error = Some(e);
+        }
+    }
+
     fn is_forked(&self, global_fork_counter: usize) -> bool {
         // In theory, on 32-bit platforms, it is possible for
         // `global_fork_counter` to wrap around after ~4e9 forks.
@@ -244,13 +477,11 @@ where
 
         let num_bytes = results.as_ref().len() * size_of::<<R as BlockRngCore>::Item>();
 
-        if let Err(e) = self.reseed() {
-            warn!("Reseeding RNG failed: {}", e);
-            let _ = e;
-        }
+        self.auto_reseed();
         self.fork_counter = global_fork_counter;
 
         self.bytes_until_reseed = self.threshold - num_bytes as i64;
+        self.stats.bytes_since_reseed += num_bytes as u64;
         self.inner.generate(results);
     }
 }
@@ -267,6 +498,10 @@ where
             threshold: self.threshold,
             bytes_until_reseed: 0, // reseed clone on first use
             fork_counter: self.fork_counter,
+            policy: self.policy.clone(),
+            stats: self.stats,
+            calls_since_reseed: self.calls_since_reseed,
+            last_error: None,
         }
     }
 }
@@ -279,51 +514,6 @@ where
 }
 
 
-#[cfg(all(unix, not(target_os = "emscripten")))]
-mod fork {
-    use core::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Once;
-
-    // Fork protection
-    //
-    // We implement fork protection on Unix using `pthread_atfork`.
-    // When the process is forked, we increment `RESEEDING_RNG_FORK_COUNTER`.
-    // Every `ReseedingRng` stores the last known value of the static in
-    // `fork_counter`. If the cached `fork_counter` is less than
-    // `RESEEDING_RNG_FORK_COUNTER`, it is time to reseed this RNG.
-    //
-    // If reseeding fails, we don't deal with this by setting a delay, but just
-    // don't update `fork_counter`, so a reseed is attempted as soon as
-    // possible.
-
-    static RESEEDING_RNG_FORK_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-    pub fn get_fork_counter() -> usize {
-        RESEEDING_RNG_FORK_COUNTER.load(Ordering::Relaxed)
-    }
-
-    extern "C" fn fork_handler() {
-        // Note: fetch_add is defined to wrap on overflow
-        // (which is what we want).
-        RESEEDING_RNG_FORK_COUNTER.fetch_add(1, Ordering::Relaxed);
-    }
-
-    pub fn register_fork_handler() {
-        static REGISTER: Once = Once::new();
-        REGISTER.call_once(|| unsafe {
-            libc::pthread_atfork(None, None, Some(fork_handler));
-        });
-    }
-}
-
-#[cfg(not(all(unix, not(target_os = "emscripten"))))]
-mod fork {
-    pub fn get_fork_counter() -> usize {
-        0
-    }
-    pub fn register_fork_handler() {}
-}
-
 
 #[cfg(feature = "std_rng")]
 #[cfg(test)]
@@ -331,7 +521,7 @@ mod test {
     use super::ReseedingRng;
     use crate::rngs::mock::StepRng;
     use crate::rngs::std::Core;
-    use crate::{Rng, SeedableRng};
+    use crate::{Rng, RngCore, SeedableRng};
 
     #[test]
     fn test_reseeding() {
@@ -353,6 +543,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_reseeding_fill_bytes() {
+        // Large `fill_bytes` requests write whole blocks directly to the
+        // destination; this must not change the output or reseeding points.
+        let new_rng = || {
+            let mut reseeder = StepRng::new(0, 1);
+            let rng = Core::from_rng(&mut reseeder).unwrap();
+            ReseedingRng::new(rng, 1000, reseeder)
+        };
+        let mut rng1 = new_rng();
+        let mut rng2 = new_rng();
+
+        let mut bulk = [0u8; 10_000];
+        rng1.fill_bytes(&mut bulk);
+        for chunk in bulk.chunks(4) {
+            assert_eq!(chunk, &rng2.next_u32().to_le_bytes()[..]);
+        }
+        assert_eq!(rng1.next_u64(), rng2.next_u64());
+    }
+
     #[test]
     fn test_clone_reseeding() {
         let mut zero = StepRng::new(0, 0);
@@ -367,4 +577,147 @@ mod test {
         let mut rng2 = rng1.clone();
         assert_eq!(first, rng2.gen::<u32>());
     }
+
+    #[test]
+    fn test_reseeding_entropy_source() {
+        use crate::entropy::TestEntropy;
+        use crate::Error;
+
+        let new_rng = |source: TestEntropy| {
+            let mut source = source;
+            let rng = Core::from_entropy_source(&mut source).unwrap();
+            ReseedingRng::new(rng, 256, source)
+        };
+
+        // Reseeding from the same deterministic source is reproducible
+        let mut rng1 = new_rng(TestEntropy::new(42));
+        let mut rng2 = new_rng(TestEntropy::new(42));
+        for _ in 0..1000 {
+            assert_eq!(rng1.next_u64(), rng2.next_u64());
+        }
+        let mut rng3 = new_rng(TestEntropy::new(43));
+        assert_ne!(rng1.next_u64(), rng3.next_u64());
+
+        // Enough for the initial seed and one reseed, but not a second
+        let mut rng = new_rng(TestEntropy::new(42).with_limit(64));
+        assert!(rng.reseed().is_ok());
+        let err = rng.reseed().unwrap_err();
+        assert_eq!(err.code().unwrap().get(), Error::ENTROPY_FAILURE);
+        // Automatic reseeding fails silently and the PRNG keeps going
+        let mut buf = [0u8; 4096];
+        rng.fill_bytes(&mut buf);
+        assert!(buf.iter().any(|&x| x != 0));
+    }
+
+    #[test]
+    fn test_reseeding_stats() {
+        let mut zero = StepRng::new(0, 0);
+        let rng = Core::from_rng(&mut zero).unwrap();
+        let mut reseeding = ReseedingRng::new(rng, 1024, zero);
+        assert_eq!(reseeding.stats().reseed_count(), 0);
+        assert_eq!(reseeding.stats().bytes_since_reseed(), 0);
+
+        // The first value generates a block of 64 `u32`
+        reseeding.next_u32();
+        assert_eq!(reseeding.stats().bytes_since_reseed(), 256);
+        for _ in 0..(4 * 64) {
+            reseeding.next_u32();
+        }
+        // Reseeded on the 5th block
+        assert_eq!(reseeding.stats().reseed_count(), 1);
+        assert_eq!(reseeding.stats().bytes_since_reseed(), 256);
+
+        assert!(reseeding.reseed().is_ok());
+        assert_eq!(reseeding.stats().reseed_count(), 2);
+        assert_eq!(reseeding.stats().bytes_since_reseed(), 0);
+        assert!(reseeding.last_reseed_error().is_none());
+        // The clock is only read with a `max_age` policy
+        assert!(reseeding.stats().last_reseed().is_none());
+    }
+
+    #[test]
+    fn test_reseed_every() {
+        use crate::entropy::TestEntropy;
+        use rand_core::block::BlockRngCore;
+
+        let mut source = TestEntropy::new(42);
+        let rng = Core::from_entropy_source(&mut source).unwrap();
+        let mut reseeding = ReseedingRng::new(rng, 0, source).with_reseed_every(3);
+
+        let mut reference = Core::from_entropy_source(&mut TestEntropy::new(42)).unwrap();
+        let mut block = Default::default();
+        reference.generate(&mut block);
+        let first: &[u32] = block.as_ref();
+        for i in 0..3 {
+            assert_eq!(reseeding.next_u32(), first[i]);
+        }
+        // The 4th call is served from a reseeded PRNG
+        assert_ne!(reseeding.next_u32(), first[3]);
+        assert_eq!(reseeding.stats().reseed_count(), 1);
+        for _ in 0..8 {
+            reseeding.next_u64();
+        }
+        assert_eq!(reseeding.stats().reseed_count(), 3);
+    }
+
+    #[test]
+    fn test_reseed_max_age() {
+        use std::time::Duration;
+
+        let mut zero = StepRng::new(0, 0);
+        let rng = Core::from_rng(&mut zero).unwrap();
+        let mut reseeding =
+            ReseedingRng::new(rng, 0, zero.clone()).with_max_age(Duration::from_secs(3600));
+        for _ in 0..100 {
+            reseeding.next_u32();
+        }
+        assert_eq!(reseeding.stats().reseed_count(), 0);
+
+        let rng = Core::from_rng(&mut zero).unwrap();
+        let mut reseeding = ReseedingRng::new(rng, 0, zero).with_max_age(Duration::from_secs(0));
+        for _ in 0..10 {
+            reseeding.next_u32();
+        }
+        assert_eq!(reseeding.stats().reseed_count(), 10);
+        assert!(reseeding.stats().time_since_reseed().unwrap() < Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_reseed_predicate() {
+        use crate::Error;
+
+        let mut zero = StepRng::new(0, 0);
+        let rng = Core::from_rng(&mut zero).unwrap();
+        let mut reseeding = ReseedingRng::new(rng, 0, zero)
+            .with_reseed_predicate(|stats| stats.bytes_since_reseed() >= 512);
+        // The second block is generated by the 65th call, so the predicate
+        // is true at the start of the 66th call
+        for _ in 0..65 {
+            reseeding.next_u32();
+        }
+        assert_eq!(reseeding.stats().reseed_count(), 0);
+        assert_eq!(reseeding.stats().bytes_since_reseed(), 512);
+        reseeding.next_u32();
+        assert_eq!(reseeding.stats().reseed_count(), 1);
+        assert_eq!(reseeding.stats().bytes_since_reseed(), 256);
+
+        // A failing reseed is recorded and retried on the next call
+        struct Failing;
+        impl RngCore for Failing {
+            fn next_u32(&mut self) -> u32 { panic!("Failing mock: not called") }
+            fn next_u64(&mut self) -> u64 { panic!("Failing mock: not called") }
+            fn fill_bytes(&mut self, _: &mut [u8]) { panic!("Failing mock: not called") }
+            fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), Error> {
+                Err(Error::from(core::num::NonZeroU32::new(Error::ENTROPY_FAILURE).unwrap()))
+            }
+        }
+        let rng = Core::from_rng(StepRng::new(0, 0)).unwrap();
+        let mut reseeding = ReseedingRng::new(rng, 0, Failing).with_reseed_predicate(|_| true);
+        assert!(reseeding.last_reseed_error().is_none());
+        reseeding.next_u32();
+        reseeding.next_u32();
+        let err = reseeding.last_reseed_error().unwrap();
+        assert_eq!(err.code().unwrap().get(), Error::ENTROPY_FAILURE);
+        assert_eq!(reseeding.stats().reseed_count(), 0);
+    }
 }
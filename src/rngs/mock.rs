@@ -6,12 +6,31 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! Mock random number generator
+//! Mock random number generators
+//!
+//! These generators produce predictable values, for testing code which uses
+//! random numbers, such as specific branches of a distribution:
+//!
+//! -   [`StepRng`] yields an arithmetic sequence
+//! -   [`ConstRng`] always yields the same value
+//! -   [`ReplayRng`] replays a fixed script of values or bytes
+//! -   [`RecordingRng`] records all calls made to another generator
+//!
+//! ```
+//! use rand::distributions::{Bernoulli, Distribution};
+//! use rand::rngs::mock::ConstRng;
+//!
+//! // Bernoulli compares a `u64` with a threshold
+//! let d = Bernoulli::new(0.5).unwrap();
+//! assert!(d.sample(&mut ConstRng::new(0)));
+//! assert!(!d.sample(&mut ConstRng::new(u64::max_value())));
+//! ```
 
+#[cfg(feature = "alloc")] use alloc::vec::Vec;
 use rand_core::{impls, Error, RngCore};
 
 #[cfg(feature = "serde1")]
-use serde::{Serialize, Deserialize};
+use serde::{de, Serialize, Deserialize, Deserializer};
 
 /// A simple implementation of `RngCore` for testing purposes.
 ///
@@ -70,8 +89,347 @@ impl RngCore for StepRng {
     }
 }
 
+/// A mock generator which always yields the same value.
+///
+/// `next_u64` returns the value, `next_u32` its lower 32 bits, and
+/// `fill_bytes` fills the destination with copies of its little-endian bytes,
+/// like [`StepRng`] with an increment of 0.
+///
+/// ```
+/// use rand::Rng;
+/// use rand::rngs::mock::ConstRng;
+///
+/// let mut rng = ConstRng::new(u64::max_value());
+/// // The maximum value, or the largest float less than 1
+/// assert_eq!(rng.gen::<u8>(), 255);
+/// assert!(rng.gen::<f64>() < 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct ConstRng {
+    v: u64,
+}
+
+impl ConstRng {
+    /// Create a `ConstRng`, always yielding `value`.
+    pub fn new(value: u64) -> Self {
+        ConstRng { v: value }
+    }
+}
+
+impl RngCore for ConstRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.v as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.v
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest);
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg(feature = "alloc")]
+enum Script {
+    Words(Vec<u64>),
+    Bytes(Vec<u8>),
+}
+
+#[cfg(feature = "alloc")]
+impl Script {
+    fn len(&self) -> usize {
+        match *self {
+            Script::Words(ref words) => words.len(),
+            Script::Bytes(ref bytes) => bytes.len(),
+        }
+    }
+}
+
+/// A mock generator which replays a fixed script.
+///
+/// The script is either a sequence of words or of bytes:
+///
+/// -   With [`ReplayRng::from_words`], each call to `next_u32` or `next_u64`
+///     consumes one word (`next_u32` returning its lower 32 bits), and
+///     `fill_bytes` consumes words as [`impls::fill_bytes_via_next`] does.
+/// -   With [`ReplayRng::from_bytes`], `next_u32` and `next_u64` consume 4 and
+///     8 bytes in little-endian order, and `fill_bytes` copies bytes.
+///
+/// When the script is exhausted, the generator panics, or starts over if
+/// [`cycle`] was used.
+///
+/// ```
+/// use rand::Rng;
+/// use rand::rngs::mock::ReplayRng;
+///
+/// let mut rng = ReplayRng::from_words(vec![1, 2, 3]).cycle();
+/// let sample: [u64; 5] = rng.gen();
+/// assert_eq!(sample, [1, 2, 3, 1, 2]);
+///
+/// let mut rng = ReplayRng::from_bytes(vec![1, 0, 0, 0, 2, 0, 0, 0]);
+/// assert_eq!(rng.gen::<u32>(), 1);
+/// assert_eq!(rng.gen::<u8>(), 2); // `u8` is generated from a `u32`
+/// ```
+///
+/// [`impls::fill_bytes_via_next`]: rand_core::impls::fill_bytes_via_next
+/// [`cycle`]: ReplayRng::cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub struct ReplayRng {
+    script: Script,
+    pos: usize,
+    cycle: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl ReplayRng {
+    /// Create a `ReplayRng` replaying `words`.
+    ///
+    /// # Panics
+    ///
+    /// If `words` is empty.
+    pub fn from_words(words: Vec<u64>) -> Self {
+        assert!(!words.is_empty(), "ReplayRng: empty script");
+        ReplayRng {
+            script: Script::Words(words),
+            pos: 0,
+            cycle: false,
+        }
+    }
+
+    /// Create a `ReplayRng` replaying `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// If `bytes` is empty.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        assert!(!bytes.is_empty(), "ReplayRng: empty script");
+        ReplayRng {
+            script: Script::Bytes(bytes),
+            pos: 0,
+            cycle: false,
+        }
+    }
+
+    /// Start over from the beginning of the script when it is exhausted,
+    /// instead of panicking.
+    pub fn cycle(mut self) -> Self {
+        self.cycle = true;
+        self
+    }
+
+    /// The number of words or bytes of the script which have been consumed,
+    /// counting from the last time it started over.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The number of words or bytes of the script left before it is
+    /// exhausted.
+    pub fn remaining(&self) -> usize {
+        self.script.len() - self.pos
+    }
+}
+
+// The script must not be empty, and the position must be within it.
+#[cfg(all(feature = "alloc", feature = "serde1"))]
+impl<'de> Deserialize<'de> for ReplayRng {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "ReplayRng")]
+        struct Fields {
+            script: Script,
+            pos: usize,
+            cycle: bool,
+        }
+
+        let Fields { script, pos, cycle } = Fields::deserialize(deserializer)?;
+        if script.len() == 0 {
+            return Err(de::Error::custom("ReplayRng: empty script"));
+        }
+        if pos > script.len() {
+            return Err(de::Error::custom("ReplayRng: position beyond the end of the script"));
+        }
+        Ok(ReplayRng { script, pos, cycle })
+    }
+}
+
+// Copy bytes from the script to `dest`, starting at `*pos`.
+#[cfg(feature = "alloc")]
+fn read_bytes(bytes: &[u8], pos: &mut usize, cycle: bool, dest: &mut [u8]) {
+    let mut filled = 0;
+    while filled < dest.len() {
+        if *pos == bytes.len() {
+            assert!(cycle, "ReplayRng: script exhausted");
+            *pos = 0;
+        }
+        let n = (dest.len() - filled).min(bytes.len() - *pos);
+        dest[filled..filled + n].copy_from_slice(&bytes[*pos..*pos + n]);
+        filled += n;
+        *pos += n;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl RngCore for ReplayRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        match self.script {
+            Script::Words(_) => self.next_u64() as u32,
+            Script::Bytes(ref bytes) => {
+                let mut buf = [0; 4];
+                read_bytes(bytes, &mut self.pos, self.cycle, &mut buf);
+                u32::from_le_bytes(buf)
+            }
+        }
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        match self.script {
+            Script::Words(ref words) => {
+                if self.pos == words.len() {
+                    assert!(self.cycle, "ReplayRng: script exhausted");
+                    self.pos = 0;
+                }
+                self.pos += 1;
+                words[self.pos - 1]
+            }
+            Script::Bytes(ref bytes) => {
+                let mut buf = [0; 8];
+                read_bytes(bytes, &mut self.pos, self.cycle, &mut buf);
+                u64::from_le_bytes(buf)
+            }
+        }
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self.script {
+            Script::Words(_) => impls::fill_bytes_via_next(self, dest),
+            Script::Bytes(ref bytes) => read_bytes(bytes, &mut self.pos, self.cycle, dest),
+        }
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// A call made to the generator wrapped by [`RecordingRng`], with the
+/// returned value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub enum RngCall {
+    /// `next_u32`, returning the value.
+    NextU32(u32),
+    /// `next_u64`, returning the value.
+    NextU64(u64),
+    /// `fill_bytes`, with the bytes written.
+    FillBytes(Vec<u8>),
+    /// `try_fill_bytes`, with the bytes written, or `None` if it failed.
+    TryFillBytes(Option<Vec<u8>>),
+}
+
+/// A wrapper around any generator which records every call made to it.
+///
+/// This shows how much output, and of which kind, code consumes.
+///
+/// ```
+/// use rand::Rng;
+/// use rand::rngs::mock::{RecordingRng, RngCall, StepRng};
+///
+/// let mut rng = RecordingRng::new(StepRng::new(0, 1));
+/// let _: bool = rng.gen();
+/// let _: u64 = rng.gen();
+/// assert_eq!(rng.calls(), &[RngCall::NextU32(0), RngCall::NextU64(1)]);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub struct RecordingRng<R> {
+    rng: R,
+    calls: Vec<RngCall>,
+}
+
+#[cfg(feature = "alloc")]
+impl<R: RngCore> RecordingRng<R> {
+    /// Wrap `rng`.
+    pub fn new(rng: R) -> Self {
+        RecordingRng {
+            rng,
+            calls: Vec::new(),
+        }
+    }
+
+    /// The calls recorded so far, in order.
+    pub fn calls(&self) -> &[RngCall] {
+        &self.calls
+    }
+
+    /// Return the calls recorded so far, and clear the record.
+    #[allow(clippy::mem_replace_with_default)] // `mem::take` requires Rust 1.40
+    pub fn take_calls(&mut self) -> Vec<RngCall> {
+        core::mem::replace(&mut self.calls, Vec::new())
+    }
+
+    /// Consume the `RecordingRng`, returning the wrapped generator.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: RngCore> RngCore for RecordingRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        let x = self.rng.next_u32();
+        self.calls.push(RngCall::NextU32(x));
+        x
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let x = self.rng.next_u64();
+        self.calls.push(RngCall::NextU64(x));
+        x
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        self.calls.push(RngCall::FillBytes(dest.to_vec()));
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        let result = self.rng.try_fill_bytes(dest);
+        let bytes = match result {
+            Ok(()) => Some(dest.to_vec()),
+            Err(_) => None,
+        };
+        self.calls.push(RngCall::TryFillBytes(bytes));
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "alloc")] use alloc::vec;
     #[test]
     #[cfg(feature = "serde1")]
     fn test_serialization_step_rng() {
@@ -84,4 +442,95 @@ mod tests {
         assert_eq!(some_rng.a, de_some_rng.a);
 
     }
+
+    #[test]
+    fn test_const_rng() {
+        use super::ConstRng;
+        use crate::RngCore;
+
+        let mut rng = ConstRng::new(0x0123_4567_89ab_cdef);
+        assert_eq!(rng.next_u64(), 0x0123_4567_89ab_cdef);
+        assert_eq!(rng.next_u32(), 0x89ab_cdef);
+        let mut buf = [0u8; 10];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, [0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01, 0xef, 0xcd]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_replay_rng() {
+        use super::ReplayRng;
+        use crate::RngCore;
+
+        let mut rng = ReplayRng::from_words(vec![1, 2, 3]);
+        assert_eq!(rng.next_u64(), 1);
+        assert_eq!(rng.next_u32(), 2);
+        assert_eq!(rng.remaining(), 1);
+        let mut buf = [0u8; 3];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, [3, 0, 0]);
+        assert_eq!(rng.remaining(), 0);
+
+        let mut rng = ReplayRng::from_bytes(vec![1, 2, 3, 4, 5, 6]).cycle();
+        assert_eq!(rng.next_u32(), 0x0403_0201);
+        assert_eq!(rng.next_u64(), 0x0605_0403_0201_0605);
+        let mut buf = [0u8; 8];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, [1, 2, 3, 4, 5, 6, 1, 2]);
+        assert_eq!(rng.position(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "ReplayRng: script exhausted")]
+    #[cfg(feature = "alloc")]
+    fn test_replay_rng_exhausted() {
+        use super::ReplayRng;
+        use crate::RngCore;
+
+        let mut rng = ReplayRng::from_bytes(vec![1, 2, 3]);
+        rng.next_u32();
+    }
+
+    #[test]
+    #[cfg(all(feature = "alloc", feature = "serde1"))]
+    fn test_serialization_replay_rng() {
+        use super::ReplayRng;
+        use crate::RngCore;
+
+        let mut rng = ReplayRng::from_words(vec![1, 2, 3]);
+        rng.next_u64();
+        let mut buf = bincode::serialize(&rng).unwrap();
+        let de_rng: ReplayRng = bincode::deserialize(&buf).unwrap();
+        assert_eq!(de_rng, rng);
+
+        // The position is followed by the `cycle` flag
+        let len = buf.len();
+        buf[len - 9..len - 1].copy_from_slice(&4u64.to_le_bytes());
+        assert!(bincode::deserialize::<ReplayRng>(&buf).is_err());
+
+        let rng = ReplayRng { script: super::Script::Bytes(vec![]), pos: 0, cycle: true };
+        let buf = bincode::serialize(&rng).unwrap();
+        assert!(bincode::deserialize::<ReplayRng>(&buf).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_recording_rng() {
+        use super::{RecordingRng, RngCall, StepRng};
+        use crate::RngCore;
+
+        let mut rng = RecordingRng::new(StepRng::new(1, 1));
+        rng.next_u32();
+        let mut buf = [0u8; 2];
+        rng.fill_bytes(&mut buf);
+        rng.try_fill_bytes(&mut buf).unwrap();
+        assert_eq!(rng.take_calls(), vec![
+            RngCall::NextU32(1),
+            RngCall::FillBytes(vec![2, 0]),
+            RngCall::TryFillBytes(Some(vec![3, 0])),
+        ]);
+        assert!(rng.calls().is_empty());
+        assert_eq!(rng.next_u64(), 4);
+        assert_eq!(rng.into_inner(), StepRng::new(5, 1));
+    }
 }
@@ -64,6 +64,8 @@
 //!     periodic reseeding.
 //! -   [`SmallRng`] is an **insecure** PRNG designed to be fast, simple, require
 //!     little memory, and have good output quality.
+//! -   [`SharedRng`] and [`ShardedRng`] share a generator, or a set of
+//!     generators, between threads.
 //!
 //! The algorithms selected for [`StdRng`] and [`SmallRng`] may change in any
 //! release and may be platform-dependent, therefore they should be considered
@@ -110,10 +112,13 @@ mod xoshiro128plusplus;
 
 #[cfg(feature = "std_rng")] mod std;
 #[cfg(all(feature = "std", feature = "std_rng"))] pub(crate) mod thread;
+#[cfg(feature = "std")] mod shared;
 
 #[cfg(feature = "small_rng")] pub use self::small::SmallRng;
 #[cfg(feature = "std_rng")] pub use self::std::StdRng;
 #[cfg(all(feature = "std", feature = "std_rng"))] pub use self::thread::ThreadRng;
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")] pub use self::shared::{ShardedRng, SharedRng};
 
 #[cfg_attr(doc_cfg, doc(cfg(feature = "getrandom")))]
 #[cfg(feature = "getrandom")] pub use rand_core::OsRng;
@@ -0,0 +1,483 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Generators which may be shared between threads
+
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::boxed::Box;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread_local;
+use std::vec::Vec;
+
+use crate::{CryptoRng, Error, RngCore, SeedableRng, SplittableRng};
+
+// Lock a generator. A panic while holding the lock cannot leave a generator
+// in an invalid state, so poisoning is ignored.
+fn lock<R>(mutex: &Mutex<R>) -> MutexGuard<'_, R> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A handle to a generator shared between threads, protected by a lock.
+///
+/// `SharedRng` is `Send + Sync + Clone` (if `R: Send`), so it may be stored in
+/// shared application state. Clones share the same generator, and every call
+/// locks it, so under contention the threads using it are serialized. Where
+/// this is a bottleneck, consider [`ShardedRng`] or [`ThreadRng`].
+///
+/// The sequence of values returned to each thread depends on the order in
+/// which the threads acquire the lock, so it is not reproducible.
+///
+/// # Example
+///
+/// ```
+/// use rand::prelude::*;
+/// use rand::rngs::SharedRng;
+///
+/// let rng = SharedRng::new(StdRng::from_entropy());
+/// let handles: Vec<_> = (0..4).map(|_| {
+///     let mut rng = rng.clone();
+///     std::thread::spawn(move || rng.gen::<u64>())
+/// }).collect();
+/// for handle in handles {
+///     println!("{}", handle.join().unwrap());
+/// }
+/// ```
+///
+/// [`ThreadRng`]: crate::rngs::ThreadRng
+pub struct SharedRng<R> {
+    rng: Arc<Mutex<R>>,
+}
+
+impl<R: RngCore> SharedRng<R> {
+    /// Share `rng`.
+    pub fn new(rng: R) -> Self {
+        SharedRng {
+            rng: Arc::new(Mutex::new(rng)),
+        }
+    }
+}
+
+impl<R> Clone for SharedRng<R> {
+    fn clone(&self) -> Self {
+        SharedRng {
+            rng: self.rng.clone(),
+        }
+    }
+}
+
+impl<R: RngCore> RngCore for SharedRng<R> {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        lock(&self.rng).next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        lock(&self.rng).next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        lock(&self.rng).fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        lock(&self.rng).try_fill_bytes(dest)
+    }
+}
+
+impl<R: RngCore + CryptoRng> CryptoRng for SharedRng<R> {}
+
+// Custom Debug implementation that does not expose the internal state
+impl<R> fmt::Debug for SharedRng<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SharedRng").finish()
+    }
+}
+
+// A generator, stored on its own cache lines to avoid false sharing.
+#[repr(align(64))]
+struct Aligned<R>(R);
+
+// A shard holds a generator, or null while a thread is using it. A generator
+// is taken and returned with single atomic operations, so no thread ever
+// waits for another.
+#[repr(align(64))]
+struct Shard<R> {
+    rng: AtomicPtr<Aligned<R>>,
+}
+
+// Only one thread at a time has access to the generator, so `R: Sync` is not
+// required.
+unsafe impl<R: Send> Send for Shard<R> {}
+unsafe impl<R: Send> Sync for Shard<R> {}
+
+impl<R> Shard<R> {
+    fn new(rng: R) -> Self {
+        Shard {
+            rng: AtomicPtr::new(Box::into_raw(Box::new(Aligned(rng)))),
+        }
+    }
+
+    // Take the generator, unless another thread is using it.
+    #[inline]
+    fn take(&self) -> Option<Box<Aligned<R>>> {
+        let ptr = self.rng.swap(ptr::null_mut(), Ordering::Acquire);
+        if ptr.is_null() {
+            None
+        } else {
+            // SAFETY: non-null pointers are created by `Box::into_raw`, and
+            // the swap transfers their ownership to this thread.
+            Some(unsafe { Box::from_raw(ptr) })
+        }
+    }
+
+    // Return a generator to the shard. If the shard was refilled meanwhile,
+    // the generator is dropped instead.
+    #[inline]
+    fn put(&self, rng: Box<Aligned<R>>) {
+        let ptr = Box::into_raw(rng);
+        let result = self.rng.compare_exchange(
+            ptr::null_mut(), ptr, Ordering::Release, Ordering::Relaxed);
+        if result.is_err() {
+            // SAFETY: `ptr` was not stored, so we still own it.
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
+impl<R> Drop for Shard<R> {
+    fn drop(&mut self) {
+        let ptr = *self.rng.get_mut();
+        if !ptr.is_null() {
+            // SAFETY: the shard owns the generator it holds.
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
+// A generator taken from a shard, which is returned to it on drop.
+struct ShardGuard<'a, R> {
+    shard: &'a Shard<R>,
+    rng: Option<Box<Aligned<R>>>,
+}
+
+impl<'a, R> Deref for ShardGuard<'a, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.rng.as_ref().unwrap().0
+    }
+}
+
+impl<'a, R> DerefMut for ShardGuard<'a, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        &mut self.rng.as_mut().unwrap().0
+    }
+}
+
+impl<'a, R> Drop for ShardGuard<'a, R> {
+    fn drop(&mut self) {
+        if let Some(rng) = self.rng.take() {
+            self.shard.put(rng);
+        }
+    }
+}
+
+// The index of the current thread.
+fn thread_index() -> usize {
+    static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+    thread_local!(
+        static INDEX: usize = NEXT_INDEX.fetch_add(1, Ordering::Relaxed);
+    );
+    INDEX.with(|index| *index)
+}
+
+// The index of the current CPU where available, otherwise of the current
+// thread, used to choose a home shard.
+#[inline]
+fn home_index() -> usize {
+    #[cfg(all(target_os = "linux", not(miri)))]
+    {
+        let cpu = unsafe { libc::sched_getcpu() };
+        if cpu >= 0 {
+            return cpu as usize;
+        }
+    }
+    thread_index()
+}
+
+struct Shards<R> {
+    parent: R,
+    shards: Box<[Shard<R>]>,
+    // The fork index of the next generator created on contention
+    next_index: AtomicUsize,
+}
+
+/// A lock-free handle to several independent generators shared between
+/// threads.
+///
+/// `ShardedRng` holds a fixed number of *shards*, each a generator forked
+/// from a parent generator with [`SplittableRng::fork`] and stored on its own
+/// cache lines, so that each shard buffers its own output. A call takes the
+/// generator of a shard with a single atomic operation and returns it
+/// afterwards. The call starts at the home shard of the current CPU (on
+/// Linux; of the current thread elsewhere) and moves on to the next shard if
+/// another thread is using it.
+///
+/// No call ever waits for another thread. If all shards are in use, the call
+/// instead forks a new generator from the parent, which is then kept in the
+/// home shard if that has become free, and dropped otherwise. This is much
+/// slower than using a shard, so the number of shards should be at least the
+/// number of threads using it concurrently, typically the number of CPU
+/// cores. Threads still contend for the cache line of a shard when they
+/// share it.
+///
+/// Like [`SharedRng`], `ShardedRng` is `Send + Sync + Clone` (if
+/// `R: Send + Sync`), clones share the same shards, and the output is not
+/// reproducible. Since all generators are forked from the same parent with
+/// different indices, their output does not overlap, subject to the
+/// guarantees of [`SplittableRng`] for `R`.
+///
+/// # Example
+///
+/// ```
+/// use rand::prelude::*;
+/// use rand::rngs::ShardedRng;
+///
+/// let rng = ShardedRng::<StdRng>::from_rng(8, thread_rng()).unwrap();
+/// let handles: Vec<_> = (0..4).map(|_| {
+///     let mut rng = rng.clone();
+///     std::thread::spawn(move || rng.gen::<u64>())
+/// }).collect();
+/// for handle in handles {
+///     println!("{}", handle.join().unwrap());
+/// }
+/// ```
+pub struct ShardedRng<R> {
+    inner: Arc<Shards<R>>,
+}
+
+impl<R: SplittableRng + SeedableRng> ShardedRng<R> {
+    /// Create `shards` generators, forked from a parent generator seeded from
+    /// `rng` with [`SeedableRng::from_rng`].
+    ///
+    /// # Panics
+    ///
+    /// If `shards` is zero.
+    pub fn from_rng<S: RngCore>(shards: usize, rng: S) -> Result<Self, Error> {
+        R::from_rng(rng).map(|parent| ShardedRng::new(shards, parent))
+    }
+}
+
+impl<R: SplittableRng> ShardedRng<R> {
+    /// Create `shards` generators, forked from `parent` with indices
+    /// `0..shards`. Generators created when all shards are in use get the
+    /// following indices.
+    ///
+    /// # Panics
+    ///
+    /// If `shards` is zero.
+    pub fn new(shards: usize, parent: R) -> Self {
+        assert!(shards > 0, "ShardedRng::new called with shards = 0");
+        let shards: Vec<_> = (0..shards)
+            .map(|index| Shard::new(parent.fork(index as u64)))
+            .collect();
+        let next_index = AtomicUsize::new(shards.len());
+        ShardedRng {
+            inner: Arc::new(Shards {
+                parent,
+                shards: shards.into_boxed_slice(),
+                next_index,
+            }),
+        }
+    }
+
+    /// The number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.inner.shards.len()
+    }
+
+    // Take the first free shard, starting at the home shard.
+    #[inline]
+    fn shard(&self) -> ShardGuard<'_, R> {
+        let shards = &self.inner.shards;
+        let n = shards.len();
+        let home = home_index() % n;
+        for i in 0..n {
+            let shard = &shards[(home + i) % n];
+            if let Some(rng) = shard.take() {
+                return ShardGuard { shard, rng: Some(rng) };
+            }
+        }
+        self.fork_new(home)
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn fork_new(&self, home: usize) -> ShardGuard<'_, R> {
+        // On 32-bit platforms the index may wrap around after 2^32
+        // generators, repeating earlier sequences.
+        let index = self.inner.next_index.fetch_add(1, Ordering::Relaxed);
+        let rng = self.inner.parent.fork(index as u64);
+        ShardGuard {
+            shard: &self.inner.shards[home],
+            rng: Some(Box::new(Aligned(rng))),
+        }
+    }
+}
+
+impl<R> Clone for ShardedRng<R> {
+    fn clone(&self) -> Self {
+        ShardedRng {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<R: SplittableRng> RngCore for ShardedRng<R> {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.shard().next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.shard().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.shard().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.shard().try_fill_bytes(dest)
+    }
+}
+
+impl<R: SplittableRng + CryptoRng> CryptoRng for ShardedRng<R> {}
+
+// Custom Debug implementation that does not expose the internal state
+impl<R> fmt::Debug for ShardedRng<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShardedRng")
+            .field("shards", &self.inner.shards.len())
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{ShardedRng, SharedRng};
+    use crate::rngs::mock::StepRng;
+    use crate::{RngCore, SeedableRng, SplittableRng};
+    use std::thread;
+    use std::vec::Vec;
+
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+    #[test]
+    fn test_shared_rng() {
+        assert_send_sync::<SharedRng<StepRng>>();
+        let rng = SharedRng::new(StepRng::new(0, 1));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut rng = rng.clone();
+                thread::spawn(move || (0..100).map(|_| rng.next_u64()).collect::<Vec<_>>())
+            })
+            .collect();
+        let mut values: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        // Every value of the shared sequence is used exactly once
+        values.sort();
+        assert_eq!(values, (0..400).collect::<Vec<_>>());
+    }
+
+    // A splittable counter, so shards produce disjoint ranges
+    struct Counter(u64);
+
+    impl RngCore for Counter {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 += 1;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), crate::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl SeedableRng for Counter {
+        type Seed = [u8; 8];
+
+        fn from_seed(seed: Self::Seed) -> Self {
+            Counter(u64::from_le_bytes(seed))
+        }
+    }
+
+    impl SplittableRng for Counter {
+        fn fork(&self, index: u64) -> Self {
+            Counter(self.0 + (index + 1) * 1000)
+        }
+    }
+
+    #[test]
+    fn test_sharded_rng() {
+        assert_send_sync::<ShardedRng<Counter>>();
+        let rng = ShardedRng::<Counter>::from_rng(3, StepRng::new(0, 1000)).unwrap();
+        assert_eq!(rng.num_shards(), 3);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut rng = rng.clone();
+                thread::spawn(move || (0..100).map(|_| rng.next_u64()).collect::<Vec<_>>())
+            })
+            .collect();
+        let mut values: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        // Shards never return the same value twice
+        values.sort();
+        values.dedup();
+        assert_eq!(values.len(), 400);
+        assert!(values.iter().all(|&x| x % 1000 != 0));
+    }
+
+    #[test]
+    fn test_sharded_rng_fork_new() {
+        let mut rng = ShardedRng::new(1, Counter(0));
+        assert_eq!(rng.next_u64(), 1001);
+        assert_eq!(rng.next_u64(), 1002);
+
+        // While the only shard is in use, a new generator is forked
+        let mut other = rng.clone();
+        {
+            let mut shard = rng.shard();
+            assert_eq!(shard.next_u64(), 1003);
+            assert_eq!(other.next_u64(), 2001);
+        }
+        // The new generator was returned to the free shard first, so the
+        // previous one was dropped
+        assert_eq!(rng.next_u64(), 2002);
+    }
+}
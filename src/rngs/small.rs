@@ -8,7 +8,10 @@
 
 //! A small fast RNG
 
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
 use rand_core::{Error, RngCore, SeedableRng};
+#[cfg(feature = "alloc")] use alloc::vec::Vec;
+#[cfg(feature = "serde1")] use serde::{Deserialize, Serialize};
 
 #[cfg(target_pointer_width = "64")]
 type Rng = super::xoshiro256plusplus::Xoshiro256PlusPlus;
@@ -72,14 +75,75 @@ type Rng = super::xoshiro128plusplus::Xoshiro128PlusPlus;
 ///     .collect();
 /// ```
 ///
+/// To run simulations in parallel from a single seed, use
+/// [`SmallRng::split_streams`] (or [`SmallRng::jump`] directly) to obtain
+/// generators whose output sequences are guaranteed not to overlap:
+///
+/// ```
+/// use rand::{Rng, SeedableRng};
+/// use rand::rngs::SmallRng;
+///
+/// let mut rng = SmallRng::seed_from_u64(42);
+/// let workers: Vec<SmallRng> = rng.split_streams(4);
+/// for mut worker in workers {
+///     // e.g. move `worker` into a new thread
+///     let _: f64 = worker.gen();
+/// }
+/// ```
+///
 /// [`StdRng`]: crate::rngs::StdRng
 /// [`thread_rng`]: crate::thread_rng
 /// [rand_chacha]: https://crates.io/crates/rand_chacha
 /// [rand_xoshiro]: https://crates.io/crates/rand_xoshiro
 #[cfg_attr(doc_cfg, doc(cfg(feature = "small_rng")))]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub struct SmallRng(Rng);
 
+impl SmallRng {
+    /// Jump forward by a very large, fixed number of steps.
+    ///
+    /// The jump length is 2^128 calls to `next_u64()` on 64-bit platforms and
+    /// 2^64 calls to `next_u32()` on other platforms. Starting from a common
+    /// state, repeated jumps therefore yield generators with non-overlapping
+    /// output sequences, suitable for parallel computations.
+    #[inline]
+    pub fn jump(&mut self) {
+        self.0.jump();
+    }
+
+    /// Jump forward by a longer distance than [`SmallRng::jump`].
+    ///
+    /// The jump length is 2^192 calls to `next_u64()` on 64-bit platforms and
+    /// 2^96 calls to `next_u32()` on other platforms. This can be used to
+    /// generate starting points, from each of which `jump()` will generate
+    /// further non-overlapping subsequences for distributed computations.
+    #[inline]
+    pub fn long_jump(&mut self) {
+        self.0.long_jump();
+    }
+
+    /// Split off `n` generators with non-overlapping output sequences.
+    ///
+    /// The first generator is a copy of `self`, each following one starts
+    /// one [`jump`](SmallRng::jump) after the previous. Finally `self` is
+    /// advanced past the last returned generator, so it may be used to
+    /// split off further generators.
+    ///
+    /// The result depends only on the state of `self` and on `n`, hence a
+    /// fixed seed yields reproducible workers.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+    pub fn split_streams(&mut self, n: usize) -> Vec<SmallRng> {
+        let mut streams = Vec::with_capacity(n);
+        for _ in 0..n {
+            streams.push(self.clone());
+            self.jump();
+        }
+        streams
+    }
+}
+
 impl RngCore for SmallRng {
     #[inline(always)]
     fn next_u32(&mut self) -> u32 {
@@ -115,3 +179,78 @@ impl SeedableRng for SmallRng {
         Rng::from_rng(rng).map(SmallRng)
     }
 }
+
+/// The snapshot is that of the underlying algorithm, so that it can only be
+/// restored by the same version of SmallRng on the same platform (or by the generator it is based on).
+impl SnapshotState for SmallRng {
+    const STATE_LEN: usize = Rng::STATE_LEN;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        self.0.write_state(w)
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        Rng::read_state(r).map(SmallRng)
+    }
+}
+
+impl Snapshot for SmallRng {
+    const ALGORITHM: &'static str = Rng::ALGORITHM;
+    const VERSION: u16 = Rng::VERSION;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rngs::SmallRng;
+    use crate::{RngCore, SeedableRng};
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_split_streams() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let mut expected = rng.clone();
+        let streams = rng.split_streams(3);
+        assert_eq!(streams.len(), 3);
+        for stream in &streams {
+            assert_eq!(*stream, expected);
+            expected.jump();
+        }
+        assert_eq!(rng, expected);
+
+        // Splitting is deterministic
+        let mut rng2 = SmallRng::seed_from_u64(7);
+        assert_eq!(rng2.split_streams(3), streams);
+
+        let mut a = streams[0].clone();
+        let mut b = streams[1].clone();
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn test_small_rng_snapshot() {
+        use crate::snapshot::Snapshot;
+
+        let mut rng = SmallRng::seed_from_u64(3);
+        rng.next_u32();
+        let mut buf = [0; 64];
+        let len = rng.export_snapshot(&mut buf).unwrap();
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(&buf[5..len - 38], b"Xoshiro256PlusPlus");
+        let mut restored = SmallRng::from_snapshot(&buf[..len]).unwrap();
+        assert_eq!(restored, rng);
+        assert_eq!(restored.next_u64(), rng.next_u64());
+    }
+
+    #[test]
+    #[cfg(feature = "serde1")]
+    fn test_small_rng_serde() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        rng.next_u32();
+        let mut restored: SmallRng =
+            bincode::deserialize(&bincode::serialize(&rng).unwrap()).unwrap();
+        assert_eq!(restored, rng);
+        for _ in 0..16 {
+            assert_eq!(restored.next_u64(), rng.next_u64());
+        }
+    }
+}
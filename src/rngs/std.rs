@@ -9,6 +9,12 @@
 //! The standard RNG
 
 use crate::{CryptoRng, Error, RngCore, SeedableRng};
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
+#[cfg(not(target_os = "emscripten"))] use crate::SplittableRng;
+#[cfg(feature = "serde1")] use core::fmt;
+#[cfg(feature = "serde1")]
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+#[cfg(feature = "serde1")] use serde::{Serialize, Serializer};
 
 #[cfg(all(any(test, feature = "std"), not(target_os = "emscripten")))]
 pub(crate) use rand_chacha::ChaCha12Core as Core;
@@ -75,6 +81,84 @@ impl SeedableRng for StdRng {
 
 impl CryptoRng for StdRng {}
 
+/// The snapshot is that of the underlying algorithm, so that it can only be
+/// restored by the same version of StdRng (or by the generator it is based on).
+impl SnapshotState for StdRng {
+    const STATE_LEN: usize = Rng::STATE_LEN;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        self.0.write_state(w)
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        Rng::read_state(r).map(StdRng)
+    }
+}
+
+impl Snapshot for StdRng {
+    const ALGORITHM: &'static str = Rng::ALGORITHM;
+    const VERSION: u16 = Rng::VERSION;
+}
+
+// `StdRng` is serialized as the bytes of its snapshot state, so that `serde1`
+// does not need to enable the optional `rand_chacha` and `rand_hc`
+// dependencies, which would require weak dependency features (Rust 1.60).
+#[cfg(feature = "serde1")]
+impl Serialize for StdRng {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = [0u8; StdRng::STATE_LEN];
+        self.write_state(&mut StateWriter::new(&mut state));
+        serializer.serialize_bytes(&state)
+    }
+}
+
+#[cfg(feature = "serde1")]
+impl<'de> Deserialize<'de> for StdRng {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(StateVisitor)
+    }
+}
+
+#[cfg(feature = "serde1")]
+struct StateVisitor;
+
+#[cfg(feature = "serde1")]
+impl<'de> Visitor<'de> for StateVisitor {
+    type Value = StdRng;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes of StdRng state", StdRng::STATE_LEN)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<StdRng, E> {
+        if v.len() != StdRng::STATE_LEN {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        StdRng::read_state(&mut StateReader::new(v)).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<StdRng, A::Error> {
+        let mut state = [0u8; StdRng::STATE_LEN];
+        for (i, byte) in state.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(StdRng::STATE_LEN + 1, &self));
+        }
+        self.visit_bytes(&state)
+    }
+}
+
+#[cfg(not(target_os = "emscripten"))]
+impl SplittableRng for StdRng {
+    #[inline]
+    fn fork(&self, index: u64) -> Self {
+        StdRng(self.0.fork(index))
+    }
+}
+
 
 #[cfg(test)]
 mod test {
@@ -99,4 +183,46 @@ mod test {
 
         assert_eq!([x0, x1], target);
     }
+
+    #[test]
+    fn test_stdrng_snapshot() {
+        use crate::snapshot::Snapshot;
+
+        let mut rng = StdRng::seed_from_u64(5);
+        rng.next_u32();
+        let mut buf = [0; 128];
+        let len = rng.export_snapshot(&mut buf).unwrap();
+        let snapshot = &buf[..len];
+        let mut restored = StdRng::from_snapshot(snapshot).unwrap();
+        assert_eq!(restored, rng);
+        for _ in 0..100 {
+            assert_eq!(restored.next_u32(), rng.next_u32());
+        }
+
+        // The snapshot is that of the underlying generator
+        #[cfg(not(target_os = "emscripten"))]
+        {
+            let mut inner = rand_chacha::ChaCha12Rng::from_snapshot(snapshot).unwrap();
+            let mut rng = StdRng::from_snapshot(snapshot).unwrap();
+            assert_eq!(inner.next_u64(), rng.next_u64());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde1")]
+    fn test_stdrng_serde() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for &n in [0, 1, 15, 16, 100].iter() {
+            for _ in 0..n {
+                rng.next_u32();
+            }
+            let mut restored: StdRng =
+                bincode::deserialize(&bincode::serialize(&rng).unwrap()).unwrap();
+            assert_eq!(restored, rng);
+            let mut clone = rng.clone();
+            for _ in 0..100 {
+                assert_eq!(restored.next_u32(), clone.next_u32());
+            }
+        }
+    }
 }
@@ -8,13 +8,15 @@
 
 //! Thread-local random number generator
 
-use core::cell::UnsafeCell;
+use core::cell::{Cell, UnsafeCell};
+use core::cmp::min;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use std::rc::Rc;
 use std::thread_local;
 
 use super::std::Core;
 use crate::rngs::adapter::ReseedingRng;
-use crate::rngs::OsRng;
+use crate::entropy::DefaultEntropy;
 use crate::{CryptoRng, Error, RngCore, SeedableRng};
 
 // Rationale for using `UnsafeCell` in `ThreadRng`:
@@ -26,10 +28,13 @@ use crate::{CryptoRng, Error, RngCore, SeedableRng};
 // be one of these methods active at a time.
 //
 // A possible scenario where there could be multiple mutable references is if
-// `ThreadRng` is used inside `next_u32` and co. But the implementation is
-// completely under our control. We just have to ensure none of them use
-// `ThreadRng` internally, which is nonsensical anyway. We should also never run
-// `ThreadRng` in destructors of its implementation, which is also nonsensical.
+// `ThreadRng` is used inside `next_u32` and co. Our implementation doesn't,
+// but reseeding calls the default entropy source, which may be a user
+// function installed with `entropy::set_default_source`. Such a function could
+// call `thread_rng` or `ThreadRng::seed_current_thread`, so every mutable
+// reference is guarded by the `borrowed` flag, which panics on re-entrant use
+// instead. We should also never run `ThreadRng` in destructors of its
+// implementation, which is nonsensical.
 
 
 // Number of generated bytes after which to reseed `ThreadRng`.
@@ -37,6 +42,11 @@ use crate::{CryptoRng, Error, RngCore, SeedableRng};
 // of 32 kB and less. We choose 64 kB to avoid significant overhead.
 const THREAD_RNG_RESEED_THRESHOLD: u64 = 1024 * 64;
 
+// The threshold used for new thread-local generators, see
+// `ThreadRng::set_reseed_threshold`. `AtomicU64` is not available on all
+// platforms; a `usize` threshold is large enough.
+static RESEED_THRESHOLD: AtomicUsize = AtomicUsize::new(THREAD_RNG_RESEED_THRESHOLD as usize);
+
 /// A reference to the thread-local generator
 ///
 /// An instance can be obtained via [`thread_rng`] or via `ThreadRng::default()`.
@@ -44,7 +54,10 @@ const THREAD_RNG_RESEED_THRESHOLD: u64 = 1024 * 64;
 /// but cannot be passed between threads (is not `Send` or `Sync`).
 ///
 /// `ThreadRng` uses the same PRNG as [`StdRng`] for security and performance
-/// and is automatically seeded from [`OsRng`].
+/// and is automatically seeded from the default entropy source, which is
+/// [`OsRng`] unless another source was installed with
+/// [`entropy::set_default_source`]. Such a source must be installed before the
+/// first use of `thread_rng` on a thread to affect its initial seed.
 ///
 /// Unlike `StdRng`, `ThreadRng` uses the  [`ReseedingRng`] wrapper to reseed
 /// the PRNG from fresh entropy every 64 kiB of random data as well as after a
@@ -54,28 +67,110 @@ const THREAD_RNG_RESEED_THRESHOLD: u64 = 1024 * 64;
 /// attacks and mis-use (e.g. if somehow weak entropy were supplied initially).
 /// The PRNG algorithms used are assumed to be secure.
 ///
+/// # Configuration
+///
+/// The reseed threshold can be changed at process start with
+/// [`ThreadRng::set_reseed_threshold`], and the entropy source used for
+/// seeding and reseeding with [`entropy::set_default_source`].
+///
+/// For reproducible tests, [`ThreadRng::seed_current_thread`] replaces the
+/// generator of the current thread with a deterministic one, affecting all
+/// uses of `thread_rng` on that thread, and
+/// [`ThreadRng::reset_current_thread`] restores a generator seeded from
+/// entropy:
+///
+/// ```
+/// use rand::{thread_rng, Rng};
+/// use rand::rngs::ThreadRng;
+///
+/// ThreadRng::seed_current_thread(42);
+/// let x: u64 = thread_rng().gen();
+/// ThreadRng::seed_current_thread(42);
+/// assert_eq!(thread_rng().gen::<u64>(), x);
+/// ThreadRng::reset_current_thread();
+/// ```
+///
 /// [`ReseedingRng`]: crate::rngs::adapter::ReseedingRng
+/// [`OsRng`]: crate::rngs::OsRng
+/// [`entropy::set_default_source`]: crate::entropy::set_default_source
 /// [`StdRng`]: crate::rngs::StdRng
 #[cfg_attr(doc_cfg, doc(cfg(all(feature = "std", feature = "std_rng"))))]
 #[derive(Clone, Debug)]
 pub struct ThreadRng {
     // Rc is explictly !Send and !Sync
-    rng: Rc<UnsafeCell<ReseedingRng<Core, OsRng>>>,
+    rng: Rc<ThreadRngState>,
+}
+
+// The generator of a thread, and whether a mutable reference to it exists.
+#[derive(Debug)]
+struct ThreadRngState {
+    rng: UnsafeCell<ReseedingRng<Core, DefaultEntropy>>,
+    borrowed: Cell<bool>,
+}
+
+impl ThreadRngState {
+    // Get the only mutable reference to the generator, which is valid until
+    // the returned guard is dropped.
+    //
+    // Panics if another reference exists, i.e. on re-entrant use.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    fn borrow(&self) -> (BorrowGuard<'_>, &mut ReseedingRng<Core, DefaultEntropy>) {
+        if self.borrowed.replace(true) {
+            borrow_failed();
+        }
+        // SAFETY: the flag was not set, so no other reference exists, and no
+        // other reference is created until the guard clears the flag.
+        (BorrowGuard(&self.borrowed), unsafe { &mut *self.rng.get() })
+    }
+}
+
+struct BorrowGuard<'a>(&'a Cell<bool>);
+
+impl<'a> Drop for BorrowGuard<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn borrow_failed() -> ! {
+    panic!("ThreadRng used re-entrantly; the default entropy source must not \
+            use thread_rng or ThreadRng");
 }
 
 thread_local!(
     // We require Rc<..> to avoid premature freeing when thread_rng is used
     // within thread-local destructors. See #968.
-    static THREAD_RNG_KEY: Rc<UnsafeCell<ReseedingRng<Core, OsRng>>> = {
-        let r = Core::from_rng(OsRng).unwrap_or_else(|err|
-                panic!("could not initialize thread_rng: {}", err));
-        let rng = ReseedingRng::new(r,
-                                    THREAD_RNG_RESEED_THRESHOLD,
-                                    OsRng);
-        Rc::new(UnsafeCell::new(rng))
+    static THREAD_RNG_KEY: Rc<ThreadRngState> = {
+        Rc::new(ThreadRngState {
+            rng: UnsafeCell::new(new_thread_rng()),
+            borrowed: Cell::new(false),
+        })
     }
 );
 
+fn new_thread_rng() -> ReseedingRng<Core, DefaultEntropy> {
+    let r = Core::from_rng(DefaultEntropy).unwrap_or_else(|err|
+            panic!("could not initialize thread_rng: {}", err));
+    ReseedingRng::new(r,
+                      RESEED_THRESHOLD.load(Ordering::Relaxed) as u64,
+                      DefaultEntropy)
+}
+
+// Replace the generator of the current thread, which is shared by all
+// `ThreadRng` handles on this thread.
+fn replace_thread_rng(rng: ReseedingRng<Core, DefaultEntropy>) {
+    THREAD_RNG_KEY.with(|t| {
+        // Panics if called from within a `ThreadRng` method, e.g. by the
+        // default entropy source while reseeding.
+        let (_guard, current) = t.borrow();
+        *current = rng;
+    });
+}
+
 /// Retrieve the lazily-initialized thread-local random number generator,
 /// seeded by the system. Intended to be used in method chaining style,
 /// e.g. `thread_rng().gen::<i32>()`, or cached locally, e.g.
@@ -89,6 +184,52 @@ pub fn thread_rng() -> ThreadRng {
     ThreadRng { rng }
 }
 
+impl ThreadRng {
+    /// Set the number of generated bytes after which thread-local generators
+    /// are reseeded; zero disables reseeding based on the number of generated
+    /// bytes. The default is 64 kiB.
+    ///
+    /// This affects generators initialized afterwards, so it should be called
+    /// at process start, before the first use of [`thread_rng`]. The
+    /// threshold is limited to `usize::MAX`.
+    pub fn set_reseed_threshold(threshold: u64) {
+        let threshold = min(threshold, usize::MAX as u64) as usize;
+        RESEED_THRESHOLD.store(threshold, Ordering::Relaxed);
+    }
+
+    /// Replace the generator of the current thread by one deterministically
+    /// seeded from `seed`, as with [`SeedableRng::seed_from_u64`].
+    ///
+    /// All uses of [`thread_rng`] on the current thread, including existing
+    /// handles, then produce a reproducible sequence. The generator is not
+    /// reseeded after a number of generated bytes, but is still reseeded from
+    /// entropy after a fork. Other threads are not affected.
+    ///
+    /// This is intended for tests; the generator must not be used for
+    /// cryptographic purposes until [`reset_current_thread`] is called.
+    ///
+    /// # Panics
+    ///
+    /// If called by the default entropy source while it is used by
+    /// `ThreadRng`.
+    ///
+    /// [`reset_current_thread`]: ThreadRng::reset_current_thread
+    pub fn seed_current_thread(seed: u64) {
+        replace_thread_rng(ReseedingRng::new(Core::seed_from_u64(seed), 0, DefaultEntropy));
+    }
+
+    /// Replace the generator of the current thread by a new one seeded from
+    /// the default entropy source, using the current reseed threshold.
+    ///
+    /// # Panics
+    ///
+    /// If the entropy source fails, like [`thread_rng`] on first use, or if
+    /// called by the default entropy source while it is used by `ThreadRng`.
+    pub fn reset_current_thread() {
+        replace_thread_rng(new_thread_rng());
+    }
+}
+
 impl Default for ThreadRng {
     fn default() -> ThreadRng {
         crate::prelude::thread_rng()
@@ -98,31 +239,23 @@ impl Default for ThreadRng {
 impl RngCore for ThreadRng {
     #[inline(always)]
     fn next_u32(&mut self) -> u32 {
-        // SAFETY: We must make sure to stop using `rng` before anyone else
-        // creates another mutable reference
-        let rng = unsafe { &mut *self.rng.get() };
+        let (_guard, rng) = self.rng.borrow();
         rng.next_u32()
     }
 
     #[inline(always)]
     fn next_u64(&mut self) -> u64 {
-        // SAFETY: We must make sure to stop using `rng` before anyone else
-        // creates another mutable reference
-        let rng = unsafe { &mut *self.rng.get() };
+        let (_guard, rng) = self.rng.borrow();
         rng.next_u64()
     }
 
     fn fill_bytes(&mut self, dest: &mut [u8]) {
-        // SAFETY: We must make sure to stop using `rng` before anyone else
-        // creates another mutable reference
-        let rng = unsafe { &mut *self.rng.get() };
+        let (_guard, rng) = self.rng.borrow();
         rng.fill_bytes(dest)
     }
 
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
-        // SAFETY: We must make sure to stop using `rng` before anyone else
-        // creates another mutable reference
-        let rng = unsafe { &mut *self.rng.get() };
+        let (_guard, rng) = self.rng.borrow();
         rng.try_fill_bytes(dest)
     }
 }
@@ -139,4 +272,28 @@ mod test {
         r.gen::<i32>();
         assert_eq!(r.gen_range(0..1), 0);
     }
+
+    #[test]
+    fn test_seed_current_thread() {
+        use super::ThreadRng;
+        use crate::rngs::StdRng;
+        use crate::{Rng, SeedableRng};
+
+        let mut existing = crate::thread_rng();
+        ThreadRng::seed_current_thread(42);
+        let x: u64 = existing.gen();
+        let y: u64 = crate::thread_rng().gen();
+        assert_eq!(x, StdRng::seed_from_u64(42).gen::<u64>());
+
+        ThreadRng::seed_current_thread(42);
+        assert_eq!(crate::thread_rng().gen::<u64>(), x);
+        assert_eq!(existing.gen::<u64>(), y);
+
+        // Other threads are not affected
+        let z: u64 = std::thread::spawn(|| crate::thread_rng().gen()).join().unwrap();
+        assert_ne!(z, x);
+
+        ThreadRng::reset_current_thread();
+        assert_ne!(crate::thread_rng().gen::<u64>(), x);
+    }
 }
@@ -9,6 +9,7 @@
 #[cfg(feature="serde1")] use serde::{Serialize, Deserialize};
 use rand_core::impls::{next_u64_via_u32, fill_bytes_via_next};
 use rand_core::le::read_u32_into;
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
 use rand_core::{SeedableRng, RngCore, Error};
 
 /// A xoshiro128++ random number generator.
@@ -25,6 +26,42 @@ pub struct Xoshiro128PlusPlus {
     s: [u32; 4],
 }
 
+impl Xoshiro128PlusPlus {
+    /// Jump forward, equivalently to 2^64 calls to `next_u32()`.
+    ///
+    /// This can be used to generate 2^64 non-overlapping subsequences for
+    /// parallel computations.
+    pub fn jump(&mut self) {
+        self.jump_by(&[0x8764000b, 0xf542d2d3, 0x6fa035c3, 0x77f2db5b]);
+    }
+
+    /// Jump forward, equivalently to 2^96 calls to `next_u32()`.
+    ///
+    /// This can be used to generate 2^32 starting points, from each of which
+    /// `jump()` will generate 2^32 non-overlapping subsequences for parallel
+    /// distributed computations.
+    pub fn long_jump(&mut self) {
+        self.jump_by(&[0xb523952e, 0x0b6f099f, 0xccf5a0ef, 0x1c580662]);
+    }
+
+    /// Apply the jump polynomial with coefficients `jump` to the state.
+    fn jump_by(&mut self, jump: &[u32; 4]) {
+        let mut s = [0; 4];
+        for &j in jump {
+            for b in 0..32 {
+                if (j & 1 << b) != 0 {
+                    s[0] ^= self.s[0];
+                    s[1] ^= self.s[1];
+                    s[2] ^= self.s[2];
+                    s[3] ^= self.s[3];
+                }
+                self.next_u32();
+            }
+        }
+        self.s = s;
+    }
+}
+
 impl SeedableRng for Xoshiro128PlusPlus {
     type Seed = [u8; 16];
 
@@ -58,6 +95,34 @@ impl SeedableRng for Xoshiro128PlusPlus {
     }
 }
 
+/// The state consists of the state words, which must not all be zero. This is
+/// compatible with `rand_xoshiro::Xoshiro128PlusPlus`.
+impl SnapshotState for Xoshiro128PlusPlus {
+    const STATE_LEN: usize = 16;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        for &x in self.s.iter() {
+            w.write_u32(x);
+        }
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let mut s = [0; 4];
+        for x in s.iter_mut() {
+            *x = r.read_u32();
+        }
+        if s.iter().all(|&x| x == 0) {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Xoshiro128PlusPlus { s })
+    }
+}
+
+impl Snapshot for Xoshiro128PlusPlus {
+    const ALGORITHM: &'static str = "Xoshiro128PlusPlus";
+    const VERSION: u16 = 1;
+}
+
 impl RngCore for Xoshiro128PlusPlus {
     #[inline]
     fn next_u32(&mut self) -> u32 {
@@ -115,4 +180,25 @@ mod tests {
             assert_eq!(rng.next_u32(), e);
         }
     }
+
+    #[test]
+    fn reference_jump() {
+        let seed = [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0];
+        // These values were produced with the `jump` and `long_jump`
+        // functions of the reference implementation:
+        // http://xoshiro.di.unimi.it/xoshiro128plusplus.c
+        let mut rng = Xoshiro128PlusPlus::from_seed(seed);
+        rng.jump();
+        let expected = [3129740764, 111290574, 1158071106, 1835317750, 1792746261];
+        for &e in &expected {
+            assert_eq!(rng.next_u32(), e);
+        }
+
+        let mut rng = Xoshiro128PlusPlus::from_seed(seed);
+        rng.long_jump();
+        let expected = [2580293941, 2135890358, 163124449, 1843864296, 1015915734];
+        for &e in &expected {
+            assert_eq!(rng.next_u32(), e);
+        }
+    }
 }
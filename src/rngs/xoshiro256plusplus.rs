@@ -9,6 +9,7 @@
 #[cfg(feature="serde1")] use serde::{Serialize, Deserialize};
 use rand_core::impls::fill_bytes_via_next;
 use rand_core::le::read_u64_into;
+use rand_core::snapshot::{Snapshot, SnapshotError, SnapshotState, StateReader, StateWriter};
 use rand_core::{SeedableRng, RngCore, Error};
 
 /// A xoshiro256** random number generator.
@@ -25,6 +26,48 @@ pub struct Xoshiro256PlusPlus {
     s: [u64; 4],
 }
 
+impl Xoshiro256PlusPlus {
+    /// Jump forward, equivalently to 2^128 calls to `next_u64()`.
+    ///
+    /// This can be used to generate 2^128 non-overlapping subsequences for
+    /// parallel computations.
+    pub fn jump(&mut self) {
+        self.jump_by(&[
+            0x180ec6d33cfd0aba, 0xd5a61266f0c9392c,
+            0xa9582618e03fc9aa, 0x39abdc4529b1661c,
+        ]);
+    }
+
+    /// Jump forward, equivalently to 2^192 calls to `next_u64()`.
+    ///
+    /// This can be used to generate 2^64 starting points, from each of which
+    /// `jump()` will generate 2^64 non-overlapping subsequences for parallel
+    /// distributed computations.
+    pub fn long_jump(&mut self) {
+        self.jump_by(&[
+            0x76e15d3efefdcbbf, 0xc5004e441c522fb3,
+            0x77710069854ee241, 0x39109bb02acbe635,
+        ]);
+    }
+
+    /// Apply the jump polynomial with coefficients `jump` to the state.
+    fn jump_by(&mut self, jump: &[u64; 4]) {
+        let mut s = [0; 4];
+        for &j in jump {
+            for b in 0..64 {
+                if (j & 1 << b) != 0 {
+                    s[0] ^= self.s[0];
+                    s[1] ^= self.s[1];
+                    s[2] ^= self.s[2];
+                    s[3] ^= self.s[3];
+                }
+                self.next_u64();
+            }
+        }
+        self.s = s;
+    }
+}
+
 impl SeedableRng for Xoshiro256PlusPlus {
     type Seed = [u8; 32];
 
@@ -58,6 +101,34 @@ impl SeedableRng for Xoshiro256PlusPlus {
     }
 }
 
+/// The state consists of the state words, which must not all be zero. This is
+/// compatible with `rand_xoshiro::Xoshiro256PlusPlus`.
+impl SnapshotState for Xoshiro256PlusPlus {
+    const STATE_LEN: usize = 32;
+
+    fn write_state(&self, w: &mut StateWriter) {
+        for &x in self.s.iter() {
+            w.write_u64(x);
+        }
+    }
+
+    fn read_state(r: &mut StateReader) -> Result<Self, SnapshotError> {
+        let mut s = [0; 4];
+        for x in s.iter_mut() {
+            *x = r.read_u64();
+        }
+        if s.iter().all(|&x| x == 0) {
+            return Err(SnapshotError::InvalidState);
+        }
+        Ok(Xoshiro256PlusPlus { s })
+    }
+}
+
+impl Snapshot for Xoshiro256PlusPlus {
+    const ALGORITHM: &'static str = "Xoshiro256PlusPlus";
+    const VERSION: u16 = 1;
+}
+
 impl RngCore for Xoshiro256PlusPlus {
     #[inline]
     fn next_u32(&mut self) -> u32 {
@@ -119,4 +190,32 @@ mod tests {
             assert_eq!(rng.next_u64(), e);
         }
     }
+
+    #[test]
+    fn reference_jump() {
+        let seed = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0,
+                    3, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0];
+        // These values were produced with the `jump` and `long_jump`
+        // functions of the reference implementation:
+        // http://xoshiro.di.unimi.it/xoshiro256plusplus.c
+        let mut rng = Xoshiro256PlusPlus::from_seed(seed);
+        rng.jump();
+        let expected = [
+            17043750140134683703, 2364973248208838314, 13951431646535487319,
+            8066193832155293345, 10838999831620499216,
+        ];
+        for &e in &expected {
+            assert_eq!(rng.next_u64(), e);
+        }
+
+        let mut rng = Xoshiro256PlusPlus::from_seed(seed);
+        rng.long_jump();
+        let expected = [
+            13097851138432240629, 5869259491745178931, 2145365994275058833,
+            16694938170147227233, 755180411581300843,
+        ];
+        for &e in &expected {
+            assert_eq!(rng.next_u64(), e);
+        }
+    }
 }
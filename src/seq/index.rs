@@ -675,4 +675,38 @@ mod test {
             103718, 963490, 826426, 509103, 736396, 807036, 5327, 632573,
         ]); // rejection
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption_sample() {
+        use crate::test::consumption;
+
+        let do_test = |length, amount, expected: (u64, u64, u64)| {
+            assert_eq!(
+                consumption(410, |rng| {
+                    sample(rng, length, amount);
+                }),
+                expected,
+                "failed sampling {}, {}",
+                length,
+                amount
+            );
+        };
+
+        do_test(10, 6, (8, 0, 0)); // floyd
+        do_test(25, 10, (14, 0, 0)); // floyd
+        do_test(300, 8, (13, 0, 0)); // floyd
+        do_test(300, 80, (128, 0, 0)); // inplace
+        do_test(300, 180, (277, 0, 0)); // inplace
+        do_test(1000_000, 8, (8, 0, 0)); // floyd
+        do_test(1000_000, 180, (180, 0, 0)); // rejection
+
+        // One `f64` per item
+        assert_eq!(
+            consumption(410, |rng| {
+                sample_weighted(rng, 10, |i| (i + 1) as f64, 3).unwrap();
+            }),
+            (0, 10, 0)
+        );
+    }
 }
@@ -1231,6 +1231,56 @@ mod test {
         }
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption_slice() {
+        use crate::test::consumption;
+
+        let v: Vec<u32> = (0..100).collect();
+        assert_eq!(consumption(414, |rng| { v.choose(rng); }), (1, 0, 0));
+        assert_eq!(
+            consumption(414, |rng| { v.choose_multiple(rng, 8).count(); }),
+            (10, 0, 0)
+        );
+        assert_eq!(
+            consumption(414, |rng| { v.choose_weighted(rng, |x| x + 1).unwrap(); }),
+            (1, 0, 0)
+        );
+        assert_eq!(
+            consumption(414, |rng| {
+                v.choose_multiple_weighted(rng, 8, |x| (x + 1) as f64).unwrap().count();
+            }),
+            (0, 100, 0)
+        );
+        assert_eq!(consumption(414, |rng| v.clone().shuffle(rng)), (140, 0, 0));
+        assert_eq!(
+            consumption(414, |rng| { v.clone().partial_shuffle(rng, 8); }),
+            (10, 0, 0)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consumption_iterator() {
+        use crate::test::consumption;
+
+        assert_eq!(consumption(414, |rng| { (0..100u32).choose(rng); }), (1, 0, 0));
+        assert_eq!(
+            consumption(414, |rng| { (0..100u32).choose_stable(rng); }),
+            (148, 0, 0)
+        );
+        assert_eq!(
+            consumption(414, |rng| {
+                (0..100u32).choose_multiple_fill(rng, &mut [0; 8]);
+            }),
+            (130, 0, 0)
+        );
+        assert_eq!(
+            consumption(414, |rng| { (0..100u32).choose_multiple(rng, 8); }),
+            (130, 0, 0)
+        );
+    }
+
     #[test]
     #[cfg(feature = "std")]
     fn test_multiple_weighted_edge_cases() {
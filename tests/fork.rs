@@ -0,0 +1,107 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tests of fork protection which actually fork the process.
+
+#![cfg(all(target_os = "linux", feature = "std", feature = "std_rng", not(miri)))]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use rand::rngs::adapter::{fork_epoch, ForkGuard};
+use rand::rngs::StdRng;
+use rand::{thread_rng, RngCore, SeedableRng};
+
+// Fork, run `f` in the child and return its result to the parent.
+//
+// The child only does simple computations and system calls before `_exit`.
+// A panic in the child must not unwind into the copy of the test harness, so
+// the child exits without a result instead.
+fn in_child<F: FnOnce() -> [u64; 4]>(f: F) -> [u64; 4] {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    match unsafe { libc::fork() } {
+        -1 => panic!("fork failed"),
+        0 => {
+            let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+                Ok(result) => result,
+                Err(_) => unsafe { libc::_exit(1) },
+            };
+            let mut bytes = [0u8; 32];
+            for (chunk, x) in bytes.chunks_mut(8).zip(result.iter()) {
+                chunk.copy_from_slice(&x.to_le_bytes());
+            }
+            unsafe {
+                libc::write(fds[1], bytes.as_ptr() as *const libc::c_void, bytes.len());
+                libc::_exit(0);
+            }
+        }
+        pid => {
+            let mut bytes = [0u8; 32];
+            let n = unsafe {
+                libc::close(fds[1]);
+                libc::read(fds[0], bytes.as_mut_ptr() as *mut libc::c_void, bytes.len())
+            };
+            let mut status = 0;
+            unsafe {
+                libc::close(fds[0]);
+                libc::waitpid(pid, &mut status, 0);
+            }
+            assert_eq!(n, 32, "no result from child");
+            let mut result = [0u64; 4];
+            for (x, chunk) in result.iter_mut().zip(bytes.chunks(8)) {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(chunk);
+                *x = u64::from_le_bytes(buf);
+            }
+            result
+        }
+    }
+}
+
+// `ThreadRng` only checks for a fork when generating a new block, so skip
+// past the buffered values.
+fn last_of_many<R: RngCore>(rng: &mut R) -> u64 {
+    (0..64).map(|_| rng.next_u64()).last().unwrap()
+}
+
+// All checks are in one test, as forking while other test threads run is
+// best avoided.
+#[test]
+fn fork_reseeds() {
+    let mut guarded = ForkGuard::new(StdRng::seed_from_u64(1));
+    let mut unguarded = StdRng::seed_from_u64(1);
+    let mut thread = thread_rng();
+    thread.next_u32(); // make sure the thread-local generator exists
+    let epoch = fork_epoch() as u64;
+
+    let child = in_child(|| {
+        [
+            fork_epoch() as u64,
+            guarded.next_u64(),
+            unguarded.next_u64(),
+            last_of_many(&mut thread),
+        ]
+    });
+    let parent = [
+        fork_epoch() as u64,
+        guarded.next_u64(),
+        unguarded.next_u64(),
+        last_of_many(&mut thread),
+    ];
+
+    // The epoch only changes in the child
+    assert_eq!(parent[0], epoch);
+    assert_eq!(child[0], epoch.wrapping_add(1));
+    // Without protection, both processes generate the same values
+    assert_eq!(child[2], parent[2]);
+    // `ForkGuard` and `ThreadRng` are reseeded in the child
+    assert_ne!(child[1], parent[1]);
+    assert_ne!(child[3], parent[3]);
+    // but not in the parent
+    assert_eq!(parent[1], StdRng::seed_from_u64(1).next_u64());
+}
@@ -0,0 +1,55 @@
+// Copyright 2021 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tests of `ThreadRng` with an installed default entropy source.
+//!
+//! The default source is global to the process, so these tests run in their
+//! own binary.
+
+#![cfg(all(feature = "std", feature = "std_rng"))]
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::panic::{self, AssertUnwindSafe};
+
+use rand::entropy::set_default_source;
+use rand::rngs::{OsRng, ThreadRng};
+use rand::{thread_rng, Error, RngCore};
+
+static REENTER: AtomicBool = AtomicBool::new(false);
+
+// A source which uses `ThreadRng` itself when `REENTER` is set.
+fn reentrant_source(dest: &mut [u8]) -> Result<(), Error> {
+    if REENTER.load(Ordering::SeqCst) {
+        thread_rng().next_u32();
+    }
+    OsRng.try_fill_bytes(dest)
+}
+
+#[test]
+fn reentrant_use_panics() {
+    // Reseed on every block
+    ThreadRng::set_reseed_threshold(1);
+    set_default_source(reentrant_source);
+    let mut rng = thread_rng();
+    rng.next_u32();
+
+    REENTER.store(true, Ordering::SeqCst);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        for _ in 0..1000 {
+            rng.next_u32();
+        }
+    }));
+    let msg = *result.unwrap_err().downcast::<&str>().unwrap();
+    assert!(msg.contains("re-entrantly"), "{}", msg);
+
+    // The generator is usable again after the panic
+    REENTER.store(false, Ordering::SeqCst);
+    rng.next_u32();
+    ThreadRng::seed_current_thread(1);
+    rng.next_u32();
+}